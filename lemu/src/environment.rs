@@ -0,0 +1,66 @@
+/// Supplies the environment globals (`@thisx`, `@ipt`, ...), which are read once, when the program is built.
+///
+/// Every method has a default, so only override what you need.
+/// ```
+/// # use lemu::{Environment, Executor};
+/// struct World;
+/// impl Environment for World {
+///     fn this_x(&self) -> f64 {
+///         12.0
+///     }
+/// }
+/// let mut lex = Executor::with_output(vec![])
+///     .environment(World)
+///     .program("print @thisx")
+///     .expect("program ok");
+/// lex.run();
+/// assert_eq!(lex.output().output.unwrap(), b"12");
+/// ```
+pub trait Environment {
+    /// `@thisx`, the x position of this processor. Defaults to `0`.
+    fn this_x(&self) -> f64 {
+        0.0
+    }
+
+    /// `@thisy`, the y position of this processor. Defaults to `0`.
+    fn this_y(&self) -> f64 {
+        0.0
+    }
+
+    /// `@ipt`, the instructions per tick of this processor. Defaults to `8` (a logic processor).
+    fn ipt(&self) -> f64 {
+        8.0
+    }
+
+    /// `@mapw`, the width of the map. Defaults to `0`.
+    fn map_width(&self) -> f64 {
+        0.0
+    }
+
+    /// `@maph`, the height of the map. Defaults to `0`.
+    fn map_height(&self) -> f64 {
+        0.0
+    }
+
+    /// `@server`, whether this is running on a server. Defaults to `true` (as in singleplayer).
+    fn server(&self) -> bool {
+        true
+    }
+}
+
+/// The default environment.
+impl Environment for () {}
+
+impl dyn Environment {
+    pub(crate) fn global(&self, name: &str) -> Option<f64> {
+        Some(match name {
+            "@thisx" => self.this_x(),
+            "@thisy" => self.this_y(),
+            "@ipt" => self.ipt(),
+            "@mapw" => self.map_width(),
+            "@maph" => self.map_height(),
+            "@server" => self.server().into(),
+            _ => return None,
+        })
+    }
+}
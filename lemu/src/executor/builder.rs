@@ -8,9 +8,11 @@ use super::{
 use crate::{
     code::Code,
     debug::info::DebugInfo,
+    environment::Environment,
     instructions::{DrawInstr, Instr},
     lexer::Token,
     memory::{LRegistry, LVar},
+    rng::Rng,
 };
 
 /// for internal use by [parser](crate::parser) only
@@ -24,10 +26,17 @@ pub struct ExecutorBuilderInternal<'v, W: Wr> {
     instruction_limit: Limit,
     pub(crate) mem: Vec<LVar<'v>>,
     pub(crate) debug_info: DebugInfo<'v>,
+    rng: Rng,
+    pub(crate) environment: Box<dyn Environment>,
 }
 
 impl<'s, W: Wr> ExecutorBuilderInternal<'s, W> {
-    pub(crate) fn new(w: Option<W>, d: Vec<Image<Vec<u8>, 4>>) -> Self {
+    pub(crate) fn new(
+        w: Option<W>,
+        d: Vec<Image<Vec<u8>, 4>>,
+        rng: Rng,
+        environment: Box<dyn Environment>,
+    ) -> Self {
         Self {
             output: w,
             displays: d
@@ -41,6 +50,8 @@ impl<'s, W: Wr> ExecutorBuilderInternal<'s, W> {
             instruction_limit: Limit::Unlimited,
             mem: Vec::with_capacity(64),
             debug_info: DebugInfo::default(),
+            rng,
+            environment,
         }
     }
 
@@ -135,6 +146,7 @@ impl<'s, W: Wr> ExecutorBuilderInternal<'s, W> {
             debug_info,
             cells,
             mem,
+            rng,
             ..
         } = self;
         Executor {
@@ -151,10 +163,12 @@ impl<'s, W: Wr> ExecutorBuilderInternal<'s, W> {
                     buffer: VecDeque::new(),
                 },
                 output,
+                rng,
             },
             instructions_ran: 0,
             debug_info,
             program,
+            source: None,
         }
     }
 }
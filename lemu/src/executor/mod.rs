@@ -10,10 +10,11 @@ use super::{
     instructions::{DrawInstr, Flow, Frozen, Instr, LInstruction},
     lexer::Token,
     memory::{LAddress, LRegistry, LVar},
+    rng::Rng,
 };
 pub use builder::ExecutorBuilderInternal;
 use fimg::Image;
-use std::{collections::VecDeque, io::Write, num::NonZeroUsize};
+use std::{collections::VecDeque, io::Write, num::NonZeroUsize, sync::Arc};
 
 #[derive(Debug, Copy, Clone, Default)]
 pub struct Display(pub usize);
@@ -117,6 +118,9 @@ pub struct Executor<'varnames, W: Write> {
     /// Counter for the number of instructions we have run so far.
     pub instructions_ran: usize,
     debug_info: DebugInfo<'varnames>,
+    /// the source of a [owned](crate::ExecutorBuilder::program_owned) executor, which everything above borrows from.
+    /// (must be dropped last)
+    pub(crate) source: Option<Arc<str>>,
 }
 
 impl<W: Write> std::fmt::Display for Executor<'_, W> {
@@ -155,6 +159,8 @@ pub struct ExecutorContext<'strings, W: Write> {
     pub output: Option<W>,
     /// Counter for the number of iterations we have run so far.
     pub iterations: usize,
+    /// for `op rand`
+    pub rng: Rng,
 }
 
 /// State of a display.
@@ -173,10 +179,11 @@ impl DisplayState {
 }
 
 impl Default for DisplayState {
+    /// same as the game: white, with a stroke of 1.
     fn default() -> Self {
         Self {
-            color: Default::default(),
-            stroke: 5.0,
+            color: (255, 255, 255, 255),
+            stroke: 1.0,
         }
     }
 }
@@ -230,11 +237,7 @@ pub struct Output<W: Write> {
 
 impl<'s, W: Write> Executor<'s, W> {
     /// Consume this executor, returning all output.
-    pub fn output(mut self) -> Output<W> {
-        for (display, _) in &mut *self.inner.display.displays {
-            // TODO make the instructions draw flipped-ly
-            display.flip_v();
-        }
+    pub fn output(self) -> Output<W> {
         Output {
             output: self.inner.output,
             displays: self.inner.display.displays,
@@ -3,16 +3,32 @@ use crate::{
     debug::{info::DebugInfo, printable::Printable},
     executor::{Display, DisplayState, ExecutorContext},
     memory::{LAddress, LRegistry, LVar},
+    raster,
 };
 use enum_dispatch::enum_dispatch;
 use fimg::Image;
 use std::fmt::{self, Display as Disp};
 use vecto::Vec2;
 
+#[cfg(feature = "diagnose")]
 pub const INSTRS: &[&str] = &[
     "clear", "color", "col", "stroke", "line", "rect", "lineRect", "triangle", "poly", "linePoly",
 ];
 
+/// `LogicDisplay.maxSides`
+const MAX_SIDES: i32 = 25;
+
+/// The game truncates draw arguments, and packs them into 10 bit signed integers.
+const fn int(n: f64) -> i32 {
+    let v = n as i32;
+    let magnitude = (v.unsigned_abs() & 0b1_1111_1111) as i32;
+    if v < 0 { -magnitude } else { magnitude }
+}
+
+fn sides(n: f64) -> usize {
+    int(n).clamp(0, MAX_SIDES) as usize
+}
+
 #[enum_dispatch]
 pub trait Apply: Disp {
     fn apply(self, image: Image<&mut [u8], 4>, state: &mut DisplayState);
@@ -107,9 +123,7 @@ pub struct ClearD((u8, u8, u8));
 impl Apply for ClearD {
     fn apply(self, mut image: Image<&mut [u8], 4>, _: &mut DisplayState) {
         let (r, g, b) = self.0;
-        for [r2, g2, b2, a2] in image.chunked_mut() {
-            (*r2, *b2, *g2, *a2) = (r, g, b, 255);
-        }
+        raster::clear(&mut image, [r, g, b]);
     }
 }
 
@@ -230,7 +244,7 @@ impl Apply for SetStrokeD {
 
 impl Frozen<SetStrokeD> for SetStroke {
     fn freeze(&self, mem: &LRegistry<'_>) -> Option<SetStrokeD> {
-        mem.get(self.size).num().map(SetStrokeD)
+        Some(SetStrokeD(f64::from(int(mem.get(self.size).num()?))))
     }
 }
 
@@ -250,7 +264,7 @@ pub type Point = (LAddress, LAddress);
 #[rustfmt::skip]
 macro_rules! point {
     ($mem:ident@$point:expr) => {
-        ($mem.get($point.0).num()?, $mem.get($point.1).num()?)
+        (int($mem.get($point.0).num()?), int($mem.get($point.1).num()?))
     }
 }
 
@@ -260,6 +274,12 @@ macro_rules! map {
         ($fn(a), $fn(b))
     }};
 }
+
+macro_rules! num {
+    ($mem:ident@$n:expr) => {
+        int(get_num!($mem.get($n)))
+    };
+}
 #[derive(Debug, Copy, Clone)]
 
 pub struct Line {
@@ -272,7 +292,7 @@ pub struct LineD(Vec2, Vec2);
 
 impl Apply for LineD {
     fn apply(self, mut image: Image<&mut [u8], 4>, state: &mut DisplayState) {
-        image.thick_line(self.0, self.1, state.stroke as f32, state.col());
+        raster::line(&mut image, self.0, self.1, state.stroke as f32, state.col());
     }
 }
 
@@ -314,22 +334,27 @@ pub struct RectFilled {
 }
 
 #[derive(Debug)]
-pub struct RectFilledD((u32, u32), (u32, u32));
+pub struct RectFilledD((i32, i32), (i32, i32));
 
 impl Apply for RectFilledD {
     fn apply(self, mut image: Image<&mut [u8], 4>, state: &mut DisplayState) {
-        image.filled_box(self.0, self.1.0, self.1.1, state.col());
+        let Self((x, y), (w, h)) = self;
+        raster::rect(
+            &mut image,
+            x as f32,
+            y as f32,
+            w as f32,
+            h as f32,
+            state.col(),
+        );
     }
 }
 
 impl Frozen<RectFilledD> for RectFilled {
     fn freeze(&self, mem: &LRegistry<'_>) -> Option<RectFilledD> {
         Some(RectFilledD(
-            map!(point!(mem@self.position), |n| n as u32),
-            (
-                get_num!(mem.get(self.width)) as u32,
-                get_num!(mem.get(self.height)) as u32,
-            ),
+            point!(mem@self.position),
+            (num!(mem@self.width), num!(mem@self.height)),
         ))
     }
 }
@@ -383,15 +408,15 @@ impl Printable for RectBordered {
 }
 
 #[derive(Debug)]
-pub struct RectBorderedD((u32, u32), (u32, u32));
+pub struct RectBorderedD((i32, i32), (i32, i32));
 
 impl Apply for RectBorderedD {
     fn apply(self, mut image: Image<&mut [u8], 4>, state: &mut DisplayState) {
-        image.stroked_box(
-            self.0,
-            self.1.0,
-            self.1.1,
-            state.stroke.round() as u32,
+        let Self((x, y), (w, h)) = self;
+        raster::line_rect(
+            &mut image,
+            (x as f32, y as f32, w as f32, h as f32),
+            state.stroke as f32,
             state.col(),
         );
     }
@@ -400,11 +425,8 @@ impl Apply for RectBorderedD {
 impl Frozen<RectBorderedD> for RectBordered {
     fn freeze(&self, mem: &LRegistry<'_>) -> Option<RectBorderedD> {
         Some(RectBorderedD(
-            map!(point!(mem@self.position), |n| n as u32),
-            (
-                get_num!(mem.get(self.width)) as u32,
-                get_num!(mem.get(self.height)) as u32,
-            ),
+            point!(mem@self.position),
+            (num!(mem@self.width), num!(mem@self.height)),
         ))
     }
 }
@@ -420,7 +442,7 @@ pub struct TriangleD(Vec2, Vec2, Vec2);
 
 impl Apply for TriangleD {
     fn apply(self, mut image: Image<&mut [u8], 4>, state: &mut DisplayState) {
-        image.tri::<f32>(self.0, self.1, self.2, state.col());
+        raster::tri(&mut image, self.0, self.1, self.2, state.col());
     }
 }
 
@@ -469,49 +491,33 @@ pub struct Poly {
 }
 
 #[derive(Debug)]
-pub enum PolyD {
-    Poly(Vec2, usize, f32, f32),
-    Circle((i32, i32), i32),
-}
+/// there are no circles: the sides are capped at [`MAX_SIDES`].
+pub struct PolyD(Vec2, usize, f32, f32);
 
 impl Apply for PolyD {
     fn apply(self, mut image: Image<&mut [u8], 4>, state: &mut DisplayState) {
-        match self {
-            PolyD::Poly(pos, sides, radius, rotation) => {
-                image.poly(pos, sides, radius, rotation, state.col())
-            }
-            PolyD::Circle(pos, radius) => image.circle(pos, radius, state.col()),
-        }
+        raster::poly(&mut image, self.0, self.1, self.2, self.3, state.col());
     }
 }
 
 impl Frozen<PolyD> for Poly {
     fn freeze(&self, mem: &LRegistry<'_>) -> Option<PolyD> {
-        let sides = get_num!(mem.get(self.sides)).round() as usize;
-        Some(if sides < 90 {
-            PolyD::Poly(
-                map!(point!(mem@self.pos), |n| n as f32).into(),
-                sides,
-                get_num!(mem.get(self.radius)) as f32,
-                get_num!(mem.get(self.rot)) as f32,
-            )
-        } else {
-            PolyD::Circle(
-                map!(point!(mem@self.pos), |n: f64| n.round() as i32),
-                get_num!(mem.get(self.radius)).round() as i32,
-            )
-        })
+        Some(PolyD(
+            map!(point!(mem@self.pos), |n| n as f32).into(),
+            sides(get_num!(mem.get(self.sides))),
+            num!(mem@self.radius) as f32,
+            num!(mem@self.rot) as f32,
+        ))
     }
 }
 
 impl Disp for PolyD {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            PolyD::Poly(Vec2 { x, y }, sides, radius, rot) => {
-                write!(f, "draw poly {x} {y} {sides} {radius} {rot}")
-            }
-            PolyD::Circle((x, y), sides) => write!(f, "draw poly {x} {y} {sides}"),
-        }
+        write!(
+            f,
+            "draw poly {} {} {} {} {}",
+            self.0.x, self.0.y, self.1, self.2, self.3
+        )
     }
 }
 
@@ -535,19 +541,19 @@ pub struct LinePoly {
 }
 
 #[derive(Debug)]
-/// border_Circle doesnt let you specify a stroke
 pub struct LinePolyD(Vec2, usize, f32, f32);
 
 impl Apply for LinePolyD {
     fn apply(self, mut image: Image<&mut [u8], 4>, state: &mut DisplayState) {
-        image.border_poly(
+        raster::line_poly(
+            &mut image,
             self.0,
             self.1,
             self.2,
             self.3,
             state.stroke as f32,
             state.col(),
-        )
+        );
     }
 }
 
@@ -555,9 +561,9 @@ impl Frozen<LinePolyD> for LinePoly {
     fn freeze(&self, mem: &LRegistry<'_>) -> Option<LinePolyD> {
         Some(LinePolyD(
             map!(point!(mem@self.pos), |n| n as f32).into(),
-            get_num!(mem.get(self.sides)).round() as usize,
-            get_num!(mem.get(self.radius)) as f32,
-            get_num!(mem.get(self.rot)) as f32,
+            sides(get_num!(mem.get(self.sides))),
+            num!(mem@self.radius) as f32,
+            num!(mem@self.rot) as f32,
         ))
     }
 }
@@ -33,6 +33,7 @@ use super::{
     memory::{LAddress, LVar},
 };
 
+#[cfg(feature = "diagnose")]
 pub const OPS: &[&str] = &[
     "equal",
     "notEqual",
@@ -99,6 +100,7 @@ pub enum Instr {
     AlwaysJump(AlwaysJump),
     Set(Set),
     Op1(Op1),
+    Rand(Rand),
     Read(io::Read),
     Write(io::Write),
     DrawFlush(draw::Flush),
@@ -117,6 +119,7 @@ impl Printable for Instr {
             Self::AlwaysJump(i) => i.print(info, f),
             Self::Set(i) => i.print(info, f),
             Self::Op1(i) => i.print(info, f),
+            Self::Rand(i) => i.print(info, f),
             Self::Read(i) => i.print(info, f),
             Self::Write(i) => i.print(info, f),
             Self::DrawFlush(i) => i.print(info, f),
@@ -248,6 +251,26 @@ impl Printable for Op1 {
     }
 }
 
+#[derive(Debug, Copy, Clone)]
+pub struct Rand {
+    pub(crate) range: LAddress,
+    pub(crate) out: LAddress,
+}
+
+impl LInstruction for Rand {
+    fn run<W: Write>(&self, exec: &mut ExecutorContext<'_, W>) -> Flow {
+        let range = exec.get(self.range).num().unwrap_or(0.0);
+        exec.memory[self.out] = LVar::Num(exec.rng.next_f64() * range);
+        Flow::Continue
+    }
+}
+
+impl Printable for Rand {
+    fn print(&self, info: &DebugInfo<'_>, f: &mut impl fmt::Write) -> fmt::Result {
+        write!(f, "op rand {} {}", info[self.out], info[self.range])
+    }
+}
+
 #[derive(Debug, Copy, Clone)]
 pub struct Op2 {
     op: for<'v> fn(&LVar<'v>, &LVar<'v>) -> f64,
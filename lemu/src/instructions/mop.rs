@@ -6,7 +6,6 @@ super::op_enum! { pub enum MathOp1 {
     Not,
     Log,
     Abs,
-    Rand,
     Ceil,
     Sqrt,
     Sin,
@@ -38,9 +37,6 @@ fn not(x: &LVar<'_>) -> f64 {
 }
 num!(log f64::ln);
 num!(abs f64::abs);
-const fn rand(_: &LVar<'_>) -> f64 {
-    4.0
-}
 num!(ceil f64::ceil);
 num!(sqrt f64::sqrt);
 num!(sin f64::sin);
@@ -56,7 +52,6 @@ super::op_impl!(MathOp1, ptr type = for<'v> fn(&LVar<'v>) -> f64 {
     Not => not,
     Log => log,
     Abs => abs,
-    Rand => rand,
     Ceil => ceil,
     Sqrt => sqrt,
     Sin => sin,
@@ -115,6 +115,11 @@ impl<'s> Lexer<'s> {
     pub fn span(&self) -> Span {
         self.inner.span()
     }
+
+    /// the source of the last token taken
+    pub fn slice(&self) -> &'s str {
+        self.inner.slice()
+    }
 }
 
 #[allow(dead_code)]
@@ -1,5 +1,5 @@
 //! crate for [MLOG](https://mindustrygame.github.io/wiki/logic/0-introduction/#what-is-mindustry-logic) emulation.
-#![feature(let_chains)]
+#![feature(let_chains, if_let_guard, try_blocks, yeet_expr)]
 #![allow(clippy::redundant_closure_call)]
 // yeah so like well you see i kinda well kinda have to yes but sorta
 #![allow(clippy::fn_address_comparisons)]
@@ -15,18 +15,22 @@
 )]
 pub(crate) mod code;
 mod debug;
+mod environment;
 mod executor;
 mod instructions;
 mod lexer;
 mod memory;
 mod parser;
+mod raster;
+mod rng;
 
-use std::io::Write;
+use std::{io::Write, sync::Arc};
 
+pub use environment::Environment;
 pub use executor::{Executor, Output};
 use executor::{ExecutorBuilderInternal, Limit};
 use fimg::Image;
-pub use parser::Error;
+pub use parser::{Error, OwnedErrors};
 
 impl<W: Write + Default> Executor<'_, W> {
     /// Create a new [`ExecutorBuilder`]
@@ -45,6 +49,8 @@ impl<W: Write> Executor<'_, W> {
             output: Some(w),
             instruction_limit: Limit::Unlimited,
             iteration_limit: Limit::limited(1),
+            seed: None,
+            environment: Box::new(()),
         }
     }
 }
@@ -60,6 +66,8 @@ pub struct ExecutorBuilder<W: Write> {
     displays: Vec<Image<Vec<u8>, 4>>,
     instruction_limit: Limit,
     iteration_limit: Limit,
+    seed: Option<u64>,
+    environment: Box<dyn Environment>,
 }
 
 impl<W: Write> Default for ExecutorBuilder<W> {
@@ -69,6 +77,8 @@ impl<W: Write> Default for ExecutorBuilder<W> {
             displays: Vec::new(),
             instruction_limit: Limit::Unlimited,
             iteration_limit: Limit::limited(1),
+            seed: None,
+            environment: Box::new(()),
         }
     }
 }
@@ -109,6 +119,24 @@ impl<W: Write> ExecutorBuilder<W> {
         }
     }
 
+    /// Seed the random number generator used by `op rand`, so that runs are reproducible.
+    ///
+    /// If unset, it is seeded from the clock.
+    pub fn seed(self, seed: u64) -> Self {
+        Self {
+            seed: Some(seed),
+            ..self
+        }
+    }
+
+    /// Supply the environment globals (`@thisx`, `@ipt`, ...).
+    pub fn environment(self, environment: impl Environment + 'static) -> Self {
+        Self {
+            environment: Box::new(environment),
+            ..self
+        }
+    }
+
     /// Add a small (`80x80`) logic display.
     pub fn display(self) -> Self {
         let mut d = self.displays;
@@ -133,15 +161,18 @@ impl<W: Write> ExecutorBuilder<W> {
     ///
     /// # Errors
     ///
-    /// errors if the code is malformed.
-    pub fn program(self, program: &str) -> Result<Executor<'_, W>, Error<'_>> {
+    /// errors if the code is malformed, with every [`Error`] found (the parser recovers at the next line).
+    pub fn program(self, program: &str) -> Result<Executor<'_, W>, Vec<Error<'_>>> {
         let Self {
             output,
             displays,
             instruction_limit,
             iteration_limit,
+            seed,
+            environment,
         } = self;
-        let mut executor = ExecutorBuilderInternal::new(output, displays);
+        let rng = seed.map_or_else(rng::Rng::random, rng::Rng::new);
+        let mut executor = ExecutorBuilderInternal::new(output, displays, rng, environment);
         executor
             .inslimit(instruction_limit)
             .itrlimit(iteration_limit);
@@ -150,11 +181,40 @@ impl<W: Write> ExecutorBuilder<W> {
         parser::parse(lexer::lex(program), &mut executor)?;
         Ok(executor.finish())
     }
+
+    /// Build a [`Executor`] that owns its code, so it can outlive the source (and be sent across threads).
+    /// ```
+    /// # use lemu::Executor;
+    /// let code = std::fs::read_to_string("src/hello.mlog").unwrap();
+    /// let mut lex = Executor::with_output(vec![]).program_owned(code).expect("program ok");
+    /// std::thread::spawn(move || lex.run()).join().unwrap();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// errors if the code is malformed, with every [`Error`] found, and the source they point into.
+    pub fn program_owned(
+        self,
+        program: impl Into<Arc<str>>,
+    ) -> Result<Executor<'static, W>, OwnedErrors> {
+        let source: Arc<str> = program.into();
+        // SAFETY: the executor (or the errors) keep the source alive (its allocation never moves),
+        // and dont hand out anything that borrows from it for longer than themselves.
+        let program = unsafe { &*std::ptr::addr_of!(*source) };
+        match self.program(program) {
+            Ok(mut executor) => {
+                executor.source = Some(source);
+                Ok(executor)
+            }
+            Err(errors) => Err(OwnedErrors { errors, source }),
+        }
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use lexer::Token;
 
     macro_rules! test {
         (run $fn:ident.mlog $($times:literal times)?;
@@ -162,7 +222,7 @@ mod test {
         $(cell[$cell_n:literal][$cell_index:literal] = $what:literal $(;)?)?
         ) => {
             #[test]
-            fn $fn() -> Result<(), Error<'static>> {
+            fn $fn() -> Result<(), Vec<Error<'static>>> {
                 let v = vec![];
                 let mut lex = Executor::with_output(v)
                     .unlimit_instructions()
@@ -182,4 +242,135 @@ mod test {
     test!(run numbers.mlog; output = b"121212");
     test!(run celliterate.mlog 5 times; cell[0][0] = 5.0);
     test!(run hello.mlog; output = b"hello world");
+
+    #[test]
+    fn owned() {
+        const fn send<T: Send>(_: &T) {}
+        let mut lex = Executor::with_output(vec![])
+            .program_owned(String::from(include_str!("hello.mlog")))
+            .unwrap();
+        send(&lex);
+        let out = std::thread::spawn(move || {
+            lex.run();
+            lex.output().output.unwrap()
+        });
+        assert_eq!(out.join().unwrap(), b"hello world");
+
+        let errors = Executor::with_output(vec![])
+            .program_owned("draw house")
+            .err()
+            .unwrap();
+        assert!(matches!(
+            errors.errors(),
+            [Error::UnsupportedImageOp("house", _)]
+        ));
+    }
+
+    #[test]
+    fn seeded() {
+        let run = |seed| {
+            let mut lex = Executor::with_output(vec![])
+                .seed(seed)
+                .program("op rand x 100\nprint x\nop rand x 100\nprint x")
+                .unwrap();
+            lex.run();
+            lex.output().output.unwrap()
+        };
+        assert_eq!(run(4), run(4));
+        assert_ne!(run(4), run(5));
+    }
+
+    #[test]
+    fn globals() {
+        struct World;
+        impl crate::Environment for World {
+            fn this_x(&self) -> f64 {
+                12.0
+            }
+        }
+        let mut lex = Executor::with_output(vec![])
+            .environment(World)
+            .program("print @thisx\nprint \"@thisx\"\nset x @thisx\nprint x")
+            .unwrap();
+        lex.run();
+        assert_eq!(lex.output().output.unwrap(), b"12@thisx12");
+    }
+
+    #[test]
+    fn display_origin() {
+        let mut lex = Executor::with_output(vec![])
+            .display()
+            .program("draw rect 0 0 2 1\ndrawflush display1")
+            .unwrap();
+        lex.run();
+        let (d, _) = &lex.output().displays[0];
+        let px = d.flatten();
+        // y goes up, from the bottom left
+        assert_eq!(px[79 * 80..79 * 80 + 3], [[255; 4], [255; 4], [0; 4]]);
+        assert_eq!(px[78 * 80], [0; 4]);
+    }
+
+    /// draws `snapshots/$fn.mlog`, and compares it to `snapshots/$fn.png` (see `snapshots/README.md`)
+    macro_rules! snapshot {
+        ($fn:ident) => {
+            #[test]
+            fn $fn() {
+                let mut lex = Executor::with_output(vec![])
+                    .display()
+                    .program(include_str!(concat!(
+                        "snapshots/",
+                        stringify!($fn),
+                        ".mlog"
+                    )))
+                    .unwrap();
+                lex.run();
+                let (d, _) = &lex.output().displays[0];
+                let path = concat!(
+                    env!("CARGO_MANIFEST_DIR"),
+                    "/src/snapshots/",
+                    stringify!($fn),
+                    ".png"
+                );
+                if std::env::var_os("LEMU_BLESS").is_some() {
+                    d.save(path);
+                    return;
+                }
+                let snapshot = fimg::Image::<Vec<u8>, 4>::open(path);
+                assert_eq!(
+                    (d.width(), d.height()),
+                    (snapshot.width(), snapshot.height())
+                );
+                let wrong = d
+                    .flatten()
+                    .iter()
+                    .zip(snapshot.flatten())
+                    .filter(|(a, b)| a != b)
+                    .count();
+                assert_eq!(wrong, 0, "{wrong} pixels differ from {path}");
+            }
+        };
+    }
+
+    snapshot!(caps);
+    snapshot!(strokes);
+    snapshot!(polys);
+    snapshot!(blend);
+
+    #[test]
+    fn recovers() {
+        let errors = Executor::with_output(vec![])
+            .program("print\nop fish x 1 2\nset x 4\njump nowhere always\ndraw house\njump elsewhere always")
+            .err()
+            .unwrap();
+        assert!(matches!(
+            &errors[..],
+            [
+                Error::ExpectedVar(Token::Newline, _),
+                Error::ExpectedOp(..),
+                Error::UnsupportedImageOp("house", _),
+                Error::LabelNotFound("nowhere", _),
+                Error::LabelNotFound("elsewhere", _),
+            ]
+        ));
+    }
 }
@@ -15,8 +15,10 @@ fn main() -> ExitCode {
             .program(&f)
         {
             Ok(x) => x,
-            Err(e) => {
-                eprint!("{}", e.diagnose(&f));
+            Err(errors) => {
+                for e in errors {
+                    eprint!("{}", e.diagnose(&f));
+                }
                 return ExitCode::FAILURE;
             }
         };
@@ -1,7 +1,9 @@
+#[cfg(feature = "diagnose")]
 use super::tokstr;
 use crate::executor::Instruction;
 use crate::lexer::Token;
 use logos::Span;
+use std::sync::Arc;
 
 /// Errors returned when parsing fails.
 #[derive(thiserror::Error, Debug)]
@@ -149,7 +151,68 @@ pub enum Error<'s> {
     TooManyVariables(Span),
 }
 
+/// Errors returned when parsing a [owned](crate::ExecutorBuilder::program_owned) program fails.
+///
+/// Holds onto the source, as the errors borrow from it.
+#[derive(Debug)]
+pub struct OwnedErrors {
+    // must be dropped before the source
+    pub(crate) errors: Vec<Error<'static>>,
+    pub(crate) source: Arc<str>,
+}
+
+impl OwnedErrors {
+    /// The program that failed to parse.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Every error found.
+    pub fn errors(&self) -> &[Error<'_>] {
+        &self.errors
+    }
+
+    /// Produces a [`Error`](lerr::Error) for each error.
+    #[cfg(feature = "diagnose")]
+    pub fn diagnose(&self) -> impl Iterator<Item = lerr::Error<'_>> {
+        self.errors.iter().map(|e| e.diagnose(&self.source))
+    }
+}
+
 impl Error<'_> {
+    /// Where in the source this error occured. [`Error::UnexpectedEof`] has no span.
+    pub const fn span(&self) -> Option<&Span> {
+        match self {
+            Self::UnexpectedEof => None,
+            Self::ExpectedVar(_, s)
+            | Self::ExpectedIdent(_, s)
+            | Self::ExpectedJump(_, s)
+            | Self::ExpectedNum(_, s)
+            | Self::ExpectedOp(_, s)
+            | Self::ExpectedInt(_, s)
+            | Self::ExpectedString(_, s)
+            | Self::ExpectedBool(_, s)
+            | Self::ExpectedInstr(_, s)
+            | Self::LabelNotFound(_, s)
+            | Self::InvalidJump(_, s)
+            | Self::MemoryTooFar(_, s)
+            | Self::InvalidMemoryType(_, s)
+            | Self::InvalidDisplayType(_, s)
+            | Self::UnsupportedImageOp(_, s)
+            | Self::UnknownControlOp(_, s)
+            | Self::UnknownUnitControlOp(_, s)
+            | Self::UnknownUnitLocateOp(_, s)
+            | Self::UnknownGetBlockOp(_, s)
+            | Self::UnknownSetBlockOp(_, s)
+            | Self::UnknownRule(_, s)
+            | Self::UnknownCutscene(_, s)
+            | Self::UnknownFetchOp(_, s)
+            | Self::NoDisplay(_, s)
+            | Self::IndexOutOfBounds(_, _, s)
+            | Self::TooManyVariables(s) => Some(s),
+        }
+    }
+
     /// Produces a [`Error`](lerr::Error) from this error.
     #[cfg(feature = "diagnose")]
     pub fn diagnose<'s>(&self, source: &'s str) -> lerr::Error<'s> {
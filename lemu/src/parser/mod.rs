@@ -1,7 +1,7 @@
 use std::io::Write as Wr;
 
 mod error;
-pub use error::Error;
+pub use error::{Error, OwnedErrors};
 
 #[rustfmt::skip]
 macro_rules! three { ($a:expr) => { ($a, $a, $a) }; }
@@ -22,7 +22,7 @@ use super::{
         },
         io::{Print, Read, Write},
         AlwaysJump, ConditionOp, DynJump, End, Instr, Jump, MathOp1, MathOp2, Op1, Op2, PackColor,
-        Set, Stop,
+        Rand, Set, Stop,
     },
     lexer::{Lexer, Token},
     memory::{LAddress, LVar},
@@ -102,10 +102,14 @@ enum UJump {
     Always,
 }
 
+/// Parses the whole program, recovering at the next line after a error.
+///
+/// Returns every error encountered, in source order, with unresolvable labels and jumps at the end.
 pub fn parse<'source, W: Wr>(
     mut tokens: Lexer<'source>,
     executor: &mut ExecutorBuilderInternal<'source, W>,
-) -> Result<(), Error<'source>> {
+) -> Result<(), Vec<Error<'source>>> {
+    let mut errors = vec![];
     let mut used = 0u32;
     let mut mem: Vec<Option<&str>> = Vec::with_capacity(64); // maps &str to usize 
     let mut dbg_info: Vec<VarInfo> = Vec::with_capacity(64);
@@ -167,7 +171,7 @@ pub fn parse<'source, W: Wr>(
     }
     macro_rules! yeet {
         ($e:ident($($stuff:expr),+)) => {
-            return Err(Error::$e($($stuff,)+ tokens.span()))
+            do yeet Error::$e($($stuff,)+ tokens.span())
         };
     }
     #[rustfmt::skip] 
@@ -204,16 +208,16 @@ pub fn parse<'source, W: Wr>(
                 .parse::<usize>()
                 .map_err(|_| Error::ExpectedInt(t, n_span.clone()))?;
             if cell_n > 126 || cell_n == 0 {
-                return Err(Error::MemoryTooFar(cell_n, n_span));
+                do yeet Error::MemoryTooFar(cell_n, n_span);
             }
             match container {
                 "bank" => executor.bank(cell_n),
                 "cell" => executor.cell(cell_n),
                 _ => {
-                    return Err(Error::InvalidMemoryType(
+                    do yeet Error::InvalidMemoryType(
                         container,
                         tokens.span().start..tokens.span().end - out.len(),
-                    ));
+                    );
                 }
             }
         }};
@@ -232,6 +236,8 @@ pub fn parse<'source, W: Wr>(
             } else {
                 match tok {
                     Token::Num(n) => push!(const n),
+                    // (only `@name`: a quoted "@name" is just text)
+                    Token::String(s) if tokens.slice().starts_with('@') && let Some(n) = executor.environment.global(&s) => push!(const n),
                     Token::String(s) => push!(const s),
                     t => Err(err!(ExpectedVar(t))),
                 }
@@ -246,6 +252,8 @@ pub fn parse<'source, W: Wr>(
             } else {
                 match tok {
                     Token::Num(n) => push!(const n),
+                    // (only `@name`: a quoted "@name" is just text)
+                    Token::String(s) if tokens.slice().starts_with('@') && let Some(n) = executor.environment.global(&s) => push!(const n),
                     t => Err(err!(ExpectedNum(t))),
                 }
             }
@@ -263,548 +271,557 @@ pub fn parse<'source, W: Wr>(
         }
     }
     while let Some(token) = tokens.next() {
-        match token {
-            // # omg
-            Token::Comment(c) => executor.program.push(UPInstr::Comment(c)),
-            // label:
-            Token::Ident(v) if v.ends_with(':') => executor
-                .debug_info
-                .labels
-                .push((&v[..v.len() - 1], executor.next())),
-            // print "5"
-            Token::Print => {
-                let val = take_var!(tok!()?)?;
-                executor.add(Print { val });
-            }
-            // set x 4
-            Token::Set => {
-                let from = tok!()?;
-                if from == Token::Counter {
-                    let to = take_numvar!(tok!()?)?;
-                    executor.add(DynJump { to, proglen: 0 });
-                } else {
-                    let from = addr!(take_ident!(from)?)?;
-                    let to = take_var!(tok!()?)?;
-                    executor.add(Set { from, to });
+        let line: Result<(), Error<'source>> = try {
+            match token {
+                // # omg
+                Token::Comment(c) => executor.program.push(UPInstr::Comment(c)),
+                // label:
+                Token::Ident(v) if v.ends_with(':') => executor
+                    .debug_info
+                    .labels
+                    .push((&v[..v.len() - 1], executor.next())),
+                // print "5"
+                Token::Print => {
+                    let val = take_var!(tok!()?)?;
+                    executor.add(Print { val });
                 }
-            }
-            // stop
-            Token::Stop => {
-                executor.add(Stop {});
-            }
-            // jump start equal a b
-            Token::Jump => {
-                let tok = tok!()?;
-                // label jump
-                if let Some(i) = tokstr!(tok) {
-                    let span = tokens.span();
-                    let op = tok!()?;
-                    if op == Token::Always {
-                        executor.jmp();
-                        unfinished_jumps.push((UJump::Always, (i, span), executor.last()));
+                // set x 4
+                Token::Set => {
+                    let from = tok!()?;
+                    if from == Token::Counter {
+                        let to = take_numvar!(tok!()?)?;
+                        executor.add(DynJump { to, proglen: 0 });
                     } else {
-                        let op = op.try_into().map_err(|op| err!(ExpectedOp(op)))?;
-                        let a = take_var!(tok!()?)?;
-                        let b = take_var!(tok!()?)?;
-                        executor.jmp();
-                        unfinished_jumps.push((
-                            UJump::Sometimes { a, b, op },
-                            (i, span),
-                            executor.last(),
-                        ));
+                        let from = addr!(take_ident!(from)?)?;
+                        let to = take_var!(tok!()?)?;
+                        executor.add(Set { from, to });
                     }
-                } else if let Ok(n) = take_int!(tok.clone()) {
-                    // SAFETY: we check at the end of the block that it is valid
-                    let to = unsafe { Instruction::new(n) };
-                    let op = tok!()?;
-                    if op == Token::Always {
-                        executor.add(AlwaysJump { to });
-                    } else {
-                        let op = op.try_into().map_err(|op| err!(ExpectedOp(op)))?;
-                        let a = take_var!(tok!()?)?;
-                        let b = take_var!(tok!()?)?;
-                        executor.add(Jump::new(op, to, a, b));
-                    }
-                } else {
-                    yeet!(ExpectedJump(tok));
-                };
-            }
-            // op add c 1 2
-            Token::Op => {
-                let op = tok!()?;
-                if let Ok(op) = MathOp1::try_from(op.clone()) {
-                    // assigning to a var is useless but legal
-                    let out = take_numvar!(tok!()?)?;
-                    let x = take_numvar!(tok!()?)?;
-                    executor.add(Op1::new(op, x, out));
-                } else if let Ok(op) = MathOp2::try_from(op.clone()) {
-                    let out = take_numvar!(tok!()?)?;
-                    let a = take_numvar!(tok!()?)?;
-                    let b = take_numvar!(tok!()?)?;
-                    executor.add(Op2::new(op, a, b, out));
-                } else {
-                    yeet!(ExpectedOp(op));
                 }
-            }
-            // write 5.0 bank1 4 (aka bank1[4] = 5.0)
-            Token::Write => {
-                let set = take_numvar!(tok!()?)?;
-                let container = take_memory!();
-                let index = take_numvar!(tok!()?)?;
-                // this is the parser so i wont bother getting unchecked
-                if let LVar::Num(v) = executor.mem[index.address as usize]
-                    && !container.fits(v.round() as usize)
-                {
-                    yeet!(IndexOutOfBounds(v.round() as usize, container.size()));
+                // stop
+                Token::Stop => {
+                    executor.add(Stop {});
                 }
-                executor.add(Write {
-                    index,
-                    set,
-                    container,
-                });
-            }
-            // read result cell1 4 (aka result = cell1[4])
-            Token::Read => {
-                let output = take_var!(tok!()?)?;
-                let container = take_memory!();
-                let index = take_numvar!(tok!()?)?;
-                if let LVar::Num(v) = executor.mem[index.address as usize]
-                    && !container.fits(v.round() as usize)
-                {
-                    yeet!(IndexOutOfBounds(v.round() as usize, container.size()));
+                // jump start equal a b
+                Token::Jump => {
+                    let tok = tok!()?;
+                    // label jump
+                    if let Some(i) = tokstr!(tok) {
+                        let span = tokens.span();
+                        let op = tok!()?;
+                        if op == Token::Always {
+                            executor.jmp();
+                            unfinished_jumps.push((UJump::Always, (i, span), executor.last()));
+                        } else {
+                            let op = op.try_into().map_err(|op| err!(ExpectedOp(op)))?;
+                            let a = take_var!(tok!()?)?;
+                            let b = take_var!(tok!()?)?;
+                            executor.jmp();
+                            unfinished_jumps.push((
+                                UJump::Sometimes { a, b, op },
+                                (i, span),
+                                executor.last(),
+                            ));
+                        }
+                    } else if let Ok(n) = take_int!(tok.clone()) {
+                        // SAFETY: we check at the end of the block that it is valid
+                        let to = unsafe { Instruction::new(n) };
+                        let op = tok!()?;
+                        if op == Token::Always {
+                            executor.add(AlwaysJump { to });
+                        } else {
+                            let op = op.try_into().map_err(|op| err!(ExpectedOp(op)))?;
+                            let a = take_var!(tok!()?)?;
+                            let b = take_var!(tok!()?)?;
+                            executor.add(Jump::new(op, to, a, b));
+                        }
+                    } else {
+                        yeet!(ExpectedJump(tok));
+                    };
                 }
-                executor.add(Read {
-                    index,
-                    output,
-                    container,
-                });
-            }
-            Token::Draw => {
-                let dty = tok!()?;
-                let Token::Ident(instr) = dty else {
-                    yeet!(ExpectedIdent(dty));
-                };
-                match instr {
-                    "clear" => {
-                        let (r, g, b) = three! { num_or_255!(tok!())? };
-                        executor.draw(Clear { r, g, b });
-                    }
-                    "color" => {
-                        let (r, g, b, a) = four! { num_or_255!(tok!())? };
-                        executor.draw(SetColor { r, g, b, a });
-                    }
-                    "col" => {
-                        let col = take_numvar!(tok!()?)?;
-                        executor.draw(SetCol { col });
-                    }
-                    "stroke" => {
-                        let size = take_numvar!(tok!()?)?;
-                        executor.draw(SetStroke { size });
-                    }
-                    "line" => {
-                        let (x, y, x2, y2) = four! { take_numvar!(tok!()?)? };
-                        executor.draw(Line {
-                            point_a: (x, y),
-                            point_b: (x2, y2),
-                        });
-                    }
-                    "rect" => {
-                        let (x, y, width, height) = four! { take_numvar!(tok!()?)? };
-                        executor.draw(RectFilled {
-                            position: (x, y),
-                            width,
-                            height,
-                        });
-                    }
-                    "lineRect" => {
-                        let (x, y, width, height) = four! { take_numvar!(tok!()?)? };
-                        executor.draw(RectBordered {
-                            position: (x, y),
-                            width,
-                            height,
-                        });
-                    }
-                    "triangle" => {
-                        let (x, y, x2, y2, x3, y3) = six! { take_numvar!(tok!()?)? };
-                        executor.draw(Triangle {
-                            points: ((x, y), (x2, y2), (x3, y3)),
-                        });
+                // op add c 1 2
+                Token::Op => {
+                    let op = tok!()?;
+                    if op == Token::Rand {
+                        let out = take_numvar!(tok!()?)?;
+                        let range = take_numvar!(tok!()?)?;
+                        executor.add(Rand { range, out });
+                    } else if let Ok(op) = MathOp1::try_from(op.clone()) {
+                        // assigning to a var is useless but legal
+                        let out = take_numvar!(tok!()?)?;
+                        let x = take_numvar!(tok!()?)?;
+                        executor.add(Op1::new(op, x, out));
+                    } else if let Ok(op) = MathOp2::try_from(op.clone()) {
+                        let out = take_numvar!(tok!()?)?;
+                        let a = take_numvar!(tok!()?)?;
+                        let b = take_numvar!(tok!()?)?;
+                        executor.add(Op2::new(op, a, b, out));
+                    } else {
+                        yeet!(ExpectedOp(op));
                     }
-                    "poly" => {
-                        let (x, y, sides, radius, rot) = five! { take_numvar!(tok!()?)? };
-                        executor.draw(Poly {
-                            pos: (x, y),
-                            sides,
-                            radius,
-                            rot,
-                        })
+                }
+                // write 5.0 bank1 4 (aka bank1[4] = 5.0)
+                Token::Write => {
+                    let set = take_numvar!(tok!()?)?;
+                    let container = take_memory!();
+                    let index = take_numvar!(tok!()?)?;
+                    // this is the parser so i wont bother getting unchecked
+                    if let LVar::Num(v) = executor.mem[index.address as usize]
+                        && !container.fits(v.round() as usize)
+                    {
+                        yeet!(IndexOutOfBounds(v.round() as usize, container.size()));
                     }
-                    "linePoly" => {
-                        let (x, y, sides, radius, rot) = five! { take_numvar!(tok!()?)? };
-                        executor.draw(LinePoly {
-                            pos: (x, y),
-                            sides,
-                            radius,
-                            rot,
-                        })
+                    executor.add(Write {
+                        index,
+                        set,
+                        container,
+                    });
+                }
+                // read result cell1 4 (aka result = cell1[4])
+                Token::Read => {
+                    let output = take_var!(tok!()?)?;
+                    let container = take_memory!();
+                    let index = take_numvar!(tok!()?)?;
+                    if let LVar::Num(v) = executor.mem[index.address as usize]
+                        && !container.fits(v.round() as usize)
+                    {
+                        yeet!(IndexOutOfBounds(v.round() as usize, container.size()));
                     }
-                    // image is WONTFIX
-                    i => yeet!(UnsupportedImageOp(i)),
+                    executor.add(Read {
+                        index,
+                        output,
+                        container,
+                    });
                 }
-            }
-            Token::PackColor => {
-                let out = take_numvar!(tok!()?)?;
-                let (r, g, b, a) = four! { take_numvar!(tok!()?)? };
-                executor.add(PackColor { out, r, g, b, a });
-            }
-            Token::DrawFlush => {
-                let t = tok!();
-                if let Ok(t) = t
-                    && t != Token::Newline
-                {
-                    let screen = take_ident!(t.clone())?;
-                    let mut out = String::new();
-                    for ch in screen.chars() {
-                        if ch.is_ascii_digit() {
-                            out.push(ch);
-                            continue;
+                Token::Draw => {
+                    let dty = tok!()?;
+                    let Token::Ident(instr) = dty else {
+                        yeet!(ExpectedIdent(dty));
+                    };
+                    match instr {
+                        "clear" => {
+                            let (r, g, b) = three! { num_or_255!(tok!())? };
+                            executor.draw(Clear { r, g, b });
                         }
-                        if !out.is_empty() {
-                            yeet!(InvalidDisplayType(screen));
+                        "color" => {
+                            let (r, g, b, a) = four! { num_or_255!(tok!())? };
+                            executor.draw(SetColor { r, g, b, a });
                         }
-                    }
-                    let screen = &screen[..screen.len() - out.len()];
-                    if screen != "display" {
-                        yeet!(InvalidDisplayType(screen));
-                    }
-                    let n_span = tokens.span().start + screen.len()..tokens.span().end;
-                    let screen_n = out
-                        .parse::<usize>()
-                        .map_err(|_| Error::ExpectedInt(t, n_span.clone()))?;
-                    let display = executor
-                        .display(screen_n)
-                        .map_err(|n| Error::NoDisplay(n, n_span))?;
-                    executor.add(Flush { display });
-                } else {
-                    executor.add(Flush::default())
-                }
-            }
-            // end
-            Token::End => {
-                executor.add(End {});
-            }
-            // starting newline, simply skip. continue, so as not to to trigger the nextline!()
-            Token::Newline => continue,
-            // unknown instruction
-            Token::Ident(i) => {
-                macro_rules! all {
-                    ($is:expr) => {
-                        |b| {
-                            for var in &mut *b {
-                                *var = $is;
-                            }
-                            Ok(())
+                        "col" => {
+                            let col = take_numvar!(tok!()?)?;
+                            executor.draw(SetCol { col });
                         }
-                    };
-                }
-                macro_rules! take {
-                    (@ $b:ident $n:ident skip) => {
-                        $b[$n]=tok!()?;
-
-                    };
-                    (@ $b:ident $n:ident skip, $($rest:tt)*) => {
-                        $b[$n] = tok!()?;$n+=1; take!(@ $b $n $($rest)*);
-                    };
-                    (@ $b:ident $n:ident $v: expr) => {
-                        $b[$n] = $v;
-                    };
-                    (@ $b:ident $n:ident $v: expr, $($rest:tt)*) => {
-                        $b[$n] = $v;
-                        $n += 1;
-                        take!(@ $b $n $($rest)*);
-                    };
-                    [$($this:tt)*] => {
-                        |b| {
-                            let mut n = 0;
-                            take!(@ b n $($this)*);
-                            Ok(())
+                        "stroke" => {
+                            let size = take_numvar!(tok!()?)?;
+                            executor.draw(SetStroke { size });
                         }
-                    };
-                }
-                macro_rules! num {
-                    () => {{
-                        let tok = tok!()?;
-                        if let Some(i) = tokstr!(tok) {
-                            Token::Ident(i)
-                        } else {
-                            match tok {
-                                Token::Num(n) => Token::Num(n),
-                                t => yeet!(ExpectedNum(t)),
-                            }
+                        "line" => {
+                            let (x, y, x2, y2) = four! { take_numvar!(tok!()?)? };
+                            executor.draw(Line {
+                                point_a: (x, y),
+                                point_b: (x2, y2),
+                            });
                         }
-                    }};
-                }
-                macro_rules! bool {
-                    () => {{
-                        let tok = tok!()?;
-                        if let Some(i) = tokstr!(tok) {
-                            Token::Ident(i)
-                        } else {
-                            match tok {
-                                Token::Num(n) => Token::Num(n),
-                                t => yeet!(ExpectedBool(t)),
-                            }
+                        "rect" => {
+                            let (x, y, width, height) = four! { take_numvar!(tok!()?)? };
+                            executor.draw(RectFilled {
+                                position: (x, y),
+                                width,
+                                height,
+                            });
                         }
-                    }};
-                }
-                macro_rules! ident {
-                    () => {
-                        take_ident!(tok!()?).map(|v| Token::Ident(v))?
-                    };
-                }
-                #[rustfmt::skip]
-                macro_rules! build { () => { ident!() }}
-                macro_rules! str {
-                    () => {
-                        match tok!()? {
-                            Token::String(s) => Token::String(s),
-                            t => yeet!(ExpectedString(t)),
+                        "lineRect" => {
+                            let (x, y, width, height) = four! { take_numvar!(tok!()?)? };
+                            executor.draw(RectBordered {
+                                position: (x, y),
+                                width,
+                                height,
+                            });
                         }
-                    };
+                        "triangle" => {
+                            let (x, y, x2, y2, x3, y3) = six! { take_numvar!(tok!()?)? };
+                            executor.draw(Triangle {
+                                points: ((x, y), (x2, y2), (x3, y3)),
+                            });
+                        }
+                        "poly" => {
+                            let (x, y, sides, radius, rot) = five! { take_numvar!(tok!()?)? };
+                            executor.draw(Poly {
+                                pos: (x, y),
+                                sides,
+                                radius,
+                                rot,
+                            })
+                        }
+                        "linePoly" => {
+                            let (x, y, sides, radius, rot) = five! { take_numvar!(tok!()?)? };
+                            executor.draw(LinePoly {
+                                pos: (x, y),
+                                sides,
+                                radius,
+                                rot,
+                            })
+                        }
+                        // image is WONTFIX
+                        i => yeet!(UnsupportedImageOp(i)),
+                    }
                 }
-                macro_rules! var {
-                    () => {{
-                        let tok = tok!()?;
-                        if let Some(i) = tokstr!(tok) {
-                            Token::Ident(i)
-                        } else {
-                            match tok {
-                                Token::Num(n) => Token::Num(n),
-                                Token::String(s) => Token::String(s),
-                                t => yeet!(ExpectedVar(t)),
+                Token::PackColor => {
+                    let out = take_numvar!(tok!()?)?;
+                    let (r, g, b, a) = four! { take_numvar!(tok!()?)? };
+                    executor.add(PackColor { out, r, g, b, a });
+                }
+                Token::DrawFlush => {
+                    let t = tok!();
+                    if let Ok(t) = t
+                        && t != Token::Newline
+                    {
+                        let screen = take_ident!(t.clone())?;
+                        let mut out = String::new();
+                        for ch in screen.chars() {
+                            if ch.is_ascii_digit() {
+                                out.push(ch);
+                                continue;
+                            }
+                            if !out.is_empty() {
+                                yeet!(InvalidDisplayType(screen));
                             }
                         }
-                    }};
-                }
-                macro_rules! instr {
-                    (($argc:literal) => $block:expr) => {{
-                        let mut v: Box<[_; $argc + 1]> =
-                            Box::new(std::array::from_fn(|_| Token::Newline));
-                        v[0] = Token::Ident(i);
-                        const fn castor<
-                            'source,
-                            F: FnMut(&mut [Token<'source>; $argc]) -> Result<(), Error<'source>>,
-                        >(
-                            f: F,
-                        ) -> F {
-                            f
+                        let screen = &screen[..screen.len() - out.len()];
+                        if screen != "display" {
+                            yeet!(InvalidDisplayType(screen));
                         }
-                        castor($block)((&mut v[1..]).try_into().unwrap())?;
-                        executor.code(v as Box<[Token<'source>]>);
-                        nextline!();
-                        continue;
-                    }};
+                        let n_span = tokens.span().start + screen.len()..tokens.span().end;
+                        let screen_n = out
+                            .parse::<usize>()
+                            .map_err(|_| Error::ExpectedInt(t, n_span.clone()))?;
+                        let display = executor
+                            .display(screen_n)
+                            .map_err(|n| Error::NoDisplay(n, n_span))?;
+                        executor.add(Flush { display });
+                    } else {
+                        executor.add(Flush::default())
+                    }
                 }
-                macro_rules! minstr {
-                    ($($sub:ident($argc:literal) => $block:expr)+ => $err:expr $(,)?) => {{
-                        let t = tok!()?;
-                        let idnt = take_ident!(t.clone())?;
-                        $(if idnt == stringify!($sub) {
-                            let mut v: Box<[_; $argc + 2]> = Box::new(std::array::from_fn(|_| Token::Newline));
-                            v[0]=Token::Ident(i);
-                            v[1]=Token::Ident(stringify!($sub));
-                            const fn castor<'source, F: FnMut(&mut [Token<'source>; $argc]) -> Result<(), Error<'source>>>(f: F) -> F { f }
-                            castor($block)((&mut v[2..]).try_into().unwrap())?;
-                            executor.code(v);
-                            nextline!();
-                            continue;
-                        })+
-                        return Err($err(idnt, t));
-                    }};
+                // end
+                Token::End => {
+                    executor.add(End {});
                 }
-                match i {
-                    "printflush" => instr! {
-                        (1) => |b| {
-                            let t = tok!()?;
-                            if let Some(t) = tokstr!(t) {
-                                b[0] = Token::Ident(t);
-                            } else if t == Token::Null {
-                                b[0] = Token::Null;
+                // starting newline, simply skip. continue, so as not to to trigger the nextline!()
+                Token::Newline => continue,
+                // unknown instruction
+                Token::Ident(i) => {
+                    macro_rules! all {
+                        ($is:expr) => {
+                            |b| {
+                                for var in &mut *b {
+                                    *var = $is;
+                                }
+                                Ok(())
+                            }
+                        };
+                    }
+                    macro_rules! take {
+                        (@ $b:ident $n:ident skip) => {
+                            $b[$n]=tok!()?;
+
+                        };
+                        (@ $b:ident $n:ident skip, $($rest:tt)*) => {
+                            $b[$n] = tok!()?;$n+=1; take!(@ $b $n $($rest)*);
+                        };
+                        (@ $b:ident $n:ident $v: expr) => {
+                            $b[$n] = $v;
+                        };
+                        (@ $b:ident $n:ident $v: expr, $($rest:tt)*) => {
+                            $b[$n] = $v;
+                            $n += 1;
+                            take!(@ $b $n $($rest)*);
+                        };
+                        [$($this:tt)*] => {
+                            |b| {
+                                let mut n = 0;
+                                take!(@ b n $($this)*);
+                                Ok(())
+                            }
+                        };
+                    }
+                    macro_rules! num {
+                        () => {{
+                            let tok = tok!()?;
+                            if let Some(i) = tokstr!(tok) {
+                                Token::Ident(i)
                             } else {
-                                b[0] = Token::Ident("message1");
+                                match tok {
+                                    Token::Num(n) => Token::Num(n),
+                                    t => yeet!(ExpectedNum(t)),
+                                }
                             }
-                            Ok(())
-                        }
-                    },
-                    "getlink" => instr! { (2) => take![var!(), num!()] },
-                    "control" => minstr! {
-                        enabled(2) => take![build!(), bool!()]
-                        shoot(4) => take![build!(), num!(), num!(), bool!()]
-                        shootp(3) => take![build!(), str!(), bool!()]
-                        config(2) => take![build!(), tok!()?]
-                        color(4) => take![build!(), num!(), num!(), num!()]
-                        => |t, _| { err!(UnknownControlOp(t)) },
-                    },
-                    "radar" => {
-                        instr! { (7) => take![ident!(), ident!(), ident!(), ident!(), build!(), num!(), var!()] }
+                        }};
                     }
-                    "sensor" => instr! { (3) => take![var!(), tok!()?, tok!()?] },
-                    "wait" => instr! { (1) => all!(num!()) },
-                    "lookup" => instr! { (3) => take![ident!(), var!(), num!()] },
-                    "packcolor" => instr! { (4) => all!(num!()) },
-                    "ubind" => instr! { (1) => |b| {
-                        let t = tok!()?;
-                        if tokstr!(t).is_some() || matches!(t, Token::Null) {
-                            b[0] = t;
-                        } else {
-                            yeet!(ExpectedString(t));
+                    macro_rules! bool {
+                        () => {{
+                            let tok = tok!()?;
+                            if let Some(i) = tokstr!(tok) {
+                                Token::Ident(i)
+                            } else {
+                                match tok {
+                                    Token::Num(n) => Token::Num(n),
+                                    t => yeet!(ExpectedBool(t)),
+                                }
+                            }
+                        }};
+                    }
+                    macro_rules! ident {
+                        () => {
+                            take_ident!(tok!()?).map(|v| Token::Ident(v))?
                         };
-                        Ok(())
-                    } },
-                    "ucontrol" => minstr! {
-                        idle(0) => |_| Ok(())
-                        stop(0) => |_| Ok(())
-                        move(2) => all!(num!())
-                        approach(3) => all!(num!())
-                        boost(1) => all!(bool!())
-                        pathfind(2) => all!(num!())
-                        target(3) => take![num!(), num!(), bool!()]
-                        targetp(2) => take![str!(), bool!()]
-                        itemDrop(2) => take![build!(), num!()]
-                        itemTake(3) => take![build!(), str!(), num!()]
-                        payDrop(0) => |_| Ok(())
-                        payTake(1) => all!(bool!())
-                        payEnter(0) => |_| Ok(())
-                        mine(2) => all!(num!())
-                        flag(1) => all!(num!())
-                        build(5) => take![num!(), num!(), build!(), num!(), tok!()?]
-                        getBlock(4) => take![num!(), num!(), build!(), build!()]
-                        within(4) => take![num!(), num!(), num!(), var!()]
-                        => |t, _| { err!(UnknownUnitControlOp(t)) }
-                    },
-                    "uradar" => {
-                        instr! { (7) => take![ident!(), ident!(), ident!(), ident!(), build!(), num!(), var!()] }
                     }
-                    "ulocate" => {
-                        minstr! {
-                            building(7) => take![build!(), bool!(), skip, num!(), num!(), bool!(), build!()]
-                            spawn(6) => take![skip, skip, skip, num!(), num!(), bool!()]
-                            damaged(7) => take![skip, skip, skip, num!(), num!(), var!(), var!()]
-                            ore(6) => take![skip, skip, tok!()?, num!(), num!(), bool!()]
-                            => |t, _| { err!(UnknownUnitLocateOp(t)) }
-                        }
+                    #[rustfmt::skip]
+                    macro_rules! build { () => { ident!() }}
+                    macro_rules! str {
+                        () => {
+                            match tok!()? {
+                                Token::String(s) => Token::String(s),
+                                t => yeet!(ExpectedString(t)),
+                            }
+                        };
                     }
-                    "getblock" => minstr! {
-                        floor(3) => take![tok!()?, num!(), num!()]
-                        ore(3) => take![tok!()?, num!(), num!()]
-                        block(3) => take![tok!()?, num!(), num!()]
-                        building(3) => take![tok!()?, num!(), num!()]
-                        => |t, _| { err!(UnknownGetBlockOp(t)) }
-                    },
-                    "setblock" => minstr! {
-                        floor(3) => take![num!(), num!(), str!()]
-                        ore(3) => take![num!(), num!(), str!()]
-                        block(3) => take![num!(), num!(), str!(), str!(), num!()]
-                        => |t, _| { err!(UnknownSetBlockOp(t)) }
-                    },
-                    "spawn" => {
-                        instr! { (6) => take![str!(), num!(), num!(), num!(), str!(), var!()] }
+                    macro_rules! var {
+                        () => {{
+                            let tok = tok!()?;
+                            if let Some(i) = tokstr!(tok) {
+                                Token::Ident(i)
+                            } else {
+                                match tok {
+                                    Token::Num(n) => Token::Num(n),
+                                    Token::String(s) => Token::String(s),
+                                    t => yeet!(ExpectedVar(t)),
+                                }
+                            }
+                        }};
                     }
-                    "status" => minstr! {
-                        true(3) => take![ident!(), var!(), num!()]
-                        false(2) => take![ident!(), var!()]
-                        => |_, t| { err!(ExpectedBool(t)) }
-                    },
-                    "spawnwave" => instr! { (3) => take![num!(), num!(), bool!()] },
-                    "setrule" => {
-                        #[rustfmt::skip]
-                        macro_rules! rule { () => { take![num!(), str!()] }}
-                        minstr! {
-                            currentWaveTime(1) => all!(num!())
-                            waveTimer(1) => all!(num!())
-                            waves(1) => all!(num!())
-                            wave(1) => all!(num!())
-                            waveSpacing(1) => all!(num!())
-                            waveSending(1) => all!(num!())
-                            attackMode(1) => all!(num!())
-                            enemyCoreBuildRadius(1) => all!(num!())
-                            dropZoneRadius(1) => all!(num!())
-                            unitCap(1) => all!(num!())
-                            wave(1) => all!(num!())
-                            mapArea(4) => |b| {
-                                tok!()?;
-                                for var in &mut *b {
-                                    *var = num!();
+                    macro_rules! instr {
+                        (($argc:literal) => $block:expr) => {{
+                            let mut v: Box<[_; $argc + 1]> =
+                                Box::new(std::array::from_fn(|_| Token::Newline));
+                            v[0] = Token::Ident(i);
+                            const fn castor<
+                                'source,
+                                F: FnMut(&mut [Token<'source>; $argc]) -> Result<(), Error<'source>>,
+                            >(
+                                f: F,
+                            ) -> F {
+                                f
+                            }
+                            castor($block)((&mut v[1..]).try_into().unwrap())?;
+                            executor.code(v as Box<[Token<'source>]>);
+                            nextline!();
+                            continue;
+                        }};
+                    }
+                    macro_rules! minstr {
+                        ($($sub:ident($argc:literal) => $block:expr)+ => $err:expr $(,)?) => {{
+                            let t = tok!()?;
+                            let idnt = take_ident!(t.clone())?;
+                            $(if idnt == stringify!($sub) {
+                                let mut v: Box<[_; $argc + 2]> = Box::new(std::array::from_fn(|_| Token::Newline));
+                                v[0]=Token::Ident(i);
+                                v[1]=Token::Ident(stringify!($sub));
+                                const fn castor<'source, F: FnMut(&mut [Token<'source>; $argc]) -> Result<(), Error<'source>>>(f: F) -> F { f }
+                                castor($block)((&mut v[2..]).try_into().unwrap())?;
+                                executor.code(v);
+                                nextline!();
+                                continue;
+                            })+
+                            do yeet $err(idnt, t);
+                        }};
+                    }
+                    match i {
+                        "printflush" => instr! {
+                            (1) => |b| {
+                                let t = tok!()?;
+                                if let Some(t) = tokstr!(t) {
+                                    b[0] = Token::Ident(t);
+                                } else if t == Token::Null {
+                                    b[0] = Token::Null;
+                                } else {
+                                    b[0] = Token::Ident("message1");
                                 }
                                 Ok(())
                             }
-                            lighting(1) => all!(num!())
-                            ambientLight(1) => all!(num!())
-                            solarMultiplier(1) => all!(num!())
-                            buildSpeed(2) => rule!{}
-                            unitHealth(2) => rule!{}
-                            unitBuildSpeed(2) => rule!{}
-                            unitCost(2) => rule!{}
-                            unitDamage(2) => rule!{}
-                            blockHealth(2) => rule!{}
-                            blockDamage(2) => rule!{}
-                            rtsMinWeight(2) => rule!{}
-                            rtsMinSquad(2) => rule!{}
-                            => |t, _| { err!(UnknownRule(t)) }
+                        },
+                        "getlink" => instr! { (2) => take![var!(), num!()] },
+                        "control" => minstr! {
+                            enabled(2) => take![build!(), bool!()]
+                            shoot(4) => take![build!(), num!(), num!(), bool!()]
+                            shootp(3) => take![build!(), str!(), bool!()]
+                            config(2) => take![build!(), tok!()?]
+                            color(4) => take![build!(), num!(), num!(), num!()]
+                            => |t, _| { err!(UnknownControlOp(t)) },
+                        },
+                        "radar" => {
+                            instr! { (7) => take![ident!(), ident!(), ident!(), ident!(), build!(), num!(), var!()] }
                         }
-                    }
-                    "cutscene" => minstr! {
-                        pan(3) => all!(num!())
-                        zoom(1) => all!(num!())
-                        stop(0) => |_| Ok(())
-                        => |t, _| { err!(UnknownCutscene(t)) }
-                    },
-                    "explosion" => {
-                        instr! { (8) => take![str!(), num!(), num!(), num!(), bool!(), bool!(), bool!()] }
-                    }
-                    "setrate" => instr! { (1) => all!(num!()) },
-                    "fetch" => minstr! {
-                        buildCount(4) => take![var!(), str!(), num!(), str!()] // useless 0
-                        unitCount(4)  => take![var!(), str!()]
-                        playerCount(4)  => take![var!(), str!()]
-                        coreCount(4)  => take![var!(), str!()]
-                        unit(3) => take![var!(), str!(), num!()]
-                        player(3) => take![var!(), str!(), num!()]
-                        core(3) => take![var!(), str!(), num!()]
-                        build(3) => take![var!(), str!(), num!()]
-                        => |t, _| { err!(UnknownFetchOp(t)) }
-                    },
-                    "getflag" => instr! { (2) => take![bool!(), str!()] },
-                    "setflag" => instr! { (2) => take![str!(), bool!()] },
-                    "setprop" => instr! { (3) => take![str!(), var!(), tok!()?] },
-                    "effect" => {
-                        let mut v = Vec::with_capacity(6);
-                        v.push(Token::Ident("effect"));
-                        while let Some(tok) = tokens.next()
-                            && tok != Token::Newline
-                        {
-                            v.push(tok);
+                        "sensor" => instr! { (3) => take![var!(), tok!()?, tok!()?] },
+                        "wait" => instr! { (1) => all!(num!()) },
+                        "lookup" => instr! { (3) => take![ident!(), var!(), num!()] },
+                        "packcolor" => instr! { (4) => all!(num!()) },
+                        "ubind" => instr! { (1) => |b| {
+                            let t = tok!()?;
+                            if tokstr!(t).is_some() || matches!(t, Token::Null) {
+                                b[0] = t;
+                            } else {
+                                yeet!(ExpectedString(t));
+                            };
+                            Ok(())
+                        } },
+                        "ucontrol" => minstr! {
+                            idle(0) => |_| Ok(())
+                            stop(0) => |_| Ok(())
+                            move(2) => all!(num!())
+                            approach(3) => all!(num!())
+                            boost(1) => all!(bool!())
+                            pathfind(2) => all!(num!())
+                            target(3) => take![num!(), num!(), bool!()]
+                            targetp(2) => take![str!(), bool!()]
+                            itemDrop(2) => take![build!(), num!()]
+                            itemTake(3) => take![build!(), str!(), num!()]
+                            payDrop(0) => |_| Ok(())
+                            payTake(1) => all!(bool!())
+                            payEnter(0) => |_| Ok(())
+                            mine(2) => all!(num!())
+                            flag(1) => all!(num!())
+                            build(5) => take![num!(), num!(), build!(), num!(), tok!()?]
+                            getBlock(4) => take![num!(), num!(), build!(), build!()]
+                            within(4) => take![num!(), num!(), num!(), var!()]
+                            => |t, _| { err!(UnknownUnitControlOp(t)) }
+                        },
+                        "uradar" => {
+                            instr! { (7) => take![ident!(), ident!(), ident!(), ident!(), build!(), num!(), var!()] }
+                        }
+                        "ulocate" => {
+                            minstr! {
+                                building(7) => take![build!(), bool!(), skip, num!(), num!(), bool!(), build!()]
+                                spawn(6) => take![skip, skip, skip, num!(), num!(), bool!()]
+                                damaged(7) => take![skip, skip, skip, num!(), num!(), var!(), var!()]
+                                ore(6) => take![skip, skip, tok!()?, num!(), num!(), bool!()]
+                                => |t, _| { err!(UnknownUnitLocateOp(t)) }
+                            }
+                        }
+                        "getblock" => minstr! {
+                            floor(3) => take![tok!()?, num!(), num!()]
+                            ore(3) => take![tok!()?, num!(), num!()]
+                            block(3) => take![tok!()?, num!(), num!()]
+                            building(3) => take![tok!()?, num!(), num!()]
+                            => |t, _| { err!(UnknownGetBlockOp(t)) }
+                        },
+                        "setblock" => minstr! {
+                            floor(3) => take![num!(), num!(), str!()]
+                            ore(3) => take![num!(), num!(), str!()]
+                            block(3) => take![num!(), num!(), str!(), str!(), num!()]
+                            => |t, _| { err!(UnknownSetBlockOp(t)) }
+                        },
+                        "spawn" => {
+                            instr! { (6) => take![str!(), num!(), num!(), num!(), str!(), var!()] }
+                        }
+                        "status" => minstr! {
+                            true(3) => take![ident!(), var!(), num!()]
+                            false(2) => take![ident!(), var!()]
+                            => |_, t| { err!(ExpectedBool(t)) }
+                        },
+                        "spawnwave" => instr! { (3) => take![num!(), num!(), bool!()] },
+                        "setrule" => {
+                            #[rustfmt::skip]
+                            macro_rules! rule { () => { take![num!(), str!()] }}
+                            minstr! {
+                                currentWaveTime(1) => all!(num!())
+                                waveTimer(1) => all!(num!())
+                                waves(1) => all!(num!())
+                                wave(1) => all!(num!())
+                                waveSpacing(1) => all!(num!())
+                                waveSending(1) => all!(num!())
+                                attackMode(1) => all!(num!())
+                                enemyCoreBuildRadius(1) => all!(num!())
+                                dropZoneRadius(1) => all!(num!())
+                                unitCap(1) => all!(num!())
+                                wave(1) => all!(num!())
+                                mapArea(4) => |b| {
+                                    tok!()?;
+                                    for var in &mut *b {
+                                        *var = num!();
+                                    }
+                                    Ok(())
+                                }
+                                lighting(1) => all!(num!())
+                                ambientLight(1) => all!(num!())
+                                solarMultiplier(1) => all!(num!())
+                                buildSpeed(2) => rule!{}
+                                unitHealth(2) => rule!{}
+                                unitBuildSpeed(2) => rule!{}
+                                unitCost(2) => rule!{}
+                                unitDamage(2) => rule!{}
+                                blockHealth(2) => rule!{}
+                                blockDamage(2) => rule!{}
+                                rtsMinWeight(2) => rule!{}
+                                rtsMinSquad(2) => rule!{}
+                                => |t, _| { err!(UnknownRule(t)) }
+                            }
+                        }
+                        "cutscene" => minstr! {
+                            pan(3) => all!(num!())
+                            zoom(1) => all!(num!())
+                            stop(0) => |_| Ok(())
+                            => |t, _| { err!(UnknownCutscene(t)) }
+                        },
+                        "explosion" => {
+                            instr! { (8) => take![str!(), num!(), num!(), num!(), bool!(), bool!(), bool!()] }
                         }
-                        executor.code(v.into_boxed_slice());
-                        // we take the newline here
-                        continue;
+                        "setrate" => instr! { (1) => all!(num!()) },
+                        "fetch" => minstr! {
+                            buildCount(4) => take![var!(), str!(), num!(), str!()] // useless 0
+                            unitCount(4)  => take![var!(), str!()]
+                            playerCount(4)  => take![var!(), str!()]
+                            coreCount(4)  => take![var!(), str!()]
+                            unit(3) => take![var!(), str!(), num!()]
+                            player(3) => take![var!(), str!(), num!()]
+                            core(3) => take![var!(), str!(), num!()]
+                            build(3) => take![var!(), str!(), num!()]
+                            => |t, _| { err!(UnknownFetchOp(t)) }
+                        },
+                        "getflag" => instr! { (2) => take![bool!(), str!()] },
+                        "setflag" => instr! { (2) => take![str!(), bool!()] },
+                        "setprop" => instr! { (3) => take![str!(), var!(), tok!()?] },
+                        "effect" => {
+                            let mut v = Vec::with_capacity(6);
+                            v.push(Token::Ident("effect"));
+                            while let Some(tok) = tokens.next()
+                                && tok != Token::Newline
+                            {
+                                v.push(tok);
+                            }
+                            executor.code(v.into_boxed_slice());
+                            // we take the newline here
+                            continue;
+                        }
+                        t => yeet!(ExpectedInstr(Token::Ident(t))),
                     }
-                    t => yeet!(ExpectedInstr(Token::Ident(t))),
                 }
+                t => yeet!(ExpectedInstr(t)),
             }
-            t => yeet!(ExpectedInstr(t)),
+        };
+        if let Err(e) = line {
+            errors.push(e);
+        }
+        // a error may have already taken the newline
+        if tokens.slice() != "\n" {
+            nextline!();
         }
-        nextline!();
     }
 
     for (j, (label, s), i) in unfinished_jumps {
-        let to = executor
-            .debug_info
-            .labels
-            .iter()
-            .find(|(v, _)| v == &label)
-            .ok_or_else(|| Error::LabelNotFound(label, s))?
-            .1;
+        let Some(&(_, to)) = executor.debug_info.labels.iter().find(|(v, _)| v == &label) else {
+            errors.push(Error::LabelNotFound(label, s));
+            continue;
+        };
         executor.program[i.get()] = UPInstr::Instr(match j {
             UJump::Always => Instr::from(AlwaysJump { to }),
             UJump::Sometimes { a, b, op } => Instr::from(Jump::new(op, to, a, b)),
@@ -818,7 +835,7 @@ pub fn parse<'source, W: Wr>(
         ) = i
         {
             if !executor.valid(*to) {
-                yeet!(InvalidJump(*to));
+                errors.push(err!(InvalidJump(*to)));
             }
         }
     }
@@ -831,6 +848,10 @@ pub fn parse<'source, W: Wr>(
         }
     }
 
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
     executor.debug_info.variables = dbg_info.into();
 
     Ok(())
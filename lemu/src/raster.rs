@@ -0,0 +1,248 @@
+//! Rasterization that matches the game's GL output.
+//!
+//! Everything is made of triangles (like the game's `Fill`/`Lines`), and a pixel is covered when its center is inside one.
+//! Centers exactly on a edge belong to the left and bottom edges only, so shapes that share a edge never overlap.
+//! Coordinates start at the bottom left, with y going up.
+use fimg::Image;
+use vecto::Vec2;
+
+/// `arc.math.Mathf`s lookup table sin (with 2^14 entries), in degrees.
+fn sin_deg(degrees: f32) -> f32 {
+    const COUNT: i32 = 1 << 14;
+    const DEG_TO_INDEX: f32 = COUNT as f32 / 360.0;
+    let index = (degrees * DEG_TO_INDEX) as i32 & (COUNT - 1);
+    // the table is exact at the right angles
+    for right in [0.0f32, 90.0, 180.0, 270.0] {
+        if index == (right * DEG_TO_INDEX) as i32 & (COUNT - 1) {
+            return (right as f64).to_radians().sin() as f32;
+        }
+    }
+    (((index as f32 + 0.5) / COUNT as f32 * std::f32::consts::TAU) as f64).sin() as f32
+}
+
+fn cos_deg(degrees: f32) -> f32 {
+    sin_deg(degrees + 90.0)
+}
+
+/// `Angles.trns`
+fn trns(angle: f32, len: f32) -> Vec2 {
+    Vec2::new(len * cos_deg(angle), len * sin_deg(angle))
+}
+
+fn blend(px: &mut [u8; 4], [r, g, b, a]: [u8; 4]) {
+    // SRC_ALPHA, ONE_MINUS_SRC_ALPHA (and ONE, ONE_MINUS_SRC_ALPHA for alpha)
+    let alpha = f32::from(a) / 255.0;
+    let mix = |s: u8, d: u8| (f32::from(s) * alpha + f32::from(d) * (1.0 - alpha)).round() as u8;
+    *px = [
+        mix(r, px[0]),
+        mix(g, px[1]),
+        mix(b, px[2]),
+        (f32::from(a) + f32::from(px[3]) * (1.0 - alpha)).round() as u8,
+    ];
+}
+
+/// is `p` on the inside of `a -> b` (for a counter clockwise triangle)
+fn inside(a: Vec2, b: Vec2, p: Vec2) -> bool {
+    let e = (b.x - a.x) * (p.y - a.y) - (b.y - a.y) * (p.x - a.x);
+    e > 0.0 || (e == 0.0 && (b.y < a.y || (b.y == a.y && b.x > a.x)))
+}
+
+/// `Fill.clear`
+pub fn clear(image: &mut Image<&mut [u8], 4>, [r, g, b]: [u8; 3]) {
+    image.flatten_mut().fill([r, g, b, 255]);
+}
+
+/// `Fill.tri`
+pub fn tri(image: &mut Image<&mut [u8], 4>, a: Vec2, b: Vec2, c: Vec2, color: [u8; 4]) {
+    let area = (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x);
+    if area == 0.0 || area.is_nan() {
+        return;
+    }
+    let (b, c) = if area < 0.0 { (c, b) } else { (b, c) };
+    let (w, h) = (image.width(), image.height());
+    // pixels whose centers are in the bounding box
+    let span = |lo: f32, hi: f32, max: u32| {
+        let first = (lo - 0.5).ceil().max(0.0) as u32;
+        let last = ((hi - 0.5).floor() + 1.0).clamp(0.0, max as f32) as u32;
+        first..last
+    };
+    let xs = span(a.x.min(b.x).min(c.x), a.x.max(b.x).max(c.x), w);
+    let ys = span(a.y.min(b.y).min(c.y), a.y.max(b.y).max(c.y), h);
+    let pixels = image.flatten_mut();
+    for y in ys {
+        for x in xs.clone() {
+            let p = Vec2::new(x as f32 + 0.5, y as f32 + 0.5);
+            if inside(a, b, p) && inside(b, c, p) && inside(c, a, p) {
+                blend(&mut pixels[((h - 1 - y) * w + x) as usize], color);
+            }
+        }
+    }
+}
+
+/// `Fill.quad`
+pub fn quad(image: &mut Image<&mut [u8], 4>, [a, b, c, d]: [Vec2; 4], color: [u8; 4]) {
+    tri(image, a, b, c, color);
+    tri(image, c, d, a, color);
+}
+
+/// `Fill.crect`
+pub fn rect(image: &mut Image<&mut [u8], 4>, x: f32, y: f32, w: f32, h: f32, color: [u8; 4]) {
+    quad(
+        image,
+        [
+            Vec2::new(x, y),
+            Vec2::new(x + w, y),
+            Vec2::new(x + w, y + h),
+            Vec2::new(x, y + h),
+        ],
+        color,
+    );
+}
+
+/// `Lines.rect`: the stroke is on the inside.
+pub fn line_rect(
+    image: &mut Image<&mut [u8], 4>,
+    (x, y, w, h): (f32, f32, f32, f32),
+    stroke: f32,
+    color: [u8; 4],
+) {
+    rect(image, x, y, w, stroke, color);
+    rect(image, x, y + h, w, -stroke, color);
+    rect(image, x + w, y, -stroke, h, color);
+    rect(image, x, y, stroke, h, color);
+}
+
+/// `Lines.line`, with square caps that stick out by half the stroke.
+/// a line with no length is just its caps: a square.
+pub fn line(image: &mut Image<&mut [u8], 4>, a: Vec2, b: Vec2, stroke: f32, color: [u8; 4]) {
+    let hstroke = stroke / 2.0;
+    let len = (b.x - a.x).hypot(b.y - a.y);
+    let (dx, dy) = if len == 0.0 {
+        (hstroke, 0.0)
+    } else {
+        ((b.x - a.x) / len * hstroke, (b.y - a.y) / len * hstroke)
+    };
+    quad(
+        image,
+        [
+            Vec2::new(a.x - dx - dy, a.y - dy + dx),
+            Vec2::new(a.x - dx + dy, a.y - dy - dx),
+            Vec2::new(b.x + dx + dy, b.y + dy - dx),
+            Vec2::new(b.x + dx - dy, b.y + dy + dx),
+        ],
+        color,
+    );
+}
+
+/// `Fill.poly`: a fan of triangles, the first corner at `rotation` degrees.
+pub fn poly(
+    image: &mut Image<&mut [u8], 4>,
+    center: Vec2,
+    sides: usize,
+    radius: f32,
+    rotation: f32,
+    color: [u8; 4],
+) {
+    let space = 360.0 / sides as f32;
+    for i in 0..sides {
+        let a = space * i as f32 + rotation;
+        let (p1, p2) = (trns(a, radius), trns(a + space, radius));
+        tri(
+            image,
+            center,
+            Vec2::new(center.x + p1.x, center.y + p1.y),
+            Vec2::new(center.x + p2.x, center.y + p2.y),
+            color,
+        );
+    }
+}
+
+/// `Lines.poly`: the stroke is centered on the radius.
+/// with 2 sides, the corners are joined by a stroke with no caps.
+pub fn line_poly(
+    image: &mut Image<&mut [u8], 4>,
+    center: Vec2,
+    sides: usize,
+    radius: f32,
+    rotation: f32,
+    stroke: f32,
+    color: [u8; 4],
+) {
+    if sides == 2 {
+        let (p, hstroke) = (trns(rotation, radius), trns(rotation + 90.0, stroke / 2.0));
+        quad(
+            image,
+            [
+                Vec2::new(center.x + p.x - hstroke.x, center.y + p.y - hstroke.y),
+                Vec2::new(center.x + p.x + hstroke.x, center.y + p.y + hstroke.y),
+                Vec2::new(center.x - p.x + hstroke.x, center.y - p.y + hstroke.y),
+                Vec2::new(center.x - p.x - hstroke.x, center.y - p.y - hstroke.y),
+            ],
+            color,
+        );
+        return;
+    }
+    let space = 360.0 / sides as f32;
+    let hstep = stroke / 2.0 / cos_deg(space / 2.0);
+    let (r1, r2) = (radius - hstep, radius + hstep);
+    for i in 0..sides {
+        let a = space * i as f32 + rotation;
+        let at = |angle, r| {
+            let p = trns(angle, r);
+            Vec2::new(center.x + p.x, center.y + p.y)
+        };
+        quad(
+            image,
+            [at(a, r1), at(a + space, r1), at(a + space, r2), at(a, r2)],
+            color,
+        );
+    }
+}
+
+#[test]
+fn edges() {
+    let mut img = Image::<Vec<u8>, 4>::alloc(4, 4);
+    let mut i = img.as_mut();
+    // two rects sharing a edge: drawn once each, and the translucent blends never stack
+    rect(&mut i, 0.0, 0.0, 2.0, 4.0, [255, 0, 0, 128]);
+    rect(&mut i, 2.0, 0.0, 2.0, 4.0, [255, 0, 0, 128]);
+    assert!(img.flatten().iter().all(|&p| p == [128, 0, 0, 128]));
+
+    let mut img = Image::<Vec<u8>, 4>::alloc(4, 4);
+    let mut i = img.as_mut();
+    line(
+        &mut i,
+        Vec2::new(1.0, 1.0),
+        Vec2::new(3.0, 1.0),
+        2.0,
+        [255; 4],
+    );
+    // the bottom row (last in memory), from 0 to 4: the caps stick out by 1
+    assert_eq!(img.flatten()[12..], [[255; 4]; 4]);
+    assert_eq!(img.flatten()[8..12], [[255; 4]; 4]);
+    assert_eq!(img.flatten()[..8], [[0; 4]; 8]);
+}
+
+#[test]
+fn degenerate() {
+    let mut img = Image::<Vec<u8>, 4>::alloc(4, 4);
+    let mut i = img.as_mut();
+    // a dot: the caps make a 2x2 square around it
+    let dot = Vec2::new(2.0, 2.0);
+    line(&mut i, dot, dot, 2.0, [255; 4]);
+    let lit = |img: &Image<Vec<u8>, 4>| {
+        img.flatten()
+            .iter()
+            .enumerate()
+            .filter(|(_, &p)| p == [255; 4])
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(lit(&img), [5, 6, 9, 10]);
+
+    let mut img = Image::<Vec<u8>, 4>::alloc(4, 4);
+    let mut i = img.as_mut();
+    // 2 sides: a horizontal stroke, from one corner to the other
+    line_poly(&mut i, dot, 2, 2.0, 0.0, 2.0, [255; 4]);
+    assert_eq!(lit(&img), [4, 5, 6, 7, 8, 9, 10, 11]);
+}
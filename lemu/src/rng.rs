@@ -0,0 +1,47 @@
+/// xorshift128+, the same generator as the game (`arc.math.Rand`), seeded the same way.
+#[derive(Debug, Clone)]
+pub struct Rng {
+    seed0: u64,
+    seed1: u64,
+}
+
+const fn murmur(mut x: u64) -> u64 {
+    x ^= x >> 33;
+    x = x.wrapping_mul(0xff51_afd7_ed55_8ccd);
+    x ^= x >> 33;
+    x = x.wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+    x ^= x >> 33;
+    x
+}
+
+impl Rng {
+    pub const fn new(seed: u64) -> Self {
+        let seed0 = murmur(if seed == 0 { 1 << 63 } else { seed });
+        Self {
+            seed0,
+            seed1: murmur(seed0),
+        }
+    }
+
+    /// seeded from the clock, for when no seed is given.
+    pub fn random() -> Self {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_nanos() as u64);
+        Self::new(now)
+    }
+
+    pub fn next(&mut self) -> u64 {
+        let mut s1 = self.seed0;
+        let s0 = self.seed1;
+        self.seed0 = s0;
+        s1 ^= s1 << 23;
+        self.seed1 = s1 ^ s0 ^ (s1 >> 17) ^ (s0 >> 26);
+        self.seed1.wrapping_add(s0)
+    }
+
+    /// in `0..1`
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next() >> 11) as f64 * (1.0 / (1u64 << 53) as f64)
+    }
+}
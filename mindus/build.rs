@@ -32,18 +32,23 @@ fn main() {
     let o = std::env::var("OUT_DIR").unwrap();
     let o = Path::new(&o);
     let mut full = File::create(o.join("full.rs")).unwrap();
-    // let mut half = File::create(o.join("half.rs")).unwrap();
+    let mut half = File::create(o.join("half.rs")).unwrap();
     let mut quar = File::create(o.join("quar.rs")).unwrap();
     let mut eigh = File::create(o.join("eigh.rs")).unwrap();
 
     let mut to_colors = File::create(o.join("2.rs")).unwrap();
     let mut from_colors = File::create(o.join("4.rs")).unwrap();
+    let mut env_colors = File::create(o.join("5.rs")).unwrap();
     let mut n = 22usize;
 
     wr!(full => "pub mod full {{");
     wr!(full => "pub static EMPTY4: Image<&[u8], 4> = Image::make::<32, 32>();");
     wr!(full => "pub static EMPTY: Image<&[u8], 3> = Image::make::<32, 32>();");
 
+    wr!(half => "pub mod half {{");
+    wr!(half => "pub static EMPTY4: Image<&[u8], 4> = Image::make::<16, 16>();");
+    wr!(half => "pub static EMPTY: Image<&[u8], 3> = Image::make::<16, 16>();");
+
     wr!(quar => "pub mod quar {{");
     // forced to do this because try_into isnt const
     wr!(quar => "pub static EMPTY4: Image<&[u8], 4> = Image::make::<8, 8>();");
@@ -55,10 +60,11 @@ fn main() {
 
     wr!(to_colors => "phf::phf_map! {{");
     wr!(from_colors => "phf::phf_map! {{");
+    wr!(env_colors => "phf::phf_map! {{");
 
     let mut mapped = HashMap::with_capacity(68);
 
-    for mut file in [&full, &quar, &eigh] {
+    for mut file in [&full, &half, &quar, &eigh] {
         wr!(file => "use crate::utils::Image;");
         wr!(file => "pub static CLIFF: Image<&[u8], 4> = EMPTY4.copy();");
         for i in 1..=16 {
@@ -83,8 +89,17 @@ fn main() {
             }
             let f = path.file_name().unwrap().to_str().unwrap();
             if f.contains("bottom")
-                || f.contains("-team")
+                // team regions are drawn in the team color
+                || f.contains("-team") && !f.ends_with("-team.png")
                 || f.contains("-end")
+                    // bridge ends are drawn with the links
+                    && !matches!(
+                        f,
+                        "bridge-conveyor-end.png"
+                            | "phase-conveyor-end.png"
+                            | "bridge-conduit-end.png"
+                            | "phase-conduit-end.png"
+                    )
                 || f.contains("stack")
             {
                 continue;
@@ -104,20 +119,12 @@ fn main() {
                     | "NEOPLASIA_REACTOR_CENTER"
                     | "FLUX_REACTOR_MID"
                     | "EDGE"
-                    | "PHASE_CONVEYOR_BRIDGE"
                     | "BRIDGE_ARROW"
                     | "DUCT_BRIDGE_BRIDGE"
                     | "DUCT_BRIDGE_ARROW"
                     | "LAUNCHPOD"
-                    | "BRIDGE_CONVEYOR_BRIDGE"
-                    | "BRIDGE_CONVEYOR_ARROW"
-                    | "PHASE_CONVEYOR_ARROW"
                     | "REINFORCED_BRIDGE_CONDUIT_ARROW"
                     | "REINFORCED_BRIDGE_CONDUIT_BRIDGE"
-                    | "PHASE_CONDUIT_BRIDGE"
-                    | "BRIDGE_CONDUIT_ARROW"
-                    | "PHASE_CONDUIT_ARROW"
-                    | "BRIDGE_CONDUIT_BRIDGE"
                     | "PLATED_CONDUIT_CAP"
             ) {
                 continue;
@@ -157,7 +164,26 @@ fn main() {
                     }
                 }
                 wr!(from_colors => "0x{:x}_u32 => &crate::block::{path},", fimg::Pack::pack(&[r,g,b]));
-                wr!(to_colors => r#""{f}" => ({r}, {g}, {b}),"#)
+                wr!(to_colors => r#""{}" => ({r}, {g}, {b}),"#, f.trim_end_matches(".png"))
+            } else if env {
+                // the average of the opaque pixels
+                let (mut sum, mut count) = ([0u64; 3], 0u64);
+                for px in p
+                    .clone()
+                    .to_rgba()
+                    .bytes()
+                    .chunks_exact(4)
+                    .filter(|px| px[3] >= 128)
+                {
+                    for (s, &c) in sum.iter_mut().zip(px) {
+                        *s += u64::from(c);
+                    }
+                    count += 1;
+                }
+                if count != 0 {
+                    let [r, g, b] = sum.map(|s| s / count);
+                    wr!(env_colors => r#""{}" => ({r}, {g}, {b}),"#, f.trim_end_matches(".png"));
+                }
             }
 
             macro_rules! writ {
@@ -192,13 +218,13 @@ fn main() {
                 };
             }
             writ!(full / 1);
-            // writ!(half + 0.5);
+            writ!(half / 2);
             writ!(quar / 4);
             writ!(eigh / 8);
             n += 1;
         }
     }
-    for mut f in [full, eigh, quar, to_colors, from_colors] {
+    for mut f in [full, half, eigh, quar, to_colors, from_colors, env_colors] {
         f.write_all(b"}").unwrap();
     }
 }
@@ -1,20 +1,63 @@
 //! defense
 use crate::block::simple::*;
 use crate::block::*;
-make_simple!(HeatedBlock => |_, buff: &mut DataRead| read_heated(buff));
-make_simple!(RadarBlock => |_, buff: &mut DataRead| buff.skip(4));
-make_simple!(ShieldBlock => |_, buff: &mut DataRead| read_shield(buff));
+use crate::data::map::Extra;
+make_simple!(HeatedBlock => |b, buff: &mut DataRead| read_heated(b, buff), write_heated);
+make_simple!(RadarBlock => |b, buff: &mut DataRead| read_radar(b, buff), write_radar);
+make_simple!(ShieldBlock => |b, buff: &mut DataRead| read_shield(b, buff), write_shield);
 
 /// format:
 /// - heat: [`f32`]
 /// - phase heat: [`f32`]
-fn read_heated(buff: &mut DataRead) -> Result<(), DataReadError> {
-    buff.skip(8)
+fn read_heated(b: &mut Build, buff: &mut DataRead) -> Result<(), DataReadError> {
+    b.extra = Extra::Heated {
+        heat: buff.read_f32()?,
+        phase_heat: buff.read_f32()?,
+    };
+    Ok(())
+}
+
+fn write_heated(b: &Build, buff: &mut DataWrite) -> Result<(), DataWriteError> {
+    let (heat, phase_heat) = match b.extra {
+        Extra::Heated { heat, phase_heat } => (heat, phase_heat),
+        _ => (0.0, 0.0),
+    };
+    buff.write_f32(heat)?;
+    buff.write_f32(phase_heat)
+}
+
+/// format:
+/// - progress: [`f32`]
+fn read_radar(b: &mut Build, buff: &mut DataRead) -> Result<(), DataReadError> {
+    b.extra = Extra::Radar {
+        progress: buff.read_f32()?,
+    };
+    Ok(())
+}
+
+fn write_radar(b: &Build, buff: &mut DataWrite) -> Result<(), DataWriteError> {
+    buff.write_f32(match b.extra {
+        Extra::Radar { progress } => progress,
+        _ => 0.0,
+    })
 }
 
 /// format:
 /// - smoothing: [`f32`]
 /// - broken: [`bool`]
-fn read_shield(buff: &mut DataRead) -> Result<(), DataReadError> {
-    buff.skip(5)
+fn read_shield(b: &mut Build, buff: &mut DataRead) -> Result<(), DataReadError> {
+    b.extra = Extra::Shield {
+        smoothing: buff.read_f32()?,
+        broken: buff.read_bool()?,
+    };
+    Ok(())
+}
+
+fn write_shield(b: &Build, buff: &mut DataWrite) -> Result<(), DataWriteError> {
+    let (smoothing, broken) = match b.extra {
+        Extra::Shield { smoothing, broken } => (smoothing, broken),
+        _ => (0.0, false),
+    };
+    buff.write_f32(smoothing)?;
+    buff.write_bool(broken)
 }
@@ -4,38 +4,35 @@ use crate::block::*;
 use crate::content;
 use crate::data::autotile::tile;
 use crate::data::dynamic::DynType;
+use crate::block::payload::PayloadBuild;
+use crate::data::map::Extra;
 use crate::item;
 
 make_simple!(
     ConveyorBlock,
     |_, name, _, ctx: Option<&RenderingContext>, rot, s| tile(ctx.unwrap(), name, rot, s),
-    |_, buff: &mut DataRead| {
-        // format:
-        // - amount: `i32`
-        // - iterate amount:
-        //  - val: `i32`
-        //  - id = (((val >> 24) as u8) & 0xff) as u16
-        //  - x = (val >> 16) as u8) as f32 / 127.0
-        //  - y = ((val >> 8) as u8 as f32 + 128.0) / 255.0
-        let amount = buff.read_i32()?;
-        for _ in 0..amount {
-            buff.skip(4)?;
-        }
-        Ok(())
-    }
+    mapped read_conveyor,
+    write_conveyor
 );
 
 make_simple!(
     DuctBlock,
     |_, name, _, ctx: Option<&RenderingContext>, rot, s| tile(ctx.unwrap(), name, rot, s),
-    |_, buff: &mut DataRead| {
+    |b: &mut Build, buff: &mut DataRead| {
         // format:
         // - rec_dir: `i8`
-        buff.skip(1)
-    }
+        b.extra = Extra::Duct {
+            rec_dir: buff.read_i8()?,
+        };
+        Ok(())
+    },
+    |b: &Build, buff: &mut DataWrite| buff.write_i8(match b.extra {
+        Extra::Duct { rec_dir } => rec_dir,
+        _ => 0,
+    })
 );
 
-make_simple!(JunctionBlock => |_, buff| { read_directional_item_buffer(buff) });
+make_simple!(JunctionBlock => read_directional_item_buffer, write_directional_item_buffer);
 make_simple!(SimpleDuctBlock, |_, name, _, _, rot: Rotation, s| {
     let mut base = load!("duct-base", s);
     let mut top = load!(from name which is ["overflow-duct" "underflow-duct"], s);
@@ -113,10 +110,24 @@ fn draw_stack(
 make_simple!(
     StackConveyor,
     draw_stack,
-    // format:
-    // - link: `i32`
-    // - cooldown: `f32`
-    |_, buff: &mut DataRead| buff.skip(8)
+    |b: &mut Build, buff: &mut DataRead| {
+        // format:
+        // - link: `i32`
+        // - cooldown: `f32`
+        b.extra = Extra::StackConveyor {
+            link: buff.read_i32()?,
+            cooldown: buff.read_f32()?,
+        };
+        Ok(())
+    },
+    |b: &Build, buff: &mut DataWrite| {
+        let (link, cooldown) = match b.extra {
+            Extra::StackConveyor { link, cooldown } => (link, cooldown),
+            _ => (-1, 0.0),
+        };
+        buff.write_i32(link)?;
+        buff.write_f32(cooldown)
+    }
 );
 make_simple!(
     SurgeRouter,
@@ -127,10 +138,28 @@ make_simple!(
         unsafe { base.overlay(&top) };
         base
     },
-    |_, buff: &mut DataRead| buff.skip(2)
+    mapped |b: &mut Build, buff: &mut DataRead, content: &ContentMap| {
+        // format:
+        // - sort: `i16` as item
+        b.extra = Extra::Sort(content.item(buff.read_u16()?));
+        Ok(())
+    },
+    |b: &Build, buff: &mut DataWrite, content: &ContentMap| buff.write_i16(match b.extra {
+        Extra::Sort(Some(item)) => content.item_id(item) as i16,
+        _ => -1,
+    })
+);
+make_simple!(
+    UnitCargoLoader => |b: &mut Build, buff: &mut DataRead| {
+        // format: id: [`i32`]
+        b.extra = Extra::CargoLoader { unit: buff.read_i32()? };
+        Ok(())
+    },
+    |b: &Build, buff: &mut DataWrite| buff.write_i32(match b.extra {
+        Extra::CargoLoader { unit } => unit,
+        _ => -1,
+    })
 );
-// format: id: [`i32`]
-make_simple!(UnitCargoLoader => |_, buff: &mut DataRead| buff.skip(4));
 
 pub struct ItemBlock {
     size: u8,
@@ -229,26 +258,37 @@ impl BlockLogic for ItemBlock {
     /// (unit-cargo-unload-point)
     /// - item: `u16` as item
     /// - stale: `bool`
-    fn read(&self, b: &mut Build, buff: &mut DataRead) -> Result<(), DataReadError> {
+    fn read(&self, b: &mut Build, buff: &mut DataRead, content: &ContentMap) -> Result<(), DataReadError> {
+        b.state = Some(Self::create_state(content.item(buff.read_u16()?)));
         match b.block.name() {
             "duct-unloader" => {
-                let n = buff.read_i16()?;
-                if n != -1 {
-                    b.state = Some(Self::create_state(item::Type::try_from(n as u16).ok()));
-                }
-                buff.skip(2)?;
+                b.extra = Extra::DuctUnloader {
+                    offset: buff.read_u16()?,
+                };
             }
             "unit-cargo-unload-point" => {
-                b.state = Some(Self::create_state(
-                    item::Type::try_from(buff.read_u16()?).ok(),
-                ));
-                buff.skip(1)?;
-            }
-            _ => {
-                b.state = Some(Self::create_state(
-                    item::Type::try_from(buff.read_u16()?).ok(),
-                ));
+                b.extra = Extra::UnloadPoint {
+                    stale: buff.read_bool()?,
+                };
             }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn write(&self, b: &Build, buff: &mut DataWrite, content: &ContentMap) -> Result<(), data::map::WriteError> {
+        let item = b.state.as_ref().and_then(|s| *Self::get_state(s));
+        buff.write_i16(item.map_or(-1, |i| content.item_id(i) as i16))?;
+        match b.block.name() {
+            "duct-unloader" => buff.write_u16(match b.extra {
+                Extra::DuctUnloader { offset } => offset,
+                _ => 0,
+            })?,
+            "unit-cargo-unload-point" => buff.write_bool(match b.extra {
+                Extra::UnloadPoint { stale } => stale,
+                _ => false,
+            })?,
+            _ => {}
         }
         Ok(())
     }
@@ -382,9 +422,9 @@ impl BlockLogic for BridgeBlock {
 
     /// format:
     /// (item bridge)
-    /// - become [`read_buffered_item_bridge`]
+    /// - become [`read_item_bridge`]
     /// (buffered brige)
-    /// - become [`read_item_buffer`]
+    /// - become [`read_item_bridge`] (with the buffer)
     /// (mass driver) (9b)
     /// - link: [`i32`]
     /// - rotation: [`f32`]
@@ -402,14 +442,31 @@ impl BlockLogic for BridgeBlock {
         &self,
         t: &mut Build,
         buff: &mut crate::data::DataRead,
+        content: &ContentMap,
     ) -> Result<(), crate::data::ReadError> {
         match t.block.name() {
-            "bridge-conveyor" => read_buffered_item_bridge(buff)?,
-            "phase-conveyor" | "phase-conduit" | "bridge-conduit" => read_item_bridge(buff)?,
-            "mass-driver" => buff.skip(9)?,
+            "bridge-conveyor" => read_item_bridge(t, buff, true)?,
+            "phase-conveyor" | "phase-conduit" | "bridge-conduit" => {
+                read_item_bridge(t, buff, false)?;
+            }
+            "mass-driver" => {
+                t.extra = Extra::MassDriver {
+                    link: buff.read_i32()?,
+                    rotation: buff.read_f32()?,
+                    state: buff.read_u8()?,
+                };
+            }
             "payload-mass-driver" | "large-payload-mass-driver" => {
-                crate::block::payload::read_payload_block(buff)?;
-                buff.skip(19)?;
+                t.extra = Extra::PayloadMassDriver {
+                    payload: crate::block::payload::read_payload_block(buff, content)?,
+                    link: buff.read_i32()?,
+                    rotation: buff.read_f32()?,
+                    state: buff.read_u8()?,
+                    reload: buff.read_f32()?,
+                    charge: buff.read_f32()?,
+                    loaded: buff.read_bool()?,
+                    charging: buff.read_bool()?,
+                };
             }
             // no state?
             "duct-bridge" | "reinforced-bridge-conduit" => {}
@@ -418,6 +475,65 @@ impl BlockLogic for BridgeBlock {
         Ok(())
     }
 
+    fn write(&self, t: &Build, buff: &mut DataWrite, content: &ContentMap) -> Result<(), data::map::WriteError> {
+        match t.block.name() {
+            "bridge-conveyor" => write_item_bridge(t, buff, true)?,
+            "phase-conveyor" | "phase-conduit" | "bridge-conduit" => {
+                write_item_bridge(t, buff, false)?;
+            }
+            "mass-driver" => {
+                let (link, rotation, state) = match t.extra {
+                    Extra::MassDriver {
+                        link,
+                        rotation,
+                        state,
+                    } => (link, rotation, state),
+                    _ => (-1, 90.0, 0),
+                };
+                buff.write_i32(link)?;
+                buff.write_f32(rotation)?;
+                buff.write_u8(state)?;
+            }
+            "payload-mass-driver" | "large-payload-mass-driver" => {
+                let Extra::PayloadMassDriver {
+                    payload,
+                    link,
+                    rotation,
+                    state,
+                    reload,
+                    charge,
+                    loaded,
+                    charging,
+                } = &t.extra
+                else {
+                    crate::block::payload::write_payload_block(
+                        &PayloadBuild::default(),
+                        buff,
+                        content,
+                    )?;
+                    buff.write_i32(-1)?;
+                    buff.write_f32(90.0)?;
+                    buff.write_u8(0)?;
+                    buff.write_f32(0.0)?;
+                    buff.write_f32(0.0)?;
+                    buff.write_bool(false)?;
+                    buff.write_bool(false)?;
+                    return Ok(());
+                };
+                crate::block::payload::write_payload_block(payload, buff, content)?;
+                buff.write_i32(*link)?;
+                buff.write_f32(*rotation)?;
+                buff.write_u8(*state)?;
+                buff.write_f32(*reload)?;
+                buff.write_f32(*charge)?;
+                buff.write_bool(*loaded)?;
+                buff.write_bool(*charging)?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
     fn draw(
         &self,
         name: &str,
@@ -458,22 +574,68 @@ pub struct BridgeConvertError {
     pub y: i16,
 }
 
-/// format;
-/// - call [`read_item_bridge`]
-/// - become [`read_item_buffer`]
-fn read_buffered_item_bridge(buff: &mut DataRead) -> Result<(), DataReadError> {
-    read_item_bridge(buff)?;
-    read_item_buffer(buff)
+/// a conveyor item.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ConveyorItem {
+    pub item: item::Type,
+    /// sideways, in `-1..=1`
+    pub x: f32,
+    /// along the conveyor, in `0..=1`
+    pub y: f32,
 }
 
 /// format:
-/// - index: `u8`
-/// - iter `u8`
-///     l: `i64`
-fn read_item_buffer(buff: &mut DataRead) -> Result<(), DataReadError> {
-    buff.skip(1)?;
-    let n = buff.read_u8()? as usize;
-    buff.skip(n * 8)
+/// - amount: `i32`
+/// - iterate amount:
+///  - val: `i32`
+///  - id = (((val >> 24) as u8) & 0xff) as u16
+///  - x = (val >> 16) as u8) as f32 / 127.0
+///  - y = ((val >> 8) as u8 as f32 + 128.0) / 255.0
+fn read_conveyor(
+    b: &mut Build,
+    buff: &mut DataRead,
+    content: &ContentMap,
+) -> Result<(), DataReadError> {
+    let amount = buff.read_i32()?;
+    let mut items = Vec::with_capacity((amount.max(0) as usize).min(buff.remaining() / 4));
+    for _ in 0..amount {
+        let val = buff.read_i32()?;
+        items.push(ConveyorItem {
+            item: content
+                .item(u16::from((val >> 24) as u8))
+                .ok_or(DataReadError::Expected("conveyor item"))?,
+            x: f32::from((val >> 16) as i8) / 127.0,
+            y: (f32::from((val >> 8) as i8) + 128.0) / 255.0,
+        });
+    }
+    b.extra = Extra::Conveyor(items);
+    Ok(())
+}
+
+fn write_conveyor(
+    b: &Build,
+    buff: &mut DataWrite,
+    content: &ContentMap,
+) -> Result<(), DataWriteError> {
+    let items = match &b.extra {
+        Extra::Conveyor(items) => &items[..],
+        _ => &[],
+    };
+    buff.write_i32(items.len() as i32)?;
+    for i in items {
+        let x = (i.x * 127.0).round() as i8 as u8;
+        let y = (i.y * 255.0 - 128.0).round() as i8 as u8;
+        buff.write_bytes(&[content.item_id(i.item) as u8, x, y, 0])?;
+    }
+    Ok(())
+}
+
+/// the items a block holds on to.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ItemBuffer {
+    pub index: u8,
+    /// the items (and when they got there), packed
+    pub items: Vec<i64>,
 }
 
 /// format:
@@ -482,22 +644,97 @@ fn read_item_buffer(buff: &mut DataRead) -> Result<(), DataReadError> {
 /// - iterate `u8`
 ///     - incoming: `i32`
 /// - moved: `bool`
-fn read_item_bridge(buff: &mut DataRead) -> Result<(), DataReadError> {
-    buff.skip(8)?;
+/// - if `buffered`: [`read_item_buffer`]
+fn read_item_bridge(b: &mut Build, buff: &mut DataRead, buffered: bool) -> Result<(), DataReadError> {
+    let link = buff.read_i32()?;
+    let warmup = buff.read_f32()?;
     let n = buff.read_u8()? as usize;
-    buff.skip((n * 4) + 1)
+    let mut incoming = Vec::with_capacity(n);
+    for _ in 0..n {
+        incoming.push(buff.read_i32()?);
+    }
+    b.extra = Extra::ItemBridge {
+        link,
+        warmup,
+        incoming,
+        moved: buff.read_bool()?,
+        buffer: buffered.then(|| read_item_buffer(buff)).transpose()?,
+    };
+    Ok(())
+}
+
+fn write_item_bridge(b: &Build, buff: &mut DataWrite, buffered: bool) -> Result<(), DataWriteError> {
+    let (link, warmup, incoming, moved, buffer) = match &b.extra {
+        Extra::ItemBridge {
+            link,
+            warmup,
+            incoming,
+            moved,
+            buffer,
+        } => (*link, *warmup, &incoming[..], *moved, buffer.as_ref()),
+        _ => (-1, 0.0, &[][..], false, None),
+    };
+    buff.write_i32(link)?;
+    buff.write_f32(warmup)?;
+    buff.write_u8(incoming.len() as u8)?;
+    for &i in incoming {
+        buff.write_i32(i)?;
+    }
+    buff.write_bool(moved)?;
+    if buffered {
+        write_item_buffer(buffer.unwrap_or(&ItemBuffer::default()), buff)?;
+    }
+    Ok(())
+}
+
+/// format:
+/// - index: `u8`
+/// - iter `u8`
+///     l: `i64`
+fn read_item_buffer(buff: &mut DataRead) -> Result<ItemBuffer, DataReadError> {
+    let index = buff.read_u8()?;
+    let n = buff.read_u8()? as usize;
+    let mut items = Vec::with_capacity(n);
+    for _ in 0..n {
+        items.push(buff.read_i64()?);
+    }
+    Ok(ItemBuffer { index, items })
+}
+
+fn write_item_buffer(b: &ItemBuffer, buff: &mut DataWrite) -> Result<(), DataWriteError> {
+    buff.write_u8(b.index)?;
+    buff.write_u8(b.items.len() as u8)?;
+    for &i in &b.items {
+        buff.write_i64(i)?;
+    }
+    Ok(())
 }
 
 /// format:
 /// - iterate 4
-///     - u8
-///     - iterate u8
-///         - i64
-fn read_directional_item_buffer(buff: &mut DataRead) -> Result<(), DataReadError> {
-    for _ in 0..4 {
-        let _ = buff.read_u8()?;
-        let n = buff.read_u8()? as usize;
-        buff.skip(n * 8)?;
+///     - call [`read_item_buffer`]
+fn read_directional_item_buffer(b: &mut Build, buff: &mut DataRead) -> Result<(), DataReadError> {
+    b.extra = Extra::Junction([
+        read_item_buffer(buff)?,
+        read_item_buffer(buff)?,
+        read_item_buffer(buff)?,
+        read_item_buffer(buff)?,
+    ]);
+    Ok(())
+}
+
+fn write_directional_item_buffer(b: &Build, buff: &mut DataWrite) -> Result<(), DataWriteError> {
+    match &b.extra {
+        Extra::Junction(buffers) => {
+            for b in buffers {
+                write_item_buffer(b, buff)?;
+            }
+        }
+        _ => {
+            for _ in 0..4 {
+                write_item_buffer(&ItemBuffer::default(), buff)?;
+            }
+        }
     }
     Ok(())
 }
@@ -1,6 +1,7 @@
 //! extraction of raw resources (mine part)
 use crate::block::simple::make_simple;
 use crate::block::*;
+use crate::data::map::Extra;
 
 make_simple!(
     DrillBlock,
@@ -12,7 +13,8 @@ make_simple!(
         unsafe { base.overlay(&top) };
         base
     },
-    |_, buff: &mut DataRead| read_drill(buff)
+    |b, buff: &mut DataRead| read_drill(b, buff),
+    write_drill
 );
 make_simple!(WallDrillBlock, |_, _, _, _, rot: Rotation, scl| {
     let mut base = load!("cliff-crusher", scl);
@@ -25,6 +27,19 @@ make_simple!(WallDrillBlock, |_, _, _, _, rot: Rotation, scl| {
 /// format:
 /// - progress: [`f32`]
 /// - warmup: [`f32`]
-fn read_drill(buff: &mut DataRead) -> Result<(), DataReadError> {
-    buff.skip(8)
+fn read_drill(b: &mut Build, buff: &mut DataRead) -> Result<(), DataReadError> {
+    b.extra = Extra::Drill {
+        progress: buff.read_f32()?,
+        warmup: buff.read_f32()?,
+    };
+    Ok(())
+}
+
+fn write_drill(b: &Build, buff: &mut DataWrite) -> Result<(), DataWriteError> {
+    let (progress, warmup) = match b.extra {
+        Extra::Drill { progress, warmup } => (progress, warmup),
+        _ => (0.0, 0.0),
+    };
+    buff.write_f32(progress)?;
+    buff.write_f32(warmup)
 }
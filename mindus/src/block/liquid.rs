@@ -99,11 +99,16 @@ impl BlockLogic for FluidBlock {
 
     /// format:
     /// - fluid: [`u16`] as [`Fluid`](fluid::Type)
-    fn read(&self, b: &mut Build, buff: &mut DataRead) -> Result<(), DataReadError> {
+    fn read(&self, b: &mut Build, buff: &mut DataRead, content: &ContentMap) -> Result<(), DataReadError> {
         let f = buff.read_u16()?;
-        b.state = Some(Self::create_state(fluid::Type::try_from(f).ok()));
+        b.state = Some(Self::create_state(content.fluid(f)));
         Ok(())
     }
+
+    fn write(&self, b: &Build, buff: &mut DataWrite, content: &ContentMap) -> Result<(), data::map::WriteError> {
+        let fluid = b.state.as_ref().and_then(|s| *Self::get_state(s));
+        Ok(buff.write_i16(fluid.map_or(-1, |f| content.fluid_id(f) as i16))?)
+    }
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Error)]
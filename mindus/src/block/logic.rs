@@ -4,21 +4,52 @@ use std::string::FromUtf8Error;
 
 use crate::block::simple::*;
 use crate::data::dynamic::DynType;
+use crate::data::map::Extra;
 use crate::{block::*, Serializable};
 
 use crate::data::{self, CompressError, DataRead, DataWrite};
 
 make_simple!(
     MemoryBlock =>
-    |_, buff: &mut DataRead| {
+    |b: &mut Build, buff: &mut DataRead| {
         // format:
-        // - iterate [`u32`]
-        //     - memory: [`f64`]
-        let n = buff.read_u32()? as usize;
-        buff.skip(n * 8)
-    }
+        // - call [`read_memory`]
+        b.extra = Extra::Memory(read_memory(buff)?);
+        Ok(())
+    },
+    |b: &Build, buff: &mut DataWrite| write_memory(match &b.extra {
+        Extra::Memory(memory) => memory,
+        _ => &[],
+    }, buff)
 );
 
+/// format:
+/// - iterate [`u32`]
+///     - memory: [`f64`]
+fn read_memory(buff: &mut DataRead) -> Result<Vec<f64>, DataReadError> {
+    let n = buff.read_u32()? as usize;
+    let mut memory = Vec::with_capacity(n.min(buff.remaining() / 8));
+    for _ in 0..n {
+        memory.push(buff.read_f64()?);
+    }
+    Ok(memory)
+}
+
+#[test]
+fn memory_count() {
+    // a count the data cannot hold errors, instead of allocating it
+    let mut r = DataRead::new(&[0xff, 0xff, 0xff, 0xff, 0, 0, 0, 0, 0, 0, 0, 0]);
+    assert!(read_memory(&mut r).is_err());
+}
+
+fn write_memory(memory: &[f64], buff: &mut DataWrite) -> Result<(), DataWriteError> {
+    buff.write_u32(memory.len() as u32)?;
+    for &v in memory {
+        buff.write_f64(v)?;
+    }
+    Ok(())
+}
+
 pub struct CanvasBlock {
     size: u8,
     symmetric: bool,
@@ -129,7 +160,7 @@ impl BlockLogic for CanvasBlock {
             let p = Self::get_state(state);
             let offset = match s {
                 Scale::Full => 7,
-                // Scale::Half => 3,
+                Scale::Half => 3,
                 Scale::Quarter => 2,
                 Scale::Eigth => 1,
             };
@@ -159,7 +190,7 @@ impl BlockLogic for CanvasBlock {
     /// format:
     /// - len: [`i32`]
     /// - read(len) -> [`deser_canvas_image`]
-    fn read(&self, build: &mut Build, buff: &mut DataRead) -> Result<(), DataReadError> {
+    fn read(&self, build: &mut Build, buff: &mut DataRead, _: &ContentMap) -> Result<(), DataReadError> {
         let n = buff.read_i32()? as usize;
         let mut b = vec![0; n];
         buff.read_bytes(&mut b)?;
@@ -169,6 +200,18 @@ impl BlockLogic for CanvasBlock {
         )));
         Ok(())
     }
+
+    fn write(&self, build: &Build, buff: &mut DataWrite, _: &ContentMap) -> Result<(), data::map::WriteError> {
+        let data = match &build.state {
+            Some(state) => match self.serialize_state(state)? {
+                DynData::ByteArray(data) => data,
+                _ => unreachable!(),
+            },
+            None => vec![0; (self.canvas_size as usize).pow(2) * 3 / 8],
+        };
+        buff.write_i32(data.len() as i32)?;
+        Ok(buff.write_bytes(&data)?)
+    }
 }
 
 pub struct MessageLogic {
@@ -228,10 +271,16 @@ impl BlockLogic for MessageLogic {
         Ok(DynData::String(Some(Self::get_state(state).clone())))
     }
 
-    fn read(&self, b: &mut Build, buff: &mut DataRead) -> Result<(), DataReadError> {
+    fn read(&self, b: &mut Build, buff: &mut DataRead, _: &ContentMap) -> Result<(), DataReadError> {
         b.state = Some(Self::create_state(buff.read_utf()?.to_string()));
         Ok(())
     }
+
+    fn write(&self, b: &Build, buff: &mut DataWrite, _: &ContentMap) -> Result<(), data::map::WriteError> {
+        Ok(buff.write_utf(
+            b.state.as_ref().map_or("", |s| Self::get_state(s)),
+        )?)
+    }
 }
 
 pub struct SwitchLogic {
@@ -276,11 +325,17 @@ impl BlockLogic for SwitchLogic {
         Ok(DynData::Boolean(*Self::get_state(state)))
     }
 
-    fn read(&self, build: &mut Build, buff: &mut DataRead) -> Result<(), DataReadError> {
+    fn read(&self, build: &mut Build, buff: &mut DataRead, _: &ContentMap) -> Result<(), DataReadError> {
         build.state = Some(Self::create_state(buff.read_bool()?));
         Ok(())
     }
 
+    fn write(&self, build: &Build, buff: &mut DataWrite, _: &ContentMap) -> Result<(), data::map::WriteError> {
+        Ok(buff.write_bool(
+            build.state.as_ref().map_or(true, |s| *Self::get_state(s)),
+        )?)
+    }
+
     fn draw(
         &self,
         _: &str,
@@ -375,23 +430,64 @@ impl BlockLogic for ProcessorLogic {
         }
     }
 
-    fn read(&self, b: &mut Build, buff: &mut DataRead) -> Result<(), DataReadError> {
+    /// format:
+    /// - len: [`u32`]
+    /// - read(len) -> inflate -> [`read_decompressed`]
+    /// - iterate [`u32`]
+    ///     - name: [`utf`](DataRead::read_utf)
+    ///     - value: [`DynData`]
+    /// - call [`read_memory`]
+    fn read(&self, b: &mut Build, buff: &mut DataRead, _: &ContentMap) -> Result<(), DataReadError> {
         let n = buff.read_u32()? as usize;
-        let mut v = vec![0; n];
-        buff.read_bytes(&mut v)?;
-        v = DataRead::new(&v).deflate().unwrap();
+        let code = DataRead::new(buff.eat(n)?)
+            .deflate()
+            .map_err(|_| DataReadError::Expected("compressed processor"))?;
         b.state = Some(Self::create_state(
-            read_decompressed(&mut DataRead::new(&v)).unwrap(),
+            read_decompressed(&mut DataRead::new(&code))
+                .map_err(|_| DataReadError::Expected("processor"))?,
         ));
-        for _ in 0..buff.read_u32()? {
-            let _ = buff.read_utf()?;
-            let _ = DynData::deserialize(buff).unwrap();
+        let n = buff.read_u32()? as usize;
+        // a name and a value take at least 3 bytes
+        let mut vars = Vec::with_capacity(n.min(buff.remaining() / 3));
+        for _ in 0..n {
+            let name = buff.read_utf()?.to_string();
+            let value = DynData::deserialize(buff)
+                .map_err(|_| DataReadError::Expected("processor variable"))?;
+            vars.push((name, value));
         }
-        let memory = buff.read_u32()? as usize;
-        buff.skip(memory * 8)?;
+        b.extra = Extra::Processor {
+            vars,
+            memory: read_memory(buff)?,
+        };
         Ok(())
     }
 
+    fn write(&self, b: &Build, buff: &mut DataWrite, _: &ContentMap) -> Result<(), data::map::WriteError> {
+        let default;
+        let state = match &b.state {
+            Some(state) => state,
+            None => {
+                default = Self::create_state(ProcessorState::default());
+                &default
+            }
+        };
+        let DynData::ByteArray(code) = self.serialize_state(state)? else {
+            unreachable!()
+        };
+        buff.write_u32(code.len() as u32)?;
+        buff.write_bytes(&code)?;
+        let (vars, memory) = match &b.extra {
+            Extra::Processor { vars, memory } => (&vars[..], &memory[..]),
+            _ => (&[][..], &[][..]),
+        };
+        buff.write_u32(vars.len() as u32)?;
+        for (name, value) in vars {
+            buff.write_utf(name)?;
+            value.serialize(buff)?;
+        }
+        Ok(write_memory(memory, buff)?)
+    }
+
     fn mirror_state(&self, state: &mut State, horizontally: bool, vertically: bool) {
         for link in &mut Self::get_state_mut(state).links {
             if horizontally {
@@ -562,6 +658,17 @@ impl ProcessorState {
         self.create_link(link.name, link.x, link.y)
     }
 
+    /// move each link to `f(x, y)`, leaving out those it gives [`None`] for.
+    pub(crate) fn map_links(&mut self, mut f: impl FnMut(i16, i16) -> Option<(i16, i16)>) {
+        self.links.retain_mut(|link| match f(link.x, link.y) {
+            Some((x, y)) => {
+                (link.x, link.y) = (x, y);
+                true
+            }
+            None => false,
+        });
+    }
+
     pub fn remove_link(&mut self, idx: usize) -> Option<ProcessorLink> {
         if idx < self.links.len() {
             Some(self.links.remove(idx))
@@ -2,13 +2,17 @@
 //!
 //! different block types are organized into modules
 use bobbin_bits::U4::{self, B0000, B0001, B0010, B0100, B1000};
+use std::collections::HashMap;
 use std::error::Error;
 use std::fmt;
+use std::sync::{Mutex, OnceLock};
 
 use crate::data::dynamic::{DynData, DynType};
-use crate::data::map::Build;
+use crate::data::map::{Build, ContentMap};
 use crate::data::{self, renderer::*, CompressError};
-use crate::data::{DataRead, GridPos, ReadError as DataReadError};
+use crate::data::{
+    DataRead, DataWrite, GridPos, ReadError as DataReadError, WriteError as DataWriteError,
+};
 use crate::item::storage::ItemStorage;
 
 macro_rules! mods {
@@ -26,6 +30,7 @@ mods! {
     content defense distribution drills liquid logic payload power production turrets walls units
 }
 
+pub mod planner;
 pub mod ratios;
 mod simple;
 use simple::*;
@@ -218,8 +223,25 @@ pub trait BlockLogic: ratios::Ratios {
         unimplemented!("{name}")
     }
 
+    /// read the [`Build::extra`] (and the state, if it is in there), with ids mapped through `content`
     #[allow(unused_variables)]
-    fn read(&self, build: &mut Build, buff: &mut DataRead) -> Result<(), DataReadError> {
+    fn read(
+        &self,
+        build: &mut Build,
+        buff: &mut DataRead,
+        content: &ContentMap,
+    ) -> Result<(), DataReadError> {
+        Ok(())
+    }
+
+    /// write what [`BlockLogic::read`] reads (defaults, if the [`Build::extra`] is not this blocks), with ids from `content`
+    #[allow(unused_variables)]
+    fn write(
+        &self,
+        build: &Build,
+        buff: &mut DataWrite,
+        content: &ContentMap,
+    ) -> Result<(), data::map::WriteError> {
         Ok(())
     }
 }
@@ -303,7 +325,7 @@ impl SerializeError {
 
 /// a block. put it in stuff!
 pub struct Block {
-    image: Option<[Image<&'static [u8], 4>; 3]>,
+    image: Option<[Image<&'static [u8], 4>; 4]>,
     name: &'static str,
     logic: BlockLogicEnum,
 }
@@ -314,6 +336,9 @@ impl PartialEq for Block {
     }
 }
 
+/// how many bytes [`Block::placeholder`]s may leak, in all.
+pub const PLACEHOLDER_LIMIT: usize = 1 << 20;
+
 impl Block {
     /// create a new block
     #[must_use]
@@ -321,11 +346,54 @@ impl Block {
     pub(crate) const fn new(
         name: &'static str,
         logic: BlockLogicEnum,
-        image: Option<[Image<&'static [u8], 4>; 3]>,
+        image: Option<[Image<&'static [u8], 4>; 4]>,
     ) -> Self {
         Self { image, name, logic }
     }
 
+    /// a stand in for a block we dont know (eg one from a mod), that keeps its name and size.
+    /// it draws nothing.
+    ///
+    /// these are leaked, once for each name and size, until [`PLACEHOLDER_LIMIT`] bytes are taken; then [`None`].
+    /// ```
+    /// # use mindus::block::Block;
+    /// let b = Block::placeholder("modded-router", 1).unwrap();
+    /// assert_eq!(b.name(), "modded-router");
+    /// assert!(std::ptr::eq(b, Block::placeholder("modded-router", 1).unwrap()));
+    /// assert_eq!(Block::placeholder("modded-router", 3).unwrap().get_size(), 3);
+    /// ```
+    #[must_use]
+    pub fn placeholder(name: &str, size: u8) -> Option<&'static Block> {
+        type Placeholders = (HashMap<(String, u8), &'static Block>, usize);
+        static PLACEHOLDERS: OnceLock<Mutex<Placeholders>> = OnceLock::new();
+        let mut guard = PLACEHOLDERS
+            .get_or_init(Mutex::default)
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        let (placeholders, taken) = &mut *guard;
+        if let Some(&b) = placeholders.get(&(name.to_string(), size)) {
+            return Some(b);
+        }
+        let cost = std::mem::size_of::<Block>() + name.len() * 2;
+        if *taken + cost > PLACEHOLDER_LIMIT {
+            return None;
+        }
+        *taken += cost;
+        let b = Box::leak(Box::new(Block::new(
+            Box::leak(name.into()),
+            BlockLogicEnum::BasicBlock(BasicBlock::new(size, true, &[])),
+            Some(load!("empty4")),
+        )));
+        placeholders.insert((name.to_string(), size), b);
+        Some(b)
+    }
+
+    /// is this a [`Block::placeholder`]
+    #[must_use]
+    pub fn is_placeholder(&self) -> bool {
+        content::Type::by_name(self.name).is_none()
+    }
+
     /// this blocks name
     /// ```
     /// assert!(mindus::block::DISTRIBUTOR.name() == "distributor")
@@ -340,6 +408,28 @@ impl Block {
         <BlockLogicEnum as ratios::Ratios>::io(&self.logic, state, self.name)
     }
 
+    /// [`Block::io`], standing on `under` (every tile of the footprint):
+    /// drills, pumps and extractors get what the ground gives them.
+    /// drills that can be are boosted with water if `boost`.
+    /// ```
+    /// # use mindus::block::{content::Type, ratios::{Ground, Resource}, MECHANICAL_DRILL};
+    /// # use mindus::item::Type::Copper;
+    /// let copper = Ground::ore(Type::OreCopper);
+    /// let stone = Ground::floor(Type::Stone);
+    /// let io = MECHANICAL_DRILL.io_on(None, &[copper, copper, copper, stone], false);
+    /// // 600 ticks, and 50 more for each hardness, per ore tile
+    /// assert_eq!(io.net(Resource::Item(Copper)), 60.0 / 650.0 * 3.0);
+    /// ```
+    #[must_use]
+    pub fn io_on(
+        &self,
+        state: Option<&State>,
+        under: &[ratios::Ground],
+        boost: bool,
+    ) -> ratios::Io {
+        ratios::on(self.name, self.io(state), under, boost)
+    }
+
     /// should you send context to [`image`]?
     #[must_use]
     #[inline]
@@ -416,8 +506,23 @@ impl Block {
     }
 
     #[inline]
-    pub(crate) fn read(&self, build: &mut Build, buff: &mut DataRead) -> Result<(), DataReadError> {
-        self.logic.read(build, buff)
+    pub(crate) fn read(
+        &self,
+        build: &mut Build,
+        buff: &mut DataRead,
+        content: &ContentMap,
+    ) -> Result<(), DataReadError> {
+        self.logic.read(build, buff, content)
+    }
+
+    #[inline]
+    pub(crate) fn write(
+        &self,
+        build: &Build,
+        buff: &mut DataWrite,
+        content: &ContentMap,
+    ) -> Result<(), data::map::WriteError> {
+        self.logic.write(build, buff, content)
     }
 }
 
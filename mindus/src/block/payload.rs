@@ -7,8 +7,9 @@ use crate::block::{self, *};
 use crate::content;
 use crate::data::dynamic::DynType;
 use crate::data::entity_mapping;
+use crate::data::map::{self, Extra, WriteError};
 use crate::data::ReadError;
-use crate::unit;
+use crate::unit::{self, Unit};
 
 make_simple!(SimplePayloadBlock, |_, n, _, _, r: Rotation, scl| {
     match n {
@@ -54,7 +55,8 @@ make_simple!(
         unsafe { base.rotate(r.rotated(false).count()) };
         base
     },
-    read_payload_conveyor
+    mapped |b, buff: &mut DataRead, content| read_payload_conveyor(b, buff, content),
+    write_payload_conveyor
 );
 
 make_simple!(
@@ -67,7 +69,8 @@ make_simple!(
         unsafe { base.overlay(&over) };
         base
     },
-    read_payload_router
+    mapped read_payload_router,
+    write_payload_router
 );
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -158,40 +161,170 @@ impl BlockLogic for PayloadBlock {
     }
 }
 
+/// something carried by a payload block.
+#[derive(Clone, Debug)]
+pub enum PayloadEntity {
+    Build(Box<Build>),
+    Unit {
+        /// which [`UnitClass`](entity_mapping::UnitClass) it is
+        class: u8,
+        unit: Box<Unit>,
+    },
+}
+
+/// the payload of a payload block.
+#[derive(Clone, Debug, Default)]
+pub struct PayloadBuild {
+    /// where the payload is, relative to the center
+    pub position: (f32, f32),
+    pub rotation: f32,
+    pub payload: Option<PayloadEntity>,
+}
+
 /// format:
 /// - call [`read_payload_conveyor`]
-/// - t: [`u8`]
-/// - sort: [`u16`]
+/// - sort type: [`i8`]
+/// - sort: [`i16`]
 /// - recdir: [`u8`]
-fn read_payload_router(b: &mut Build, buff: &mut DataRead) -> Result<(), DataReadError> {
-    read_payload_conveyor(b, buff)?;
-    buff.skip(4)
+fn read_payload_router(
+    b: &mut Build,
+    buff: &mut DataRead,
+    content: &ContentMap,
+) -> Result<(), DataReadError> {
+    read_payload_conveyor(b, buff, content)?;
+    let Extra::PayloadConveyor {
+        progress,
+        rotation,
+        payload,
+    } = std::mem::take(&mut b.extra)
+    else {
+        unreachable!()
+    };
+    b.extra = Extra::PayloadRouter {
+        progress,
+        rotation,
+        payload,
+        sort_type: buff.read_i8()?,
+        sort: buff.read_i16()?,
+        rec_dir: buff.read_u8()?,
+    };
+    Ok(())
+}
+
+fn write_payload_router(
+    b: &Build,
+    buff: &mut DataWrite,
+    content: &ContentMap,
+) -> Result<(), WriteError> {
+    let (progress, rotation, payload, sort_type, sort, rec_dir) = match &b.extra {
+        Extra::PayloadRouter {
+            progress,
+            rotation,
+            payload,
+            sort_type,
+            sort,
+            rec_dir,
+        } => (*progress, *rotation, payload, *sort_type, *sort, *rec_dir),
+        _ => (0.0, 0.0, &None, -1, -1, 0),
+    };
+    buff.write_f32(progress)?;
+    buff.write_f32(rotation)?;
+    write_payload(payload.as_ref(), buff, content)?;
+    buff.write_i8(sort_type)?;
+    buff.write_i16(sort)?;
+    Ok(buff.write_u8(rec_dir)?)
 }
 
 /// format:
-/// - [`skip(4)`](`DataRead::skip`)
+/// - progress: [`f32`]
 /// - rot: [`f32`]
 /// - become [`read_payload`]
-fn read_payload_conveyor(_: &mut Build, buff: &mut DataRead) -> Result<(), DataReadError> {
-    buff.skip(8)?;
-    read_payload(buff)
+fn read_payload_conveyor(
+    b: &mut Build,
+    buff: &mut DataRead,
+    content: &ContentMap,
+) -> Result<(), DataReadError> {
+    b.extra = Extra::PayloadConveyor {
+        progress: buff.read_f32()?,
+        rotation: buff.read_f32()?,
+        payload: read_payload(buff, content)?,
+    };
+    Ok(())
+}
+
+fn write_payload_conveyor(
+    b: &Build,
+    buff: &mut DataWrite,
+    content: &ContentMap,
+) -> Result<(), WriteError> {
+    let (progress, rotation, payload) = match &b.extra {
+        Extra::PayloadConveyor {
+            progress,
+            rotation,
+            payload,
+        } => (*progress, *rotation, payload),
+        _ => (0.0, 0.0, &None),
+    };
+    buff.write_f32(progress)?;
+    buff.write_f32(rotation)?;
+    write_payload(payload.as_ref(), buff, content)
 }
 
 /// format:
 /// - iterate [`i16`]..0
-///     - [`u8`], [`i16`], [`i32`]
-pub(crate) fn read_payload_seq(buff: &mut DataRead) -> Result<(), DataReadError> {
-    let amount = (-buff.read_i16()?) as usize;
-    buff.skip(amount * 7)
+///     - content type: [`u8`] as [`content::Type`]
+///     - id: [`u16`]
+///     - amount: [`i32`]
+pub(crate) fn read_payload_seq(
+    buff: &mut DataRead,
+) -> Result<Vec<(content::Type, u16, i32)>, DataReadError> {
+    let amount = -buff.read_i16()?;
+    let mut seq = Vec::with_capacity(amount.max(0) as usize);
+    for _ in 0..amount {
+        let ty = content::Type::try_from(buff.read_u8()?)
+            .map_err(|_| ReadError::Expected("content type"))?;
+        seq.push((ty, buff.read_u16()?, buff.read_i32()?));
+    }
+    Ok(seq)
+}
+
+pub(crate) fn write_payload_seq(
+    seq: &[(content::Type, u16, i32)],
+    buff: &mut DataWrite,
+) -> Result<(), data::WriteError> {
+    buff.write_i16(-(seq.len() as i16))?;
+    for &(ty, id, amount) in seq {
+        buff.write_u8(ty.into())?;
+        buff.write_u16(id)?;
+        buff.write_i32(amount)?;
+    }
+    Ok(())
 }
 
 /// format:
 /// - vector: ([`f32`], [`f32`])
 /// - rotation: [`f32`]
 /// - become [`read_payload`]
-pub(crate) fn read_payload_block(buff: &mut DataRead) -> Result<(), DataReadError> {
-    buff.skip(12)?;
-    read_payload(buff)
+pub(crate) fn read_payload_block(
+    buff: &mut DataRead,
+    content: &ContentMap,
+) -> Result<PayloadBuild, DataReadError> {
+    Ok(PayloadBuild {
+        position: (buff.read_f32()?, buff.read_f32()?),
+        rotation: buff.read_f32()?,
+        payload: read_payload(buff, content)?,
+    })
+}
+
+pub(crate) fn write_payload_block(
+    p: &PayloadBuild,
+    buff: &mut DataWrite,
+    content: &ContentMap,
+) -> Result<(), WriteError> {
+    buff.write_f32(p.position.0)?;
+    buff.write_f32(p.position.1)?;
+    buff.write_f32(p.rotation)?;
+    write_payload(p.payload.as_ref(), buff, content)
 }
 
 /// format:
@@ -201,13 +334,18 @@ pub(crate) fn read_payload_block(buff: &mut DataRead) -> Result<(), DataReadErro
 /// - if type == `1` (payload block):
 ///     - block: [`u16`]
 ///     - version: [`u8`]
-///     - [`BlockLogic::read`] (recursion :ferrisHmm:),
-/// - if type == 2 (paylood unit):
+///     - [`Build::read`] (recursion :ferrisHmm:),
+/// - if type == 0 (paylood unit):
 ///     - id: [`u8`]
-///     - call [`UnitClass::read`](crate::data::entity_mapping::UnitClass::read)
-pub fn read_payload(buff: &mut DataRead) -> Result<(), DataReadError> {
+///     - call [`UnitClass::read_mapped`](crate::data::entity_mapping::UnitClass::read_mapped)
+///
+/// ids are mapped through `content`.
+pub fn read_payload(
+    buff: &mut DataRead,
+    content: &ContentMap,
+) -> Result<Option<PayloadEntity>, DataReadError> {
     if !buff.read_bool()? {
-        return Ok(());
+        return Ok(None);
     }
     let t = buff.read_u8()?;
     const BLOCK: u8 = 1;
@@ -215,24 +353,58 @@ pub fn read_payload(buff: &mut DataRead) -> Result<(), DataReadError> {
     match t {
         BLOCK => {
             let b = buff.read_u16()?;
-            buff.skip(1)?;
-            let block = BlockEnum::try_from(b)
-                .unwrap_or(BlockEnum::Router)
-                .to_block()
-                .expect("payload should not be a environment block");
+            let block = content
+                .block(b)
+                .and_then(BlockEnum::to_block)
+                .ok_or(ReadError::Expected("payload block"))?;
             let mut b = Build::new(block);
-            let _ = b.read(buff);
+            b.revision = buff.read_i8()?;
+            b.read_all(buff, content).map_err(|e| match e {
+                map::ReadError::Read(e) => e,
+                _ => ReadError::Expected("payload build"),
+            })?;
+            Ok(Some(PayloadEntity::Build(Box::new(b))))
         }
         UNIT => {
-            let u = buff.read_u8()? as usize;
-            let Some(&Some(u)) = entity_mapping::ID.get(u) else {
+            let class = buff.read_u8()?;
+            let Some(&Some(u)) = entity_mapping::ID.get(class as usize) else {
                 return Err(ReadError::Expected("map entry"));
             };
-            let _ = u.read(buff)?;
+            let start = buff.data;
+            let mut unit = u
+                .read_mapped(buff, content)?
+                .ok_or(ReadError::Expected("payload unit type"))?;
+            unit.raw = start[..start.len() - buff.data.len()].into();
+            Ok(Some(PayloadEntity::Unit {
+                class,
+                unit: Box::new(unit),
+            }))
+        }
+        _ => Err(ReadError::Expected("0 | 1")),
+    }
+}
+
+/// write what [`read_payload`] reads, with ids from `content`.
+pub fn write_payload(
+    payload: Option<&PayloadEntity>,
+    buff: &mut DataWrite,
+    content: &ContentMap,
+) -> Result<(), WriteError> {
+    buff.write_bool(payload.is_some())?;
+    match payload {
+        None => Ok(()),
+        Some(PayloadEntity::Build(b)) => {
+            buff.write_u8(1)?;
+            buff.write_u16(content.block_id(b.block).unwrap_or(0))?;
+            buff.write_i8(b.revision)?;
+            b.write_mapped(buff, content)
+        }
+        Some(PayloadEntity::Unit { class, unit }) => {
+            buff.write_u8(0)?;
+            buff.write_u8(*class)?;
+            Ok(buff.write_bytes(&unit.raw)?)
         }
-        _ => return Err(ReadError::Expected("0 | 1")),
     }
-    Ok(())
 }
 
 #[cfg(test)]
@@ -242,13 +414,29 @@ mod tests {
     #[test]
     fn payload_conv() {
         let mut r = DataRead::new(&[0, 0, 0, 0, 0, 0, 0, 0, 0]);
-        read_payload_conveyor(&mut Build::new(&PAYLOAD_CONVEYOR), &mut r).unwrap();
+        let content = ContentMap::default();
+        read_payload_conveyor(&mut Build::new(&PAYLOAD_CONVEYOR), &mut r, &content).unwrap();
         assert!(r.read_bool().is_err());
-        let mut r = DataRead::new(&[
-            65, 198, 232, 0, 67, 51, 255, 249, 1, 1, 0, 157, 0, 67, 197, 128, 0, 128, 1, 3,
-        ]);
-        read_payload_conveyor(&mut Build::new(&PAYLOAD_CONVEYOR), &mut r).unwrap();
+        let bytes = [
+            65, 198, 232, 0, 67, 51, 255, 249, 1, 1, 0, 157, 0, 67, 197, 128, 0, 128, 1, 3, 1, 0,
+            0, 0, 0, 0, 0, 0, 63, 128, 0, 0,
+        ];
+        let mut r = DataRead::new(&bytes);
+        let mut b = Build::new(&PAYLOAD_CONVEYOR);
+        read_payload_conveyor(&mut b, &mut r, &content).unwrap();
         assert!(r.read_bool().is_err());
+        let Extra::PayloadConveyor {
+            payload: Some(PayloadEntity::Build(crucible)),
+            ..
+        } = &b.extra
+        else {
+            panic!("{:?}", b.extra)
+        };
+        assert_eq!(crucible.name(), "silicon-crucible");
+        assert!(matches!(crucible.extra, Extra::Crafter { warmup, .. } if warmup == 1.0));
+        let mut w = DataWrite::default();
+        write_payload_conveyor(&b, &mut w, &content).unwrap();
+        assert_eq!(w.get_written(), bytes);
     }
 }
 
@@ -0,0 +1,274 @@
+//! work out what it takes to make something, from the [ratios](super::ratios) of every block
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::ratios::{Io, IoBuilder, Resource};
+use super::{Block, BLOCK_REGISTRY};
+use crate::fluid::Type as Fluid;
+use crate::item::Type as Item;
+
+/// what gets mined or pumped (or generated), instead of made
+const RAW: &[Resource] = &[
+    Resource::Item(Item::Copper),
+    Resource::Item(Item::Lead),
+    Resource::Item(Item::Sand),
+    Resource::Item(Item::Coal),
+    Resource::Item(Item::Titanium),
+    Resource::Item(Item::Thorium),
+    Resource::Item(Item::Scrap),
+    Resource::Item(Item::Beryllium),
+    Resource::Item(Item::Tungsten),
+    Resource::Fluid(Fluid::Water),
+    Resource::Fluid(Fluid::Arkycite),
+    Resource::Power,
+    Resource::Heat,
+];
+
+/// plans production chains.
+/// ```
+/// # use mindus::block::planner::Planner;
+/// # use mindus::block::ratios::Resource;
+/// # use mindus::item::Type::*;
+/// let plan = Planner::default().plan(Resource::Item(Silicon), 10.0);
+/// assert_eq!(plan.factories[0].0.name(), "silicon-smelter");
+/// assert_eq!(
+///     plan.input,
+///     [(Resource::Item(Sand), 20.0), (Resource::Item(Coal), 10.0), (Resource::Power, 200.0)]
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct Planner {
+    raw: BTreeSet<Resource>,
+    prefer: Vec<&'static Block>,
+}
+
+impl Default for Planner {
+    /// ores, water, power and heat are raw, and no recipes are preferred
+    fn default() -> Self {
+        Self {
+            raw: RAW.iter().copied().collect(),
+            prefer: vec![],
+        }
+    }
+}
+
+/// the result of [`Planner::plan`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Plan {
+    /// how many of each block (they may be part-time)
+    pub factories: Vec<(&'static Block, f32)>,
+    /// what goes in, per second
+    pub input: Vec<(Resource, f32)>,
+    /// what else comes out, per second
+    pub byproducts: Vec<(Resource, f32)>,
+}
+
+impl Planner {
+    /// make everything `block` outputs with `block`, even if it is raw.
+    /// earlier preferences win.
+    /// ```
+    /// # use mindus::block::{planner::Planner, ratios::Resource, SILICON_CRUCIBLE};
+    /// # use mindus::item::Type::*;
+    /// let plan = Planner::default()
+    ///     .prefer(&SILICON_CRUCIBLE)
+    ///     .plan(Resource::Item(Silicon), 10.0);
+    /// assert_eq!(plan.factories[0].0.name(), "silicon-crucible");
+    /// // it needs pyratite, which needs a mixer
+    /// assert_eq!(plan.factories[1].0.name(), "pyratite-mixer");
+    /// ```
+    #[must_use]
+    pub fn prefer(mut self, block: &'static Block) -> Self {
+        self.prefer.push(block);
+        self
+    }
+
+    /// dont make `resource`, take it as input.
+    #[must_use]
+    pub fn raw(mut self, resource: Resource) -> Self {
+        self.raw.insert(resource);
+        self
+    }
+
+    /// the recipe for `resource`, if it is made.
+    /// without a preference, the recipe with the fewest inputs that are not raw,
+    /// then the fewest inputs, then the most output, wins.
+    fn recipe(&self, resource: Resource) -> Option<(&'static Block, Io)> {
+        let makes = |io: &Io| {
+            io.output
+                .iter()
+                .find(|&&(r, _)| r == resource)
+                .map(|&(_, n)| n)
+        };
+        if let Some((b, io)) = self
+            .prefer
+            .iter()
+            .map(|&b| (b, b.io(None)))
+            .find(|(_, io)| makes(io).is_some())
+        {
+            return Some((b, io));
+        }
+        if self.raw.contains(&resource) {
+            return None;
+        }
+        BLOCK_REGISTRY
+            .values()
+            .filter_map(|&b| {
+                let io = b.io(None);
+                let n = makes(&io)?;
+                if io.input.iter().any(|&(r, _)| r == resource) {
+                    return None;
+                }
+                let made = io
+                    .input
+                    .iter()
+                    .filter(|(r, _)| !self.raw.contains(r))
+                    .count();
+                Some(((made, io.input.len(), -n, b.name()), b, io))
+            })
+            .min_by(|(a, ..), (b, ..)| a.partial_cmp(b).unwrap_or(Ordering::Equal))
+            .map(|(_, b, io)| (b, io))
+    }
+
+    /// picks recipes for `resource` and what it needs, and puts them in `order` after what needs them
+    fn visit(
+        &self,
+        resource: Resource,
+        recipes: &mut BTreeMap<Resource, Option<(&'static Block, Io)>>,
+        stack: &mut Vec<Resource>,
+        order: &mut Vec<Resource>,
+    ) {
+        if recipes.contains_key(&resource) {
+            return;
+        }
+        let recipe = self.recipe(resource).filter(|(_, io)| {
+            // a cycle: take it as raw
+            !io.input
+                .iter()
+                .any(|(r, _)| *r == resource || stack.contains(r))
+        });
+        let Some((b, io)) = recipe else {
+            recipes.insert(resource, None);
+            return;
+        };
+        stack.push(resource);
+        for &(r, _) in &*io.input {
+            self.visit(r, recipes, stack, order);
+        }
+        stack.pop();
+        recipes.insert(resource, Some((b, io)));
+        order.push(resource);
+    }
+
+    /// works out how to make `rate` of `resource` per second.
+    /// by-products are used before anything else is built to make them.
+    #[must_use]
+    pub fn plan(&self, resource: Resource, rate: f32) -> Plan {
+        let mut recipes = BTreeMap::new();
+        let mut order = vec![];
+        self.visit(resource, &mut recipes, &mut vec![], &mut order);
+        let mut net = IoBuilder::default();
+        net.net.insert(resource, -rate);
+        let mut factories: Vec<(&'static Block, f32)> = vec![];
+        for r in order.into_iter().rev() {
+            let Some((b, io)) = &recipes[&r] else {
+                continue;
+            };
+            let need = -net.net.get(&r).copied().unwrap_or(0.0);
+            if need <= 0.0 {
+                continue;
+            }
+            let &(_, each) = io.output.iter().find(|&&(o, _)| o == r).unwrap();
+            let n = need / each;
+            match factories.iter_mut().find(|(f, _)| f.name() == b.name()) {
+                Some((_, count)) => *count += n,
+                None => factories.push((b, n)),
+            }
+            for &(r, v) in &*io.output {
+                *net.net.entry(r).or_default() += v * n;
+            }
+            for &(r, v) in &*io.input {
+                *net.net.entry(r).or_default() -= v * n;
+            }
+        }
+        let Io { input, output } = net.into();
+        let (mut input, mut byproducts) = (input.into_owned(), output.into_owned());
+        input.sort_by_key(|&(r, _)| r);
+        byproducts.sort_by_key(|&(r, _)| r);
+        Plan {
+            factories,
+            input,
+            byproducts,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::{ELECTROLYZER, SEPARATOR};
+
+    #[test]
+    fn chain() {
+        let plan = Planner::default().plan(Resource::Item(Item::Plastanium), 1.0);
+        let names = plan
+            .factories
+            .iter()
+            .map(|(b, _)| b.name())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            ["plastanium-compressor", "spore-press", "cultivator"]
+        );
+        // 15 oil from 0.83 spore presses, from 4.17 cultivators
+        assert!((plan.factories[2].1 - 4.17).abs() < 0.01);
+        assert_eq!(
+            plan.input,
+            [
+                (Resource::Item(Item::Titanium), 2.0),
+                (Resource::Fluid(Fluid::Water), 75.0),
+                (Resource::Power, 548.33)
+            ]
+        );
+        assert!(plan.byproducts.is_empty());
+    }
+
+    #[test]
+    fn byproducts() {
+        // one electrolyzer makes both
+        let plan = Planner::default().plan(Resource::Fluid(Fluid::Ozone), 4.0);
+        assert_eq!(plan.factories, [(&ELECTROLYZER, 1.0)]);
+        assert_eq!(plan.byproducts, [(Resource::Fluid(Fluid::Hydrogen), 6.0)]);
+
+        let plan = Planner::default()
+            .prefer(&SEPARATOR)
+            .plan(Resource::Item(Item::Titanium), 0.28);
+        assert_eq!(
+            plan.factories
+                .iter()
+                .map(|(b, n)| (b.name(), (n * 100.0).round()))
+                .collect::<Vec<_>>(),
+            [("separator", 100.0), ("melter", 33.0)]
+        );
+        assert_eq!(
+            plan.input,
+            [(Resource::Item(Item::Scrap), 2.0), (Resource::Power, 86.0)]
+        );
+        assert_eq!(
+            plan.byproducts,
+            [
+                (Resource::Item(Item::Copper), 0.71),
+                (Resource::Item(Item::Lead), 0.43),
+                (Resource::Item(Item::Graphite), 0.28)
+            ]
+        );
+    }
+
+    #[test]
+    fn raw() {
+        let plan = Planner::default()
+            .raw(Resource::Item(Item::Silicon))
+            .plan(Resource::Item(Item::Silicon), 2.0);
+        assert!(plan.factories.is_empty());
+        assert_eq!(plan.input, [(Resource::Item(Item::Silicon), 2.0)]);
+    }
+}
@@ -4,10 +4,11 @@ use thiserror::Error;
 use crate::block::simple::*;
 use crate::block::*;
 use crate::data::dynamic::DynType;
+use crate::data::map::Extra;
 
-make_simple!(GeneratorBlock => |_, buff: &mut DataRead| read_generator(buff));
-make_simple!(NuclearGeneratorBlock => |_, buff: &mut DataRead| read_nuclear(buff));
-make_simple!(ImpactReactorBlock => |_, buff: &mut DataRead| read_impact(buff));
+make_simple!(GeneratorBlock => |b, buff: &mut DataRead| read_generator(b, buff, false), write_generator);
+make_simple!(NuclearGeneratorBlock => |b, buff: &mut DataRead| read_generator(b, buff, true), write_generator);
+make_simple!(ImpactReactorBlock => |b, buff: &mut DataRead| read_generator(b, buff, true), write_generator);
 make_simple!(
     Neoplasia,
     |_, _, _, _, rot: Rotation, scl| {
@@ -20,7 +21,8 @@ make_simple!(
         unsafe { base.overlay(&top) };
         base
     },
-    |_, buff: &mut DataRead| read_heater(buff)
+    |b, buff: &mut DataRead| read_generator(b, buff, true),
+    write_generator
 );
 make_simple!(DiodeBlock, |_, _, _, _, rot: Rotation, s| {
     let mut base = load!("diode", s);
@@ -179,35 +181,50 @@ impl BlockLogic for LampBlock {
         let state = Self::get_state(state);
         Ok(DynData::Int(u32::from(*state) as i32))
     }
-}
 
-/// format:
-/// - production efficiency: [`f32`]
-/// - generate time: [`f32`]
-fn read_generator(buff: &mut DataRead) -> Result<(), DataReadError> {
-    buff.skip(8)
-}
+    /// format:
+    /// - color: [`u32`] as [`Rgba`]
+    fn read(&self, build: &mut Build, buff: &mut DataRead, _: &ContentMap) -> Result<(), DataReadError> {
+        build.state = Some(Self::create_state(Rgba::from(buff.read_u32()?)));
+        Ok(())
+    }
 
-/// format:
-/// - call [`read_generator`]
-/// - heat: [`f32`]
-fn read_nuclear(buff: &mut DataRead) -> Result<(), DataReadError> {
-    read_generator(buff)?;
-    buff.skip(4)
+    fn write(&self, build: &Build, buff: &mut DataWrite, _: &ContentMap) -> Result<(), data::map::WriteError> {
+        let color = build.state.as_ref().map_or(Rgba(255, 255, 255, 255), |s| *Self::get_state(s));
+        Ok(buff.write_u32(color.into())?)
+    }
 }
 
 /// format:
-/// - call [`read_generator`]
-/// - warmup: [`f32`]
-fn read_impact(buff: &mut DataRead) -> Result<(), DataReadError> {
-    read_generator(buff)?;
-    buff.skip(4)
+/// - production efficiency: [`f32`]
+/// - generate time: [`f32`]
+/// - (nuclear, neoplasia) heat / (impact) warmup: [`f32`]
+fn read_generator(b: &mut Build, buff: &mut DataRead, heat: bool) -> Result<(), DataReadError> {
+    b.extra = Extra::Generator {
+        productivity: buff.read_f32()?,
+        generate_time: buff.read_f32()?,
+        heat: heat.then(|| buff.read_f32()).transpose()?,
+    };
+    Ok(())
 }
 
-/// format:
-/// - call [`read_generator`]
-/// - heat: [`f32`]
-fn read_heater(buff: &mut DataRead) -> Result<(), DataReadError> {
-    read_generator(buff)?;
-    buff.skip(4)
+fn write_generator(b: &Build, buff: &mut DataWrite) -> Result<(), DataWriteError> {
+    let (productivity, generate_time, heat) = match b.extra {
+        Extra::Generator {
+            productivity,
+            generate_time,
+            heat,
+        } => (productivity, generate_time, heat),
+        _ => (0.0, 0.0, None),
+    };
+    buff.write_f32(productivity)?;
+    buff.write_f32(generate_time)?;
+    let heats = matches!(
+        b.block.name(),
+        "thorium-reactor" | "impact-reactor" | "neoplasia-reactor"
+    );
+    if heats {
+        buff.write_f32(heat.unwrap_or(0.0))?;
+    }
+    Ok(())
 }
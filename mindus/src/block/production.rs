@@ -1,10 +1,10 @@
 //! the industry part of mindustry
 use crate::block::simple::*;
 use crate::block::*;
+use crate::data::map::Extra;
 use crate::data::DataRead;
 
-// format: call [`read_production_block`], seed: [`i32`]
-make_simple!(SeparatorBlock => |_, buff: &mut DataRead| buff.skip(12));
+make_simple!(SeparatorBlock => |b, buff: &mut DataRead| read_separator(b, buff), write_separator);
 
 make_simple!(
     ProductionBlock,
@@ -28,13 +28,10 @@ make_simple!(
         base
     },
     |b: &mut Build, buff: &mut DataRead| {
-        // format:
-        // - progress: `f32`
-        // - warmup: `f32`
-        // (cultivator)
-        // `f32`
-        buff.skip(8 + if b.name() == "cultivator" { 4 } else { 0 })
-    }
+        let cultivator = b.name() == "cultivator";
+        read_crafter(b, buff, cultivator)
+    },
+    |b: &Build, buff: &mut DataWrite| write_crafter(b, buff, b.name() == "cultivator")
 );
 
 make_simple!(
@@ -53,14 +50,8 @@ make_simple!(
         unsafe { base.overlay(&top) };
         base
     },
-    |_, buff: &mut DataRead| {
-        // format:
-        // - progress: `f32`
-        // - warmup: `f32`
-        // - heat: f32
-        buff.skip(12)?;
-        Ok(())
-    }
+    |b, buff: &mut DataRead| read_crafter(b, buff, true),
+    |b, buff: &mut DataWrite| write_crafter(b, buff, true)
 );
 make_simple!(HeatConduit, |_, n, _, _, r: Rotation, s| {
     let mut base = load!(from n which is ["heat-router" | "heat-redirector"], s);
@@ -76,3 +67,60 @@ make_simple!(HeatConduit, |_, n, _, _, r: Rotation, s| {
     unsafe { base.overlay(&top) };
     base
 });
+
+/// format:
+/// - progress: [`f32`]
+/// - warmup: [`f32`]
+/// - if `heat` (heat crafters, cultivator): heat: [`f32`]
+fn read_crafter(b: &mut Build, buff: &mut DataRead, heat: bool) -> Result<(), DataReadError> {
+    b.extra = Extra::Crafter {
+        progress: buff.read_f32()?,
+        warmup: buff.read_f32()?,
+        heat: heat.then(|| buff.read_f32()).transpose()?,
+    };
+    Ok(())
+}
+
+fn write_crafter(b: &Build, buff: &mut DataWrite, heats: bool) -> Result<(), DataWriteError> {
+    let (progress, warmup, heat) = match b.extra {
+        Extra::Crafter {
+            progress,
+            warmup,
+            heat,
+        } => (progress, warmup, heat),
+        _ => (0.0, 0.0, None),
+    };
+    buff.write_f32(progress)?;
+    buff.write_f32(warmup)?;
+    if heats {
+        buff.write_f32(heat.unwrap_or(0.0))?;
+    }
+    Ok(())
+}
+
+/// format:
+/// - progress: [`f32`]
+/// - warmup: [`f32`]
+/// - seed: [`i32`]
+fn read_separator(b: &mut Build, buff: &mut DataRead) -> Result<(), DataReadError> {
+    b.extra = Extra::Separator {
+        progress: buff.read_f32()?,
+        warmup: buff.read_f32()?,
+        seed: buff.read_i32()?,
+    };
+    Ok(())
+}
+
+fn write_separator(b: &Build, buff: &mut DataWrite) -> Result<(), DataWriteError> {
+    let (progress, warmup, seed) = match b.extra {
+        Extra::Separator {
+            progress,
+            warmup,
+            seed,
+        } => (progress, warmup, seed),
+        _ => (0.0, 0.0, 0),
+    };
+    buff.write_f32(progress)?;
+    buff.write_f32(warmup)?;
+    buff.write_i32(seed)
+}
@@ -1,29 +1,62 @@
 use std::borrow::Cow;
 use std::collections::HashMap;
 
+use super::content::Type as Floor;
 use super::State;
+use crate::content::Content;
 use crate::unit::Type::*;
 #[macro_export]
 macro_rules! ratios {
     ([$($input:ident: $icnt:literal),*] => [$($output:ident: $ocnt:literal),*]) => {{
         #[allow(unused_imports)]
-        use $crate::{fluid::Type::*, item::Type::*, block::ConstFrom};
+        use $crate::{fluid::Type::*, item::Type::*, block::ConstFrom, block::ratios::{Power, Heat, Battery}};
         const I: &[($crate::block::ratios::Resource, ::std::primitive::f32)] = &[$(($crate::block::ratios::Resource::fro($input), $icnt as ::std::primitive::f32),)*];
         const O: &[($crate::block::ratios::Resource, ::std::primitive::f32)] = &[$(($crate::block::ratios::Resource::fro($output), $ocnt as ::std::primitive::f32),)*];
         $crate::block::ratios::Io::new(I, O)
     }};
     [$($input:ident: $cnt:literal),*] => {{
         #[allow(unused_imports)]
-        use $crate::{fluid::Type::*, item::Type::*, block::ConstFrom};
+        use $crate::{fluid::Type::*, item::Type::*, block::ConstFrom, block::ratios::{Power, Heat, Battery}};
         const I: &[($crate::block::ratios::Resource, ::std::primitive::f32)] = &[$(($crate::block::ratios::Resource::fro($input), $cnt as ::std::primitive::f32),)*];
         $crate::block::ratios::Io::new(I, &[][..])
     }}
 }
 pub use ratios;
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Resource {
     Item(crate::item::Type),
     Fluid(crate::fluid::Type),
+    /// power units per second
+    Power,
+    /// erekir heat (not per second)
+    Heat,
+    /// power units stored (not per second)
+    Battery,
+}
+
+/// [`Resource::Power`], for [`ratios!`]
+pub struct Power;
+/// [`Resource::Heat`], for [`ratios!`]
+pub struct Heat;
+/// [`Resource::Battery`], for [`ratios!`]
+pub struct Battery;
+
+impl const super::ConstFrom<Power> for Resource {
+    fn fro(_: Power) -> Self {
+        Self::Power
+    }
+}
+
+impl const super::ConstFrom<Heat> for Resource {
+    fn fro(_: Heat) -> Self {
+        Self::Heat
+    }
+}
+
+impl const super::ConstFrom<Battery> for Resource {
+    fn fro(_: Battery) -> Self {
+        Self::Battery
+    }
 }
 
 impl const super::ConstFrom<crate::item::Type> for Resource {
@@ -58,6 +91,22 @@ impl Io {
             output: Cow::from(output),
         }
     }
+
+    /// how much of `resource` comes out, less how much goes in.
+    /// ```
+    /// # use mindus::block::{ratios::Resource, SILICON_SMELTER};
+    /// assert_eq!(SILICON_SMELTER.io(None).net(Resource::Power), -30.0);
+    /// ```
+    #[must_use]
+    pub fn net(&self, resource: Resource) -> f32 {
+        let sum = |l: &[(Resource, f32)]| {
+            l.iter()
+                .filter(|&&(r, _)| r == resource)
+                .map(|&(_, n)| n)
+                .sum::<f32>()
+        };
+        sum(&self.output) - sum(&self.input)
+    }
 }
 
 #[derive(Default)]
@@ -92,6 +141,9 @@ impl From<IoBuilder> for Io {
     }
 }
 
+/// what a block takes and makes, per second.
+///
+/// not covered: turret ammo, the payload blocks, the shield projectors, the payload mass drivers, lustre and malign.
 pub trait Ratios {
     #[inline]
     #[must_use]
@@ -131,123 +183,401 @@ macro_rules! rats {
 rats!(ConveyorBlock { none });
 rats!(JunctionBlock { none });
 rats!(StackConveyor { none });
-rats!(BridgeBlock { none });
+rats!(BridgeBlock {
+    "phase-conveyor": [Power: 18] => []
+    "phase-conduit": [Power: 18] => []
+    "mass-driver": [Power: 105] => []
+});
 rats!(ItemBlock { none });
 rats!(DuctBlock { none });
 rats!(SimpleDuctBlock { none });
 rats!(FluidBlock { none });
 rats!(SurgeRouter { none });
 rats!(ProductionBlock {
-    "cultivator": [Water: 18] => [SporePod: 0.6]
+    "cultivator": [Water: 18, Power: 80] => [SporePod: 0.6]
     "graphite-press": [Coal: 1.33] => [Graphite: 0.66]
-    "multi-press": [Coal: 6, Water: 6] => [Graphite: 4]
-    "silicon-smelter": [Coal: 1.5, Sand: 3] => [Silicon: 1.5]
-    "silicon-crucible": [Coal: 2.66, Sand: 4, Pyratite: 0.66] => [Silicon: 5.33]
-    "kiln": [Lead: 2, Sand: 2] => [Metaglass: 2]
-    "plastanium-compressor": [Oil: 15, Titanium: 2] => [Plastanium: 1]
-    "phase-weaver": [Thorium: 2, Sand: 5] => [PhaseFabric: 0.5]
-    "surge-smelter": [Copper: 2.4, Lead: 3.2, Titanium: 1.6, Silicon: 2.4] => [SurgeAlloy: 0.8]
-    "cryofluid-mixer": [Titanium: 0.5, Water: 12] => [Cryofluid: 12]
-    "pyratite-mixer": [Coal: 0.75, Lead: 1.5, Sand: 1.5] => [Pyratite: 0.75]
-    "blast-mixer": [Pyratite: 0.75, SporePod: 0.75] => [BlastCompound: 0.75]
-    "melter": [Scrap: 6] => [Slag: 12]
-    "spore-press": [SporePod: 3] => [Oil: 18]
-    "pulverizer": [Scrap: 1.5] => [Sand: 1.5]
-    "coal-centrifuge": [Oil: 6] => [Coal: 2]
-    "silicon-arc-furnace": [Graphite: 1.2, Sand: 4.8] => [Silicon: 4.8]
-    "electrolyzer": [Water: 10] => [Ozone: 4, Hydrogen: 6]
-    "atmospheric-concentrator": [] => [Nitrogen: 4]
-    "carbide-crucible": [Tungsten: 0.88, Graphite: 1.33] => [Carbide: 0.44]
-    "cyanogen-synthesizer": [Arkycite: 40, Graphite: 0.75] => [Cyanogen: 3]
-    "phase-synthesizer": [Thorium: 1, Sand: 3, Ozone: 2] => [PhaseFabric: 0.5]
-    "vent-condenser": [] => [Water: 30]
+    "multi-press": [Coal: 6, Water: 6, Power: 108] => [Graphite: 4]
+    "silicon-smelter": [Coal: 1.5, Sand: 3, Power: 30] => [Silicon: 1.5]
+    "silicon-crucible": [Coal: 2.66, Sand: 4, Pyratite: 0.66, Power: 240] => [Silicon: 5.33]
+    "kiln": [Lead: 2, Sand: 2, Power: 36] => [Metaglass: 2]
+    "plastanium-compressor": [Oil: 15, Titanium: 2, Power: 180] => [Plastanium: 1]
+    "phase-weaver": [Thorium: 2, Sand: 5, Power: 300] => [PhaseFabric: 0.5]
+    "surge-smelter": [Copper: 2.4, Lead: 3.2, Titanium: 1.6, Silicon: 2.4, Power: 240] => [SurgeAlloy: 0.8]
+    "cryofluid-mixer": [Titanium: 0.5, Water: 12, Power: 60] => [Cryofluid: 12]
+    "pyratite-mixer": [Coal: 0.75, Lead: 1.5, Sand: 1.5, Power: 12] => [Pyratite: 0.75]
+    "blast-mixer": [Pyratite: 0.75, SporePod: 0.75, Power: 24] => [BlastCompound: 0.75]
+    "melter": [Scrap: 6, Power: 60] => [Slag: 12]
+    "spore-press": [SporePod: 3, Power: 42] => [Oil: 18]
+    "pulverizer": [Scrap: 1.5, Power: 30] => [Sand: 1.5]
+    "coal-centrifuge": [Oil: 6, Power: 42] => [Coal: 2]
+    "silicon-arc-furnace": [Graphite: 1.2, Sand: 4.8, Power: 360] => [Silicon: 4.8]
+    "electrolyzer": [Water: 10, Power: 60] => [Ozone: 4, Hydrogen: 6]
+    "atmospheric-concentrator": [Power: 120, Heat: 6] => [Nitrogen: 4]
+    "carbide-crucible": [Tungsten: 0.88, Graphite: 1.33, Power: 120, Heat: 10] => [Carbide: 0.44]
+    "cyanogen-synthesizer": [Arkycite: 40, Graphite: 0.75, Power: 120, Heat: 5] => [Cyanogen: 3]
+    "phase-synthesizer": [Thorium: 1, Sand: 3, Ozone: 2, Power: 480, Heat: 8] => [PhaseFabric: 0.5]
+    "vent-condenser": [Power: 30] => [Water: 30]
 });
 rats!(SeparatorBlock {
-    "separator": [Slag: 4] => [Copper: 0.71, Lead: 0.43, Graphite: 0.28, Titanium: 0.28]
-    "disassembler": [Slag: 7.2, Scrap: 4] => [Sand: 1.6, Graphite: 0.8, Titanium: 0.8, Thorium: 0.8]
+    "separator": [Slag: 4, Power: 66] => [Copper: 0.71, Lead: 0.43, Graphite: 0.28, Titanium: 0.28]
+    "disassembler": [Slag: 7.2, Scrap: 4, Power: 240] => [Sand: 1.6, Graphite: 0.8, Titanium: 0.8, Thorium: 0.8]
 });
 rats!(HeatConduit { none });
 rats!(HeatCrafter {
-    "oxidation-chamber": [Ozone: 2, Beryllium: 0.5] => [Oxide: 0.5]
-    "phase-heater": [PhaseFabric: 0.12] => []
+    "oxidation-chamber": [Ozone: 2, Beryllium: 0.5, Power: 30] => [Oxide: 0.5, Heat: 5]
+    "electric-heater": [Power: 100] => [Heat: 3]
+    "slag-heater": [Slag: 40] => [Heat: 8]
+    "phase-heater": [PhaseFabric: 0.12] => [Heat: 15]
+    "heat-source": [] => [Heat: 1000]
 });
 rats!(WallBlock { none });
 rats!(DoorBlock { none });
-rats!(HeatedBlock { none }); // these arent erekir, btw
+rats!(HeatedBlock { // these arent erekir, btw
+    "mender": [Power: 18] => []
+    "mend-projector": [Power: 24] => []
+    "overdrive-projector": [Power: 210] => []
+    "overdrive-dome": [Power: 600] => []
+});
 rats!(ConduitBlock { none });
 impl Ratios for super::UnitFactory {
     fn io(&self, state: Option<&State>, name: &str) -> Io {
         state.map_or(Io::none(), |s| {
             Self::get_state(s).map_or(Io::none(), |t| match (name, t) {
-                ("ground-factory", Dagger) => ratios![Silicon: 0.66, Lead: 0.66],
-                ("ground-factory", Crawler) => ratios![Silicon: 0.8, Coal: 1],
-                ("ground-factory", Nova) => ratios![Silicon: 0.75, Lead: 0.5, Titanium: 0.5],
-                ("air-factory", Flare) => ratios![Silicon: 1],
-                ("air-factory", Mono) => ratios![Silicon: 0.85, Lead: 0.42],
-                ("naval-factory", Risso) => ratios![Silicon: 0.44, Metaglass: 0.77],
-                ("naval-factory", Retusa) => ratios![Silicon: 0.3, Metaglass: 0.5, Titanium: 0.4],
-                ("tank-fabricator", _) => ratios![Beryllium: 1.14, Silicon: 1.42],
-                ("ship-fabricator", _) => ratios![Graphite: 1.25, Silicon: 1.75],
-                ("mech-fabricator", _) => ratios![Beryllium: 1.25, Silicon: 1.75],
+                ("ground-factory", Dagger) => ratios![Silicon: 0.66, Lead: 0.66, Power: 72],
+                ("ground-factory", Crawler) => ratios![Silicon: 0.8, Coal: 1, Power: 72],
+                ("ground-factory", Nova) => {
+                    ratios![Silicon: 0.75, Lead: 0.5, Titanium: 0.5, Power: 72]
+                }
+                ("air-factory", Flare) => ratios![Silicon: 1, Power: 72],
+                ("air-factory", Mono) => ratios![Silicon: 0.85, Lead: 0.42, Power: 72],
+                ("naval-factory", Risso) => ratios![Silicon: 0.44, Metaglass: 0.77, Power: 72],
+                ("naval-factory", Retusa) => {
+                    ratios![Silicon: 0.3, Metaglass: 0.5, Titanium: 0.4, Power: 72]
+                }
+                ("tank-fabricator", _) => ratios![Beryllium: 1.14, Silicon: 1.42, Power: 120],
+                ("ship-fabricator", _) => ratios![Graphite: 1.25, Silicon: 1.75, Power: 120],
+                ("mech-fabricator", _) => ratios![Beryllium: 1.25, Silicon: 1.75, Power: 120],
                 (f, t) => unreachable!("{f}, {t:?}"),
             })
         })
     }
 }
 rats!(ConstructorBlock {
-    "additive-reconstructor": [Silicon: 4, Graphite: 4] => []
-    "multiplicative-reconstructor": [Silicon: 4.33, Titanium: 2.66, Metaglass: 1.33] => []
-    "exponential-reconstructor": [Silicon: 9.44, Titanium: 8.33, Plastanium: 7.22, Cryofluid: 60] => []
-    "tetrative-reconstructor": [Silicon: 4.16, Plastanium: 2.5, SurgeAlloy: 2.08, PhaseFabric: 1.45, Cryofluid: 180] => []
-    "tank-refabricator": [Hydrogen: 3, Silicon: 1.33, Titanium: 1] => []
-    "mech-refabricator": [Hydrogen: 3, Silicon: 1.11, Tungsten: 0.88] => []
-    "ship-refabricator": [Hydrogen: 3, Silicon: 1.2, Tungsten: 0.8] => []
-    "prime-refabricator": [Nitrogen: 10, Thorium: 1.33, Silicon: 1.66] => []
+    "additive-reconstructor": [Silicon: 4, Graphite: 4, Power: 180] => []
+    "multiplicative-reconstructor": [Silicon: 4.33, Titanium: 2.66, Metaglass: 1.33, Power: 360] => []
+    "exponential-reconstructor": [Silicon: 9.44, Titanium: 8.33, Plastanium: 7.22, Cryofluid: 60, Power: 780] => []
+    "tetrative-reconstructor": [Silicon: 4.16, Plastanium: 2.5, SurgeAlloy: 2.08, PhaseFabric: 1.45, Cryofluid: 180, Power: 1500] => []
+    "tank-refabricator": [Hydrogen: 3, Silicon: 1.33, Titanium: 1, Power: 180] => []
+    "mech-refabricator": [Hydrogen: 3, Silicon: 1.11, Tungsten: 0.88, Power: 150] => []
+    "ship-refabricator": [Hydrogen: 3, Silicon: 1.2, Tungsten: 0.8, Power: 150] => []
+    "prime-refabricator": [Nitrogen: 10, Thorium: 1.33, Silicon: 1.66, Power: 300] => []
 });
 rats!(BasicBlock {
-    "water-extractor": [] => [Water: 6.6]
-    "unit-repair-tower": [Ozone: 3] => []
-    "oil-extractor": [Water: 9, Sand: 1] => [] // *cough*
+    "water-extractor": [Power: 90] => [Water: 6.6]
+    "unit-repair-tower": [Ozone: 3, Power: 60] => []
+    "oil-extractor": [Water: 9, Sand: 1, Power: 180] => [] // *cough*
+    "rotary-pump": [Power: 18] => []
+    "impulse-pump": [Power: 78] => []
+    "force-projector": [Power: 240] => []
+    "battery": [] => [Battery: 1000]
+    "battery-large": [] => [Battery: 50000]
 });
-rats!(WallDrillBlock { none }); // more cough
+rats!(WallDrillBlock { "cliff-crusher": [Power: 11] => [] }); // more cough
 rats!(DrillBlock {
-    "laser-drill": [Water: 4.8] => []
-    "blast-drill": [Water: 6] => []
-    "large-plasma-bore": [Nitrogen: 3] => []
-    "impact-drill": [Water: 12] => []
-    "eruption-drill": [Hydrogen: 4] => []
+    "laser-drill": [Water: 4.8, Power: 66] => []
+    "blast-drill": [Water: 6, Power: 180] => []
+    "plasma-bore": [Power: 9] => []
+    "large-plasma-bore": [Nitrogen: 3, Power: 48] => []
+    "impact-drill": [Water: 12, Power: 160] => []
+    "eruption-drill": [Hydrogen: 4, Power: 360] => []
 });
 rats!(MessageLogic { none });
 rats!(SwitchLogic { none });
 rats!(ProcessorLogic { "hyper-processor": [Cryofluid: 4.8] => [] });
 rats!(MemoryBlock { none });
 rats!(CanvasBlock { none });
-rats!(LampBlock { none });
+rats!(LampBlock { "illuminator": [Power: 3] => [] });
 rats!(ConnectorBlock { none });
 rats!(DiodeBlock { none });
-just!(NuclearGeneratorBlock, [Thorium: 0.16, Cryofluid: 2.4] => []); // thoreactor
-just!(ImpactReactorBlock, [BlastCompound: 0.42, Cryofluid: 15] => []);
-just!(Neoplasia, [Arkycite: 80, Water: 10, PhaseFabric: 0.33] => [Neoplasm: 20]);
+just!(NuclearGeneratorBlock, [Thorium: 0.16, Cryofluid: 2.4] => [Power: 900]); // thoreactor
+just!(ImpactReactorBlock, [BlastCompound: 0.42, Cryofluid: 15, Power: 1500] => [Power: 7800]);
+just!(Neoplasia, [Arkycite: 80, Water: 10, PhaseFabric: 0.33] => [Neoplasm: 20, Power: 8400, Heat: 60]);
 rats!(GeneratorBlock {
-    "differential-generator": [Pyratite: 0.27, Cryofluid: 6] => []
-    "turbine-condenser": [] => [Water: 5]
-    "chemical-combustion-chamber": [Ozone: 2, Arkycite: 40] => []
-    "pyrolysis-generator": [Slag: 20, Arkycite: 40] => [Water: 20]
-    "flux-reactor": [Cyanogen: 9] => []
-    "rtg-generator": [Thorium: 0.07] => []
+    "combustion-generator": [] => [Power: 60] // and anything flammable
+    "steam-generator": [Water: 6] => [Power: 330]
+    "differential-generator": [Pyratite: 0.27, Cryofluid: 6] => [Power: 1080]
+    "solar-panel": [] => [Power: 6]
+    "solar-panel-large": [] => [Power: 78]
+    "turbine-condenser": [] => [Water: 5, Power: 180]
+    "chemical-combustion-chamber": [Ozone: 2, Arkycite: 40] => [Power: 600]
+    "pyrolysis-generator": [Slag: 20, Arkycite: 40] => [Water: 20, Power: 1500]
+    "flux-reactor": [Cyanogen: 9, Heat: 140] => [Power: 18000]
+    "rtg-generator": [Thorium: 0.07] => [Power: 270]
 });
-rats!(ItemTurret { none }); // eh
-rats!(TractorBeamTurret { none });
-rats!(PointDefenseTurret { none });
-rats!(ContinousTurret { none });
-rats!(Turret { none });
-rats!(ShieldBlock { none });
+// ammo is left out, as it depends on what is shot
+rats!(ItemTurret { "foreshadow": [Power: 600] => [] });
+rats!(TractorBeamTurret { "parallax": [Power: 180] => [] });
+rats!(PointDefenseTurret { "segment": [Power: 480] => [] });
+rats!(ContinousTurret { none }); // sublimate burns fluids, lustre is not covered
+rats!(Turret {
+    "lancer": [Power: 360] => []
+    "arc": [Power: 198] => []
+    "meltdown": [Power: 1020] => []
+    "afflict": [Power: 300, Heat: 10] => []
+}); // malign is not covered
+rats!(ShieldBlock { none }); // not covered
 rats!(PayloadBlock { none });
 rats!(PayloadConveyor { none });
 rats!(SimplePayloadBlock { none });
 rats!(PayloadRouter { none });
-rats!(RadarBlock { none });
-rats!(UnitCargoLoader { "unit-cargo-loader": [Nitrogen: 10] => [] });
-rats!(AssemblerBlock { none });
-rats!(RepairTurret { none });
+rats!(RadarBlock { "radar": [Power: 36] => [] });
+rats!(UnitCargoLoader { "unit-cargo-loader": [Nitrogen: 10, Power: 8] => [] });
+rats!(AssemblerBlock {
+    "tank-assembler": [Power: 180] => []
+    "ship-assembler": [Power: 180] => []
+    "mech-assembler": [Power: 180] => []
+});
+rats!(RepairTurret {
+    "repair-point": [Power: 18] => []
+    "repair-turret": [Power: 300] => []
+});
 rats!(AssemblerModule { none });
+
+/// the floor and ore of a tile, for [`Block::io_on`](super::Block::io_on)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ground {
+    pub floor: Floor,
+    pub ore: Floor,
+}
+
+impl Ground {
+    /// `ore` on stone
+    #[must_use]
+    pub const fn ore(ore: Floor) -> Self {
+        Self {
+            floor: Floor::Stone,
+            ore,
+        }
+    }
+
+    /// just `floor`
+    #[must_use]
+    pub const fn floor(floor: Floor) -> Self {
+        Self {
+            floor,
+            ore: Floor::Air,
+        }
+    }
+
+    /// the item drills get here
+    fn item(self) -> Option<crate::item::Type> {
+        if !self.ore.get_name().starts_with("ore-wall-") {
+            if let Some(item) = ore_item(self.ore) {
+                return Some(item);
+            }
+        }
+        matches!(self.floor, Floor::SandFloor | Floor::Darksand).then_some(crate::item::Type::Sand)
+    }
+
+    /// the liquid pumps get here, and how much more than usual
+    const fn liquid(self) -> Option<(crate::fluid::Type, f32)> {
+        use crate::fluid::Type::*;
+        Some(match self.floor {
+            Floor::DeepWater => (Water, 1.5),
+            Floor::ShallowWater
+            | Floor::TaintedWater
+            | Floor::DeepTaintedWater
+            | Floor::DarksandTaintedWater
+            | Floor::SandWater
+            | Floor::DarksandWater => (Water, 1.0),
+            Floor::Tar => (Oil, 1.0),
+            Floor::PooledCryofluid => (Cryofluid, 0.5),
+            Floor::MoltenSlag => (Slag, 1.0),
+            Floor::ArkyciteFloor => (Arkycite, 1.0),
+            _ => return None,
+        })
+    }
+
+    /// how much this helps an extractor of `fluid`
+    const fn attribute(self, fluid: crate::fluid::Type) -> f32 {
+        use crate::fluid::Type::*;
+        match (fluid, self.floor) {
+            (Water, Floor::Ice) => 0.4,
+            (Water, Floor::IceSnow) => 0.3,
+            (Water, Floor::Snow) => 0.2,
+            (Water, Floor::Salt) => -0.3,
+            (Oil, Floor::SandFloor) => 0.7,
+            (Oil, Floor::Darksand) => 1.5,
+            (Oil, Floor::Shale) => 1.6,
+            (Oil, Floor::Tar) => 1.0,
+            (Oil, Floor::Salt) => 0.3,
+            _ => 0.0,
+        }
+    }
+}
+
+/// the item an ore gives (wall ores too)
+pub(crate) fn ore_item(ore: Floor) -> Option<crate::item::Type> {
+    let name = ore.get_name();
+    let name = name
+        .strip_prefix("ore-wall-")
+        .or_else(|| name.strip_prefix("ore-"))?;
+    crate::item::Type::by_name(match name {
+        "crystal-thorium" => "thorium",
+        n => n,
+    })
+}
+
+/// ticks to drill an item is this plus this per hardness
+const HARDNESS_TIME: f32 = 50.0;
+/// how much faster a boosted drill drills (it warms up faster, too)
+const BOOST: f32 = 1.6 * 1.6;
+
+/// the tier of a drill, its ticks per item, and the water (per second) that boosts it
+fn drill(name: &str) -> Option<(u8, f32, Option<f32>)> {
+    Some(match name {
+        "mechanical-drill" => (2, 600.0, Some(3.0)),
+        "pneumatic-drill" => (3, 400.0, Some(3.6)),
+        "laser-drill" => (4, 280.0, Some(4.8)),
+        "blast-drill" => (5, 280.0, Some(6.0)),
+        "impact-drill" => (6, 600.0, None),
+        "eruption-drill" => (7, 281.0, None),
+        _ => return None,
+    })
+}
+
+/// liquid per second per tile
+fn pump(name: &str) -> Option<f32> {
+    Some(match name {
+        "mechanical-pump" => 7.0,
+        "rotary-pump" => 12.0,
+        "impulse-pump" => 13.2,
+        "reinforced-pump" => 15.0,
+        _ => return None,
+    })
+}
+
+/// what an extractor makes, how much (per second, at 100%), and how much it makes without help
+fn extractor(name: &str) -> Option<(crate::fluid::Type, f32, f32)> {
+    use crate::fluid::Type::*;
+    Some(match name {
+        "water-extractor" => (Water, 6.6, 1.0),
+        "oil-extractor" => (Oil, 15.0, 0.0),
+        _ => return None,
+    })
+}
+
+/// `io` (of the block `name`), with what drills, pumps and extractors get out of `under`
+pub(crate) fn on(name: &str, io: Io, under: &[Ground], boost: bool) -> Io {
+    let mut input = io.input.into_owned();
+    let mut output = io.output.into_owned();
+    let tiles = under.len() as f32;
+    if let Some((tier, time, boost_water)) = drill(name) {
+        let water = Resource::Fluid(crate::fluid::Type::Water);
+        let boost = boost && boost_water.is_some();
+        if let Some(w) = boost_water {
+            input.retain(|&(r, _)| r != water);
+            if boost {
+                input.push((water, w));
+            }
+        }
+        let mut counts = HashMap::<crate::item::Type, usize>::new();
+        for item in under.iter().filter_map(|g| g.item()) {
+            if item.hardness() <= tier {
+                *counts.entry(item).or_default() += 1;
+            }
+        }
+        // like the game: sand last, then the most, then the newest
+        if let Some((item, n)) = counts
+            .into_iter()
+            .max_by_key(|&(item, n)| (item != crate::item::Type::Sand, n, item))
+        {
+            let speed = if boost { BOOST } else { 1.0 };
+            let time = time + HARDNESS_TIME * f32::from(item.hardness());
+            output.push((Resource::Item(item), 60.0 / time * n as f32 * speed));
+        }
+    } else if let Some(each) = pump(name) {
+        // (the last liquid wins, but every liquid tile counts)
+        let mut pumped = None;
+        let mut amount = 0.0;
+        for (liquid, n) in under.iter().filter_map(|g| g.liquid()) {
+            pumped = Some(liquid);
+            amount += n;
+        }
+        if let Some(liquid) = pumped {
+            output.push((Resource::Fluid(liquid), each * amount));
+        }
+    } else if let Some((fluid, rate, base)) = extractor(name) {
+        let valid = under.iter().filter(|g| g.liquid().is_none()).count() as f32;
+        let boost = under.iter().map(|g| g.attribute(fluid)).sum::<f32>();
+        let fraction = ((valid * base + boost) / tiles.max(1.0)).max(0.0);
+        let made = Resource::Fluid(fluid);
+        output.retain(|&(r, _)| r != made);
+        if fraction > 0.0 {
+            output.push((made, rate * fraction));
+        }
+    }
+    Io {
+        input: Cow::Owned(input),
+        output: Cow::Owned(output),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::{
+        LANCER, MASS_DRIVER, MECHANICAL_DRILL, OIL_EXTRACTOR, REPAIR_TURRET, ROTARY_PUMP,
+        WATER_EXTRACTOR,
+    };
+    use crate::fluid::Type as F;
+    use crate::item::Type as I;
+
+    #[test]
+    fn drills() {
+        let sand = Ground::floor(Floor::Darksand);
+        let copper = Ground::ore(Floor::OreCopper);
+        let titanium = Ground::ore(Floor::OreTitanium);
+        // sand only wins if there is nothing else
+        let io = MECHANICAL_DRILL.io_on(None, &[sand, sand, sand, copper], false);
+        assert_eq!(&*io.output, [(Resource::Item(I::Copper), 60.0 / 650.0)]);
+        // too hard
+        let io = MECHANICAL_DRILL.io_on(None, &[titanium, titanium, sand, sand], true);
+        assert_eq!(
+            &*io.output,
+            [(Resource::Item(I::Sand), 60.0 / 600.0 * 2.0 * BOOST)]
+        );
+        assert_eq!(&*io.input, [(Resource::Fluid(F::Water), 3.0)]);
+        // wall ores are not for drills
+        let wall = Ground::ore(Floor::OreWallBeryllium);
+        assert!(MECHANICAL_DRILL
+            .io_on(None, &[wall; 4], false)
+            .output
+            .is_empty());
+    }
+
+    #[test]
+    fn liquids() {
+        let deep = Ground::floor(Floor::DeepWater);
+        let tar = Ground::floor(Floor::Tar);
+        let io = ROTARY_PUMP.io_on(None, &[deep, deep, tar, Ground::floor(Floor::Stone)], false);
+        assert_eq!(&*io.output, [(Resource::Fluid(F::Oil), 12.0 * 4.0)]);
+
+        let ice = Ground::floor(Floor::Ice);
+        let io = WATER_EXTRACTOR.io_on(None, &[ice; 4], false);
+        assert!((io.net(Resource::Fluid(F::Water)) - 6.6 * 1.4).abs() < 0.001);
+        let io = OIL_EXTRACTOR.io_on(None, &[Ground::floor(Floor::Stone); 9], false);
+        assert_eq!(io.net(Resource::Fluid(F::Oil)), 0.0);
+        let io = OIL_EXTRACTOR.io_on(None, &[Ground::floor(Floor::Shale); 9], false);
+        assert!((io.net(Resource::Fluid(F::Oil)) - 15.0 * 1.6).abs() < 0.001);
+        let io = OIL_EXTRACTOR.io_on(None, &[Ground::floor(Floor::Salt); 9], false);
+        assert!((io.net(Resource::Fluid(F::Oil)) - 15.0 * 0.3).abs() < 0.001);
+    }
+
+    #[test]
+    fn power() {
+        assert_eq!(LANCER.io(None).net(Resource::Power), -360.0);
+        assert_eq!(MASS_DRIVER.io(None).net(Resource::Power), -105.0);
+        assert_eq!(REPAIR_TURRET.io(None).net(Resource::Power), -300.0);
+    }
+}
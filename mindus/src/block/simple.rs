@@ -23,9 +23,10 @@ macro_rules! state_impl {
 pub(crate) use state_impl;
 
 /// draw is called with self, name, state, context, rotation
-/// read is called with build, reg, `entity_mapping`, buff
+/// read is called with build, buff (and the [`ContentMap`](crate::data::map::ContentMap), if `mapped`)
+/// write is called with build, buff (and the [`ContentMap`](crate::data::map::ContentMap), if `mapped`)
 macro_rules! make_simple {
-    ($name: ident, $draw: expr, $read: expr) => {
+    ($name: ident, $draw: expr, mapped $read: expr, $write: expr) => {
         pub struct $name {
             size: u8,
             symmetric: bool,
@@ -95,25 +96,58 @@ macro_rules! make_simple {
                 &self,
                 build: &mut crate::data::map::Build,
                 buff: &mut crate::data::DataRead,
+                content: &crate::data::map::ContentMap,
             ) -> Result<(), crate::data::ReadError> {
-                $read(build, buff)
+                $read(build, buff, content)
+            }
+
+            fn write(
+                &self,
+                build: &crate::data::map::Build,
+                buff: &mut crate::data::DataWrite,
+                content: &crate::data::map::ContentMap,
+            ) -> Result<(), crate::data::map::WriteError> {
+                $write(build, buff, content).map_err(Into::into)
             }
         }
     };
-    ($name: ident, $draw: expr) => {
-        crate::block::simple::make_simple!($name, $draw, |_, _| Ok(()));
+    ($name: ident, $draw: expr, $read: expr, $write: expr) => {
+        crate::block::simple::make_simple!(
+            $name,
+            $draw,
+            mapped |b, buff, _| $read(b, buff),
+            |b, buff, _| $write(b, buff)
+        );
     };
-    ($name: ident, $draw: expr, $read: expr) => {
-        crate::block::simple::make_simple!($name, $draw, $read);
+    ($name: ident => mapped $read: expr, $write: expr) => {
+        crate::block::simple::make_simple!(
+            $name,
+            |_, n, _, _, _, _| unimplemented!("{n}"),
+            mapped $read,
+            $write
+        );
     };
-    ($name: ident => $read: expr) => {
-        crate::block::simple::make_simple!($name, |_, n, _, _, _, _| unimplemented!("{n}"), $read);
+    ($name: ident, $draw: expr) => {
+        crate::block::simple::make_simple!($name, $draw, |_, _| Ok(()), |_, _| Ok::<
+            _,
+            crate::data::WriteError,
+        >(()));
     };
-    ($name: ident => $draw: expr, $read: expr) => {
-        crate::block::simple::make_simple!($name, |_, _, _, _, _, scl| $draw(scl), $read);
+    ($name: ident => $read: expr, $write: expr) => {
+        crate::block::simple::make_simple!(
+            $name,
+            |_, n, _, _, _, _| unimplemented!("{n}"),
+            $read,
+            $write
+        );
     };
     ($name: ident / $draw: expr) => {
-        crate::block::simple::make_simple!($name, |_, _, _, _, _, scl| $draw(scl), |_, _| Ok(()));
+        crate::block::simple::make_simple!(
+            $name,
+            |_, _, _, _, _, scl| $draw(scl),
+            |_, _| Ok(()),
+            |_, _| Ok::<_, crate::data::WriteError>(())
+        );
     };
     ($name: ident) => {
         crate::block::simple::make_simple!($name, |_, n, _, _, _, _| unimplemented!("{n}"));
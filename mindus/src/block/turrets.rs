@@ -1,22 +1,60 @@
 //! idk why its not in the [`crate::block::defense`] module
 use super::simple::make_simple;
-use crate::data::{DataRead, ReadError};
+use crate::data::map::{Build, ContentMap, Extra};
+use crate::data::{DataRead, DataWrite, ReadError, WriteError};
 
-make_simple!(Turret => |_, buff: &mut DataRead| read_turret(buff));
-make_simple!(PointDefenseTurret => |_, buff: &mut DataRead| read_point_defense_turret(buff));
-make_simple!(ContinousTurret => |_, buff: &mut DataRead| read_continous_turret(buff));
-make_simple!(TractorBeamTurret => |_, buff: &mut DataRead| read_tractor_beam_turret(buff));
-make_simple!(ItemTurret => |_, buff: &mut DataRead| read_item_turret(buff));
+make_simple!(Turret => |b, buff: &mut DataRead| read_turret(b, buff), write_turret);
+make_simple!(PointDefenseTurret => |b, buff: &mut DataRead| read_aim(b, buff), write_aim);
+make_simple!(ContinousTurret => |b, buff: &mut DataRead| read_continous_turret(b, buff), write_continous_turret);
+make_simple!(TractorBeamTurret => |b, buff: &mut DataRead| read_aim(b, buff), write_aim);
+make_simple!(ItemTurret => mapped read_item_turret, write_item_turret);
 
 /// format:
 /// - call [`read_turret`]
 /// - iterate [`u8`]
 ///     - item: [`u16`] as [`Item`](crate::item::Type)
 ///     - amount: [`u16`]
-fn read_item_turret(buff: &mut DataRead) -> Result<(), ReadError> {
-    read_turret(buff)?;
-    for _ in 0..buff.read_u8()? {
-        buff.skip(4)?;
+fn read_item_turret(
+    b: &mut Build,
+    buff: &mut DataRead,
+    content: &ContentMap,
+) -> Result<(), ReadError> {
+    let (reload, rotation) = (buff.read_f32()?, buff.read_f32()?);
+    let n = buff.read_u8()? as usize;
+    let mut ammo = Vec::with_capacity(n);
+    for _ in 0..n {
+        let item = content
+            .item(buff.read_u16()?)
+            .ok_or(ReadError::Expected("ammo item"))?;
+        ammo.push((item, buff.read_u16()?));
+    }
+    b.extra = Extra::ItemTurret {
+        reload,
+        rotation,
+        ammo,
+    };
+    Ok(())
+}
+
+fn write_item_turret(
+    b: &Build,
+    buff: &mut DataWrite,
+    content: &ContentMap,
+) -> Result<(), WriteError> {
+    let (reload, rotation, ammo) = match &b.extra {
+        Extra::ItemTurret {
+            reload,
+            rotation,
+            ammo,
+        } => (*reload, *rotation, &ammo[..]),
+        _ => (0.0, 90.0, &[][..]),
+    };
+    buff.write_f32(reload)?;
+    buff.write_f32(rotation)?;
+    buff.write_u8(ammo.len() as u8)?;
+    for &(item, amount) in ammo {
+        buff.write_u16(content.item_id(item))?;
+        buff.write_u16(amount)?;
     }
     Ok(())
 }
@@ -24,26 +62,61 @@ fn read_item_turret(buff: &mut DataRead) -> Result<(), ReadError> {
 /// format:
 /// - reload: f32
 /// - rotation: f32
-fn read_turret(buff: &mut DataRead) -> Result<(), ReadError> {
-    buff.skip(8)
+fn read_turret(b: &mut Build, buff: &mut DataRead) -> Result<(), ReadError> {
+    b.extra = Extra::Turret {
+        reload: buff.read_f32()?,
+        rotation: buff.read_f32()?,
+    };
+    Ok(())
+}
+
+fn write_turret(b: &Build, buff: &mut DataWrite) -> Result<(), WriteError> {
+    let (reload, rotation) = match b.extra {
+        Extra::Turret { reload, rotation } => (reload, rotation),
+        _ => (0.0, 90.0),
+    };
+    buff.write_f32(reload)?;
+    buff.write_f32(rotation)
 }
 
 /// format:
 /// - rotation: [`f32`]
-fn read_point_defense_turret(buff: &mut DataRead) -> Result<(), ReadError> {
-    buff.skip(4)
+fn read_aim(b: &mut Build, buff: &mut DataRead) -> Result<(), ReadError> {
+    b.extra = Extra::Aim {
+        rotation: buff.read_f32()?,
+    };
+    Ok(())
+}
+
+pub(crate) fn write_aim(b: &Build, buff: &mut DataWrite) -> Result<(), WriteError> {
+    buff.write_f32(match b.extra {
+        Extra::Aim { rotation } => rotation,
+        _ => 90.0,
+    })
 }
 
 /// format:
 /// - call [`read_turret`]
 /// - last length: [`f32`]
-fn read_continous_turret(buff: &mut DataRead) -> Result<(), ReadError> {
-    read_turret(buff)?;
-    buff.skip(4)
+fn read_continous_turret(b: &mut Build, buff: &mut DataRead) -> Result<(), ReadError> {
+    b.extra = Extra::ContinousTurret {
+        reload: buff.read_f32()?,
+        rotation: buff.read_f32()?,
+        last_length: buff.read_f32()?,
+    };
+    Ok(())
 }
 
-/// format:
-/// - rotation: [`f32`]
-fn read_tractor_beam_turret(buff: &mut DataRead) -> Result<(), ReadError> {
-    buff.skip(4)
+fn write_continous_turret(b: &Build, buff: &mut DataWrite) -> Result<(), WriteError> {
+    let (reload, rotation, last_length) = match b.extra {
+        Extra::ContinousTurret {
+            reload,
+            rotation,
+            last_length,
+        } => (reload, rotation, last_length),
+        _ => (0.0, 90.0, 0.0),
+    };
+    buff.write_f32(reload)?;
+    buff.write_f32(rotation)?;
+    buff.write_f32(last_length)
 }
@@ -1,11 +1,15 @@
 //! unit creation related blocks
 use thiserror::Error;
 
-use super::payload::{read_payload_block, read_payload_seq};
+use super::payload::{
+    read_payload_block, read_payload_seq, write_payload_block, write_payload_seq, PayloadBuild,
+};
+use super::turrets::write_aim;
 use crate::block::simple::*;
 use crate::block::*;
 use crate::data::command::UnitCommand;
 use crate::data::dynamic::DynType;
+use crate::data::map::Extra;
 use crate::unit;
 
 // fn is_pay(b: &str) -> bool {
@@ -53,23 +57,99 @@ make_simple!(
         unsafe { base.overlay(&top) };
         base
     },
-    |_, buff| read_assembler(buff)
+    mapped read_assembler,
+    write_assembler
 );
 
 /// format:
 /// - call [`read_payload_block`]
 /// - progress: [`f32`]
 /// - iterate [`u8`]
-///     - read: [`i32`]
+///     - unit id: [`i32`]
 /// - call [`read_payload_seq`]
-/// - point: ([`f32`], [`f32`]) (maybe [`NaN`](f32::NAN))
-fn read_assembler(buff: &mut DataRead) -> Result<(), DataReadError> {
-    read_payload_block(buff)?;
-    buff.skip(4)?;
+/// - point: [`read_vec_nullable`]
+fn read_assembler(
+    b: &mut Build,
+    buff: &mut DataRead,
+    content: &ContentMap,
+) -> Result<(), DataReadError> {
+    let payload = read_payload_block(buff, content)?;
+    let progress = buff.read_f32()?;
     let n = buff.read_u8()? as usize;
-    buff.skip(n * 4)?;
-    read_payload_seq(buff)?;
-    buff.skip(8)
+    let mut units = Vec::with_capacity(n);
+    for _ in 0..n {
+        units.push(buff.read_i32()?);
+    }
+    b.extra = Extra::Assembler {
+        payload,
+        progress,
+        units,
+        blocks: read_payload_seq(buff)?,
+        command_pos: read_vec_nullable(buff)?,
+    };
+    Ok(())
+}
+
+fn write_assembler(
+    b: &Build,
+    buff: &mut DataWrite,
+    content: &ContentMap,
+) -> Result<(), data::map::WriteError> {
+    let Extra::Assembler {
+        payload,
+        progress,
+        units,
+        blocks,
+        command_pos,
+    } = &b.extra
+    else {
+        write_payload_block(&PayloadBuild::default(), buff, content)?;
+        buff.write_f32(0.0)?;
+        buff.write_u8(0)?;
+        write_payload_seq(&[], buff)?;
+        return Ok(write_vec_nullable(None, buff)?);
+    };
+    write_payload_block(payload, buff, content)?;
+    buff.write_f32(*progress)?;
+    buff.write_u8(units.len() as u8)?;
+    for &unit in units {
+        buff.write_i32(unit)?;
+    }
+    write_payload_seq(blocks, buff)?;
+    Ok(write_vec_nullable(*command_pos, buff)?)
+}
+
+/// format:
+/// - point: ([`f32`], [`f32`]) (maybe [`NaN`](f32::NAN))
+fn read_vec_nullable(buff: &mut DataRead) -> Result<Option<(f32, f32)>, DataReadError> {
+    let (x, y) = (buff.read_f32()?, buff.read_f32()?);
+    Ok((!x.is_nan() && !y.is_nan()).then_some((x, y)))
+}
+
+fn write_vec_nullable(v: Option<(f32, f32)>, buff: &mut DataWrite) -> Result<(), DataWriteError> {
+    let (x, y) = v.unwrap_or((f32::NAN, f32::NAN));
+    buff.write_f32(x)?;
+    buff.write_f32(y)
+}
+
+/// format:
+/// - (revision >= 3) command: [`u8`] as [`UnitCommand`] (255 for none)
+fn read_command(b: &Build, buff: &mut DataRead) -> Result<Option<UnitCommand>, DataReadError> {
+    if b.revision < 3 {
+        return Ok(None);
+    }
+    Ok(UnitCommand::try_from(buff.read_u8()?).ok())
+}
+
+fn write_command(
+    b: &Build,
+    command: Option<UnitCommand>,
+    buff: &mut DataWrite,
+) -> Result<(), DataWriteError> {
+    if b.revision < 3 {
+        return Ok(());
+    }
+    buff.write_u8(command.map_or(255, u8::from))
 }
 
 make_simple!(
@@ -84,13 +164,23 @@ make_simple!(
         unsafe { base.overlay(&side) };
         base
     },
-    |_, buff| read_payload_block(buff)
+    mapped |b: &mut Build, buff: &mut DataRead, content| {
+        b.extra = Extra::Payload(read_payload_block(buff, content)?);
+        Ok(())
+    },
+    |b: &Build, buff: &mut DataWrite, content| match &b.extra {
+        Extra::Payload(payload) => write_payload_block(payload, buff, content),
+        _ => write_payload_block(&PayloadBuild::default(), buff, content),
+    }
 );
 
 make_simple!(
-    RepairTurret => |_, buff: &mut DataRead| {
-        buff.skip(4) // rotation: [`f32`]
-    }
+    RepairTurret => |b: &mut Build, buff: &mut DataRead| {
+        // rotation: [`f32`]
+        b.extra = Extra::Aim { rotation: buff.read_f32()? };
+        Ok(())
+    },
+    write_aim
 );
 
 pub const GROUND_UNITS: &[unit::Type] =
@@ -201,15 +291,34 @@ impl BlockLogic for ConstructorBlock {
     /// format:
     /// - call [`read_payload_block`]
     /// - progress: [`f32`]
-    /// - point: ([`f32`], [`f32`]) (maybe [`NaN`](f32::NAN))
-    /// - command: [`DynData::UnitCommand`]
-    fn read(&self, _: &mut Build, buff: &mut DataRead) -> Result<(), DataReadError> {
-        read_payload_block(buff)?;
-        buff.skip(12)?;
-        // self.deserialize_state(DynData::deserialize(buff).unwrap())
-        //     .unwrap();
+    /// - point: [`read_vec_nullable`]
+    /// - call [`read_command`]
+    fn read(&self, b: &mut Build, buff: &mut DataRead, content: &ContentMap) -> Result<(), DataReadError> {
+        b.extra = Extra::Reconstructor {
+            payload: read_payload_block(buff, content)?,
+            progress: buff.read_f32()?,
+            command_pos: read_vec_nullable(buff)?,
+            command: read_command(b, buff)?,
+        };
         Ok(())
     }
+
+    fn write(&self, b: &Build, buff: &mut DataWrite, content: &ContentMap) -> Result<(), data::map::WriteError> {
+        let default = PayloadBuild::default();
+        let (payload, progress, command_pos, command) = match &b.extra {
+            Extra::Reconstructor {
+                payload,
+                progress,
+                command_pos,
+                command,
+            } => (payload, *progress, *command_pos, *command),
+            _ => (&default, 0.0, None, None),
+        };
+        write_payload_block(payload, buff, content)?;
+        buff.write_f32(progress)?;
+        write_vec_nullable(command_pos, buff)?;
+        Ok(write_command(b, command, buff)?)
+    }
 }
 
 pub struct UnitFactory {
@@ -315,11 +424,37 @@ impl BlockLogic for UnitFactory {
     /// format:
     /// - call [`read_payload_block`]
     /// - progress: [`f32`]
-    /// - plan: [`u16`]
-    /// - point: ([`f32`], [`f32`]) (maybe [`NaN`](f32::NAN))
-    fn read(&self, _: &mut Build, buff: &mut DataRead) -> Result<(), DataReadError> {
-        read_payload_block(buff)?;
-        buff.skip(14)
+    /// - plan: [`i16`]
+    /// - point: [`read_vec_nullable`]
+    /// - call [`read_command`]
+    fn read(&self, b: &mut Build, buff: &mut DataRead, content: &ContentMap) -> Result<(), DataReadError> {
+        b.extra = Extra::UnitFactory {
+            payload: read_payload_block(buff, content)?,
+            progress: buff.read_f32()?,
+            plan: buff.read_i16()?,
+            command_pos: read_vec_nullable(buff)?,
+            command: read_command(b, buff)?,
+        };
+        Ok(())
+    }
+
+    fn write(&self, b: &Build, buff: &mut DataWrite, content: &ContentMap) -> Result<(), data::map::WriteError> {
+        let default = PayloadBuild::default();
+        let (payload, progress, plan, command_pos, command) = match &b.extra {
+            Extra::UnitFactory {
+                payload,
+                progress,
+                plan,
+                command_pos,
+                command,
+            } => (payload, *progress, *plan, *command_pos, *command),
+            _ => (&default, 0.0, -1, None, None),
+        };
+        write_payload_block(payload, buff, content)?;
+        buff.write_f32(progress)?;
+        buff.write_i16(plan)?;
+        write_vec_nullable(command_pos, buff)?;
+        Ok(write_command(b, command, buff)?)
     }
 }
 
@@ -72,8 +72,14 @@ impl BlockLogic for DoorBlock {
         Ok(DynData::Boolean(*state))
     }
 
-    fn read(&self, build: &mut Build, buff: &mut DataRead) -> Result<(), DataReadError> {
+    fn read(&self, build: &mut Build, buff: &mut DataRead, _: &ContentMap) -> Result<(), DataReadError> {
         build.state = Some(Self::create_state(buff.read_bool()?));
         Ok(())
     }
+
+    fn write(&self, build: &Build, buff: &mut DataWrite, _: &ContentMap) -> Result<(), data::map::WriteError> {
+        Ok(buff.write_bool(
+            build.state.as_ref().map_or(false, |s| *Self::get_state(s)),
+        )?)
+    }
 }
@@ -11,3 +11,5 @@ pub fn block(col: [u8; 3]) -> Option<&'static Block> {
 pub fn color(block: &Block) -> Option<(u8, u8, u8)> {
     BLOCK2COLOR.get(block.name()).copied()
 }
+/// environment blocks (walls, ores, boulders, ..) to the average color of their sprite.
+pub static ENV2COLOR: phf::Map<&str, (u8, u8, u8)> = include!(concat!(env!("OUT_DIR"), "/5.rs"));
@@ -50,6 +50,20 @@ macro_rules! content_enum {
 			$([<$val:camel>]),*,
 		});
 
+		impl $tname {
+			/// every variant, in id order
+			pub const ALL: &'static [Self] = &[$(Self::[<$val:camel>]),*];
+
+			/// look up content by its name
+			#[must_use]
+			pub fn by_name(name: &str) -> Option<Self> {
+				match name {
+					$($val => Some(Self::[<$val:camel>]),)*
+					_ => None,
+				}
+			}
+		}
+
 		impl $crate::content::Content for $tname {
 			fn get_type(&self) -> $crate::content::Type {
 				$crate::content::Type::$ctype
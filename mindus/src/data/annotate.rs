@@ -0,0 +1,404 @@
+//! extras drawn over renders, for documentation: grids, rulers, highlights, conveyor arrows and labels
+use std::cell::OnceCell;
+use std::f32::consts::{FRAC_PI_2, PI};
+
+use super::renderer::*;
+use super::GridPos;
+use crate::block::{Block, Rotation};
+use crate::data::map::Map;
+use crate::data::schematic::Schematic;
+
+/// where the tiles of something are in its render (before it is [fit](RenderOptions::fit))
+#[derive(Debug, Clone, Copy)]
+pub struct Layout {
+    pub scale: Scale,
+    /// the tile at the left edge of the render (-1 for a border)
+    pub left: i64,
+    /// the row (counting up) at the top edge of the render
+    pub top: i64,
+    /// how many tiles the render is
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Layout {
+    /// the top left of tile `(x, y)`, in pixels
+    #[must_use]
+    pub fn pixel(&self, x: i64, y: i64) -> (i64, i64) {
+        let px = i64::from(self.scale.px());
+        ((x - self.left) * px, (self.top - y) * px)
+    }
+}
+
+/// things that can be [annotated](Annotated)
+pub trait Annotatable: Renderable + Sized {
+    /// how many tiles this is
+    fn size(&self) -> (usize, usize);
+
+    /// where the tiles are in [`Renderable::render_with`]
+    fn layout(&self, opts: &RenderOptions) -> Layout;
+
+    /// the blocks, at their centers
+    fn blocks(&self) -> Vec<(GridPos, &'static Block, Rotation)>;
+
+    /// start annotating this.
+    /// ```
+    /// # use mindus::*;
+    /// # use mindus::block::*;
+    /// # use mindus::data::{annotate::Annotatable, GridPos};
+    /// let mut s = Schematic::new(3, 1);
+    /// s.put(0, 0, &CONVEYOR).put(1, 0, &ROUTER);
+    /// let output = s
+    ///     .annotate()
+    ///     .grid((0, 0, 0))
+    ///     .arrows((255, 255, 255))
+    ///     .highlight(|_, b| b.name() == "router", (255, 0, 0))
+    ///     .label(GridPos(2, 0), "out", (255, 255, 255))
+    ///     .render();
+    /// assert_eq!(output.width(), 5 * 32);
+    /// ```
+    fn annotate(&self) -> Annotated<'_, Self> {
+        Annotated {
+            inner: self,
+            layers: vec![],
+        }
+    }
+}
+
+impl Annotatable for Schematic {
+    fn size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    fn layout(&self, opts: &RenderOptions) -> Layout {
+        schematic_layout((self.width, self.height), opts)
+    }
+
+    fn blocks(&self) -> Vec<(GridPos, &'static Block, Rotation)> {
+        self.block_iter()
+            .map(|(pos, p)| (pos, p.block, p.rot))
+            .collect()
+    }
+}
+
+impl Annotatable for Map {
+    fn size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    fn layout(&self, opts: &RenderOptions) -> Layout {
+        let ((x, y, w, h), scale) = opts.map_view(self.width, self.height);
+        Layout {
+            scale,
+            left: x as i64,
+            top: (y + h) as i64 - 1,
+            width: w,
+            height: h,
+        }
+    }
+
+    fn blocks(&self) -> Vec<(GridPos, &'static Block, Rotation)> {
+        self.tiles
+            .iter()
+            .enumerate()
+            .filter_map(|(j, tile)| {
+                let b = tile.build()?;
+                Some((GridPos(j % self.width, j / self.width), b.block, b.rotation))
+            })
+            .collect()
+    }
+}
+
+type Predicate<'a> = Box<dyn Fn(GridPos, &'static Block) -> bool + 'a>;
+
+enum Layer<'a> {
+    Grid((u8, u8, u8)),
+    Rulers(usize, (u8, u8, u8)),
+    Highlight(Predicate<'a>, (u8, u8, u8)),
+    Arrows((u8, u8, u8)),
+    Label(GridPos, String, (u8, u8, u8)),
+}
+
+/// a render, with extras drawn over it in the order they were added. see [`Annotatable::annotate`].
+pub struct Annotated<'a, R> {
+    inner: &'a R,
+    layers: Vec<Layer<'a>>,
+}
+
+impl<'a, R: Annotatable> Annotated<'a, R> {
+    /// lines between the tiles
+    #[must_use]
+    pub fn grid(mut self, color: (u8, u8, u8)) -> Self {
+        self.layers.push(Layer::Grid(color));
+        self
+    }
+
+    /// the positions of every `every`th column and row, along the top and left
+    #[must_use]
+    pub fn rulers(mut self, every: usize, color: (u8, u8, u8)) -> Self {
+        self.layers.push(Layer::Rulers(every.max(1), color));
+        self
+    }
+
+    /// outlines the blocks (by their center) that `predicate` likes
+    #[must_use]
+    pub fn highlight(
+        mut self,
+        predicate: impl Fn(GridPos, &'static Block) -> bool + 'a,
+        color: (u8, u8, u8),
+    ) -> Self {
+        self.layers
+            .push(Layer::Highlight(Box::new(predicate), color));
+        self
+    }
+
+    /// arrows on conveyors and ducts, the way they go
+    #[must_use]
+    pub fn arrows(mut self, color: (u8, u8, u8)) -> Self {
+        self.layers.push(Layer::Arrows(color));
+        self
+    }
+
+    /// text, from the top left of a tile. letters, numbers, and some punctuation.
+    #[must_use]
+    pub fn label(mut self, at: GridPos, text: impl Into<String>, color: (u8, u8, u8)) -> Self {
+        self.layers.push(Layer::Label(at, text.into(), color));
+        self
+    }
+}
+
+impl<R: Annotatable> Renderable for Annotated<'_, R> {
+    fn render_with(&self, opts: &RenderOptions) -> Image<Vec<u8>, 3> {
+        let layout = self.inner.layout(opts);
+        let mut img = self.inner.render_with(&RenderOptions {
+            scale: Some(layout.scale),
+            max_size: None,
+            ..*opts
+        });
+        let blocks = OnceCell::new();
+        let blocks = || blocks.get_or_init(|| self.inner.blocks());
+        let px = i64::from(layout.scale.px());
+        // a pixel of text, and the width of lines
+        let (dot, line) = ((px / 16).max(1), (px / 8).max(1));
+        let img = &mut img.as_mut();
+        for layer in &self.layers {
+            match *layer {
+                Layer::Grid(color) => {
+                    let (w, h) = (i64::from(img.width()), i64::from(img.height()));
+                    for x in 0..=layout.width as i64 {
+                        fill(img, (x * px, 0), (1, h), color, 160);
+                    }
+                    for y in 0..=layout.height as i64 {
+                        fill(img, (0, y * px), (w, 1), color, 160);
+                    }
+                }
+                Layer::Rulers(every, color) => {
+                    let (width, height) = self.inner.size();
+                    let (left, top) = (layout.left, layout.top);
+                    for x in left.max(0)..(left + layout.width as i64).min(width as i64) {
+                        if x as usize % every == 0 {
+                            let (px, _) = layout.pixel(x, 0);
+                            text(img, &x.to_string(), (px, 0), dot, color);
+                        }
+                    }
+                    let bottom = top + 1 - layout.height as i64;
+                    for y in bottom.max(0)..(top + 1).min(height as i64) {
+                        if y as usize % every == 0 {
+                            let (_, py) = layout.pixel(0, y);
+                            text(img, &y.to_string(), (0, py), dot, color);
+                        }
+                    }
+                }
+                Layer::Highlight(ref predicate, color) => {
+                    for &(pos @ GridPos(x, y), block, _) in blocks() {
+                        if !predicate(pos, block) {
+                            continue;
+                        }
+                        let size = i64::from(block.get_size());
+                        let (x, y) = layout.pixel(x as i64 - (size - 1) / 2, y as i64 + size / 2);
+                        let side = size * px;
+                        fill(img, (x, y), (side, side), color, 48);
+                        fill(img, (x, y), (side, line), color, 255);
+                        fill(img, (x, y + side - line), (side, line), color, 255);
+                        fill(img, (x, y), (line, side), color, 255);
+                        fill(img, (x + side - line, y), (line, side), color, 255);
+                    }
+                }
+                Layer::Arrows((r, g, b)) => {
+                    for &(GridPos(x, y), block, rot) in blocks() {
+                        if !is_conveyor(block.name()) {
+                            continue;
+                        }
+                        let (x, y) = layout.pixel(x as i64, y as i64);
+                        let half = px as f32 / 2.0;
+                        // (y goes down)
+                        let angle = match rot {
+                            Rotation::Right => 0.0,
+                            Rotation::Down => FRAC_PI_2,
+                            Rotation::Left => PI,
+                            Rotation::Up => -FRAC_PI_2,
+                        };
+                        img.poly(
+                            (x as f32 + half, y as f32 + half),
+                            3,
+                            half * 0.6,
+                            angle,
+                            [r, g, b],
+                        );
+                    }
+                }
+                Layer::Label(GridPos(x, y), ref s, color) => {
+                    text(img, s, layout.pixel(x as i64, y as i64), dot, color);
+                }
+            }
+        }
+        opts.fit(Image::build(img.width(), img.height()).buf(img.buffer().to_vec()))
+    }
+}
+
+/// the blocks that get arrows
+fn is_conveyor(name: &str) -> bool {
+    matches!(
+        name,
+        "conveyor"
+            | "titanium-conveyor"
+            | "armored-conveyor"
+            | "plastanium-conveyor"
+            | "duct"
+            | "armored-duct"
+    )
+}
+
+/// blends a `(width, height)` rectangle at `(x, y)` with `color`, cutting off what does not fit
+fn fill(
+    img: &mut Image<&mut [u8], 3>,
+    (x, y): (i64, i64),
+    (width, height): (i64, i64),
+    (r, g, b): (u8, u8, u8),
+    alpha: u16,
+) {
+    let clip = |v: i64, max: u32| v.clamp(0, i64::from(max)) as u32;
+    for py in clip(y, img.height())..clip(y + height, img.height()) {
+        for px in clip(x, img.width())..clip(x + width, img.width()) {
+            blend(img, px, py, [r, g, b], alpha);
+        }
+    }
+}
+
+/// writes `s` with its top left at `(x, y)`, on a dark box, in pixels `dot` big
+fn text(img: &mut Image<&mut [u8], 3>, s: &str, (x, y): (i64, i64), dot: i64, color: (u8, u8, u8)) {
+    let len = s.chars().count() as i64;
+    fill(img, (x, y), ((len * 4 + 1) * dot, 7 * dot), (0, 0, 0), 160);
+    for (i, c) in s.chars().enumerate() {
+        let x = x + (1 + 4 * i as i64) * dot;
+        for (row, bits) in glyph(c).into_iter().enumerate() {
+            for col in 0..3 {
+                if bits & (0b100 >> col) != 0 {
+                    let at = (x + col * dot, y + (1 + row as i64) * dot);
+                    fill(img, at, (dot, dot), color, 255);
+                }
+            }
+        }
+    }
+}
+
+/// a 3x5 letter, a row of 3 bits at a time (from the top, left bit first)
+const fn glyph(c: char) -> [u8; 5] {
+    match c.to_ascii_uppercase() {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b001, 0b001, 0b001],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        'A' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'B' => [0b110, 0b101, 0b110, 0b101, 0b110],
+        'C' => [0b011, 0b100, 0b100, 0b100, 0b011],
+        'D' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'E' => [0b111, 0b100, 0b110, 0b100, 0b111],
+        'F' => [0b111, 0b100, 0b110, 0b100, 0b100],
+        'G' => [0b011, 0b100, 0b101, 0b101, 0b011],
+        'H' => [0b101, 0b101, 0b111, 0b101, 0b101],
+        'I' => [0b111, 0b010, 0b010, 0b010, 0b111],
+        'J' => [0b001, 0b001, 0b001, 0b101, 0b010],
+        'K' => [0b101, 0b101, 0b110, 0b101, 0b101],
+        'L' => [0b100, 0b100, 0b100, 0b100, 0b111],
+        'M' => [0b101, 0b111, 0b111, 0b101, 0b101],
+        'N' => [0b110, 0b101, 0b101, 0b101, 0b101],
+        'O' => [0b010, 0b101, 0b101, 0b101, 0b010],
+        'P' => [0b110, 0b101, 0b110, 0b100, 0b100],
+        'Q' => [0b010, 0b101, 0b101, 0b110, 0b011],
+        'R' => [0b110, 0b101, 0b110, 0b101, 0b101],
+        'S' => [0b011, 0b100, 0b010, 0b001, 0b110],
+        'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        'U' => [0b101, 0b101, 0b101, 0b101, 0b111],
+        'V' => [0b101, 0b101, 0b101, 0b101, 0b010],
+        'W' => [0b101, 0b101, 0b111, 0b111, 0b101],
+        'X' => [0b101, 0b101, 0b010, 0b101, 0b101],
+        'Y' => [0b101, 0b101, 0b010, 0b010, 0b010],
+        'Z' => [0b111, 0b001, 0b010, 0b100, 0b111],
+        ' ' => [0; 5],
+        '-' => [0, 0, 0b111, 0, 0],
+        '+' => [0, 0b010, 0b111, 0b010, 0],
+        '_' => [0, 0, 0, 0, 0b111],
+        '.' => [0, 0, 0, 0, 0b010],
+        ',' => [0, 0, 0, 0b010, 0b100],
+        ':' => [0, 0b010, 0, 0b010, 0],
+        '!' => [0b010, 0b010, 0b010, 0, 0b010],
+        '(' => [0b001, 0b010, 0b010, 0b010, 0b001],
+        ')' => [0b100, 0b010, 0b010, 0b010, 0b100],
+        '/' => [0b001, 0b001, 0b010, 0b100, 0b100],
+        _ => [0b110, 0b001, 0b010, 0, 0b010],
+    }
+}
+
+#[test]
+fn overlays() {
+    use crate::block::{content::Type, CONVEYOR, ROUTER};
+    use crate::data::dynamic::DynData;
+    use crate::team::SHARDED;
+    let mut s = Schematic::new(3, 1);
+    s.set(0, 0, &CONVEYOR, DynData::Empty, Rotation::Right)
+        .unwrap();
+    s.put(1, 0, &ROUTER);
+    let plain = s.render();
+    let img = s
+        .annotate()
+        .arrows((255, 255, 255))
+        .highlight(|_, b| b.name() == "router", (255, 0, 0))
+        .label(GridPos(9, 9), "off the edge", (0, 255, 0))
+        .render();
+    let px = |img: &Image<Vec<u8>, 3>, x: u32, y: u32| {
+        let at = (y * img.width() + x) as usize * 3;
+        <[u8; 3]>::try_from(&img.buffer()[at..at + 3]).unwrap()
+    };
+    // the conveyor is at (32, 32) (a border goes around schematics) and points right
+    assert_ne!(px(&plain, 54, 48), [255; 3]);
+    assert_eq!(px(&img, 54, 48), [255; 3]);
+    assert_eq!(px(&img, 41, 48), px(&plain, 41, 48));
+    // the router is outlined
+    assert_eq!(px(&img, 64, 32), [255, 0, 0]);
+    assert_eq!(px(&img, 95, 63), [255, 0, 0]);
+    assert_eq!(px(&img, 16, 16), px(&plain, 16, 16));
+
+    let mut m = Map::filled(8, 8, Type::Stone);
+    m.set_block(2, 5, &ROUTER, Rotation::Up, SHARDED).unwrap();
+    let opts = RenderOptions {
+        viewport: Some((1, 4, 3, 3)),
+        scale: Some(Scale::Full),
+        ..RenderOptions::default()
+    };
+    let l = m.layout(&opts);
+    assert_eq!(l.pixel(2, 5), (32, 32));
+    let img = m
+        .annotate()
+        .highlight(|_, _| true, (0, 0, 255))
+        .rulers(1, (255, 255, 255))
+        .render_with(&opts);
+    assert_eq!((img.width(), img.height()), (96, 96));
+    assert_eq!(px(&img, 63, 63), [0, 0, 255]);
+}
@@ -0,0 +1,218 @@
+//! comparing schematics
+use std::collections::HashMap;
+
+use super::renderer::*;
+use super::schematic::{Placement, Schematic};
+use super::GridPos;
+
+/// what changed between two schematics. see [`Schematic::diff`].
+///
+/// can be rendered: the new schematic, with additions tinted green, reconfigurations yellow,
+/// and removals drawn over it as red ghosts.
+#[derive(Debug)]
+pub struct Diff<'s> {
+    /// placements only in the new schematic
+    pub added: Vec<(GridPos, &'s Placement)>,
+    /// placements only in the old schematic
+    pub removed: Vec<(GridPos, &'s Placement)>,
+    /// the same placement (block, rotation and config) somewhere else: `(from, to, placement)`
+    pub moved: Vec<(GridPos, GridPos, &'s Placement)>,
+    /// the same block in the same place, with another rotation or config: `(position, old, new)`
+    pub reconfigured: Vec<(GridPos, &'s Placement, &'s Placement)>,
+    /// sorted by key
+    pub tags: Vec<TagChange<'s>>,
+    old: &'s Schematic,
+    new: &'s Schematic,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TagChange<'s> {
+    Added {
+        key: &'s str,
+        value: &'s str,
+    },
+    Removed {
+        key: &'s str,
+        value: &'s str,
+    },
+    Changed {
+        key: &'s str,
+        old: &'s str,
+        new: &'s str,
+    },
+}
+
+impl Diff<'_> {
+    /// are the schematics the same
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.moved.is_empty()
+            && self.reconfigured.is_empty()
+            && self.tags.is_empty()
+    }
+}
+
+/// same rotation and config
+fn same(a: &Placement, b: &Placement) -> bool {
+    a.rot == b.rot
+        && match (a.get_state(), b.get_state()) {
+            (None, None) => true,
+            (Some(x), Some(y)) => match (a.block.serialize_state(x), b.block.serialize_state(y)) {
+                (Ok(x), Ok(y)) => x == y,
+                _ => false,
+            },
+            _ => false,
+        }
+}
+
+impl Schematic {
+    /// what changed from `self` to `new`.
+    ///
+    /// blocks in the same place are compared; what is left is matched up by block, rotation and config (as moves).
+    /// ```
+    /// # use mindus::Schematic;
+    /// # use mindus::block::{ROUTER, SORTER, DUO};
+    /// let mut old = Schematic::new(3, 3);
+    /// old.put(0, 0, &ROUTER).put(1, 1, &SORTER).put(2, 2, &DUO);
+    /// let mut new = old.clone();
+    /// new.take(0, 0).unwrap();
+    /// new.put(2, 0, &ROUTER).put(1, 1, &DUO);
+    /// new.tags.insert("name".to_string(), "duos".to_string());
+    /// let diff = old.diff(&new);
+    /// assert_eq!(diff.moved.len(), 1);
+    /// assert_eq!(diff.removed[0].1.block.name(), "sorter");
+    /// assert_eq!(diff.added[0].1.block.name(), "duo");
+    /// assert_eq!(diff.tags.len(), 1);
+    /// ```
+    #[must_use]
+    pub fn diff<'s>(&'s self, new: &'s Schematic) -> Diff<'s> {
+        let mut diff = Diff {
+            added: vec![],
+            removed: vec![],
+            moved: vec![],
+            reconfigured: vec![],
+            tags: vec![],
+            old: self,
+            new,
+        };
+        let mut removed = vec![];
+        for (pos @ GridPos(x, y), old) in self.block_iter() {
+            match new.get(x, y) {
+                Ok(Some(p)) if p.block == old.block => {
+                    if !same(old, p) {
+                        diff.reconfigured.push((pos, old, p));
+                    }
+                }
+                _ => removed.push((pos, old)),
+            }
+        }
+        // new placements that are not in the same place, by block
+        let mut added = HashMap::<&str, Vec<Option<(GridPos, &Placement)>>>::new();
+        for (pos @ GridPos(x, y), p) in new.block_iter() {
+            if !matches!(self.get(x, y), Ok(Some(old)) if old.block == p.block) {
+                added
+                    .entry(p.block.name())
+                    .or_default()
+                    .push(Some((pos, p)));
+            }
+        }
+        for (from, old) in removed {
+            let moved = added.get_mut(old.block.name()).and_then(|a| {
+                a.iter_mut()
+                    .find(|a| a.is_some_and(|(_, p)| same(old, p)))
+                    .and_then(Option::take)
+            });
+            match moved {
+                Some((to, _)) => diff.moved.push((from, to, old)),
+                None => diff.removed.push((from, old)),
+            }
+        }
+        diff.added = added.into_values().flatten().flatten().collect();
+        diff.added
+            .sort_unstable_by_key(|&(GridPos(x, y), _)| (x, y));
+
+        let mut keys = self.tags.keys().chain(new.tags.keys()).collect::<Vec<_>>();
+        keys.sort_unstable();
+        keys.dedup();
+        diff.tags = keys
+            .into_iter()
+            .filter_map(|key| match (self.tags.get(key), new.tags.get(key)) {
+                (Some(old), Some(new)) if old != new => Some(TagChange::Changed { key, old, new }),
+                (Some(value), None) => Some(TagChange::Removed { key, value }),
+                (None, Some(value)) => Some(TagChange::Added { key, value }),
+                _ => None,
+            })
+            .collect();
+        diff
+    }
+}
+
+const ADDED: (u8, u8, u8) = (96, 255, 96);
+const RECONFIGURED: (u8, u8, u8) = (255, 255, 96);
+const REMOVED: (u8, u8, u8) = (255, 64, 64);
+
+impl Renderable for Diff<'_> {
+    /// draws both versions of the schematic.
+    /// ```
+    /// # use mindus::*;
+    /// # use mindus::block::*;
+    /// let mut old = Schematic::new(2, 2);
+    /// old.put(0, 0, &ROUTER);
+    /// let mut new = Schematic::new(3, 2);
+    /// new.put(2, 1, &SORTER);
+    /// let output /*: Image */ = old.diff(&new).render();
+    /// assert_eq!(output.width(), 5 * 32);
+    /// ```
+    fn render_with(&self, opts: &RenderOptions) -> Image<Vec<u8>, 3> {
+        let (width, height) = (
+            self.old.width.max(self.new.width),
+            self.old.height.max(self.new.height),
+        );
+        let tints = self
+            .added
+            .iter()
+            .map(|&(pos, _)| (pos, ADDED))
+            .chain(self.moved.iter().map(|&(_, pos, _)| (pos, ADDED)))
+            .chain(
+                self.reconfigured
+                    .iter()
+                    .map(|&(pos, ..)| (pos, RECONFIGURED)),
+            )
+            .collect::<HashMap<_, _>>();
+        render_schematic(
+            (width, height),
+            opts,
+            |canvas, scale| {
+                draw_placements(
+                    canvas,
+                    self.new,
+                    height,
+                    scale,
+                    self.new
+                        .block_iter()
+                        .map(|(pos, p)| (pos, p, tints.get(&pos).copied())),
+                );
+            },
+            |img, frame| {
+                draw_schematic_links(&mut img.as_mut(), self.new, frame);
+                let removed = self
+                    .removed
+                    .iter()
+                    .copied()
+                    .chain(self.moved.iter().map(|&(from, _, p)| (from, p)));
+                for placement in removed {
+                    draw_placement_ghost(
+                        img.as_mut(),
+                        self.old,
+                        placement,
+                        height,
+                        frame.scale,
+                        REMOVED,
+                    );
+                }
+            },
+        )
+    }
+}
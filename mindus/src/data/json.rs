@@ -0,0 +1,372 @@
+//! arc json, as written by the game (eg the `rules` tag).
+//!
+//! this is a superset of json: keys and strings may be unquoted ("minimal" output), and there can be comments.
+use std::fmt::{self, Write};
+use thiserror::Error;
+
+/// a json value. objects keep their order.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Value>),
+    Object(Vec<(String, Value)>),
+}
+
+impl Value {
+    /// get a field of a object
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Self::Object(o) => o.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub const fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    /// numbers (and strings containing numbers, as arc reads them)
+    #[must_use]
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::Number(n) => Some(*n),
+            Self::String(s) => s.parse().ok(),
+            _ => None,
+        }
+    }
+
+    #[must_use]
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// parse some json
+    /// ```
+    /// # use mindus::data::json::Value;
+    /// let v = Value::parse("{waves:true,spawns:[{type:dagger}],name:\"a map\"}").unwrap();
+    /// assert_eq!(v.get("waves"), Some(&Value::Bool(true)));
+    /// assert_eq!(v.to_string(), "{waves:true,spawns:[{type:dagger}],name:a map}");
+    /// ```
+    pub fn parse(s: &str) -> Result<Self, Error> {
+        let mut p = Parser {
+            s: s.as_bytes(),
+            at: 0,
+            depth: 0,
+        };
+        let v = p.value()?;
+        p.ws();
+        match p.peek() {
+            None => Ok(v),
+            Some(c) => Err(Error::Unexpected(c as char, p.at)),
+        }
+    }
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum Error {
+    #[error("unexpected end of input")]
+    Eof,
+    #[error("unexpected {0:?} at {1}")]
+    Unexpected(char, usize),
+    #[error("invalid escape at {0}")]
+    Escape(usize),
+    #[error("nested deeper than {MAX_DEPTH} at {0}")]
+    TooDeep(usize),
+}
+
+/// how deep arrays and objects may nest
+pub const MAX_DEPTH: usize = 128;
+
+struct Parser<'s> {
+    s: &'s [u8],
+    at: usize,
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.s.get(self.at).copied()
+    }
+
+    /// skip whitespace and comments
+    fn ws(&mut self) {
+        loop {
+            match (self.peek(), self.s.get(self.at + 1)) {
+                (Some(b' ' | b'\t' | b'\r' | b'\n'), _) => self.at += 1,
+                (Some(b'/'), Some(b'/')) => {
+                    while !matches!(self.peek(), None | Some(b'\n')) {
+                        self.at += 1;
+                    }
+                }
+                (Some(b'/'), Some(b'*')) => {
+                    self.at += 2;
+                    while self.at < self.s.len() && !self.s[self.at..].starts_with(b"*/") {
+                        self.at += 1;
+                    }
+                    self.at = (self.at + 2).min(self.s.len());
+                }
+                _ => return,
+            }
+        }
+    }
+
+    fn eat(&mut self, c: u8) -> Result<(), Error> {
+        self.ws();
+        match self.peek() {
+            Some(x) if x == c => {
+                self.at += 1;
+                Ok(())
+            }
+            Some(x) => Err(Error::Unexpected(x as char, self.at)),
+            None => Err(Error::Eof),
+        }
+    }
+
+    /// enter a array or object
+    fn deeper(&mut self) -> Result<(), Error> {
+        if self.depth == MAX_DEPTH {
+            return Err(Error::TooDeep(self.at));
+        }
+        self.depth += 1;
+        self.at += 1;
+        Ok(())
+    }
+
+    fn value(&mut self) -> Result<Value, Error> {
+        self.ws();
+        match self.peek().ok_or(Error::Eof)? {
+            b'{' => {
+                self.deeper()?;
+                let mut o = vec![];
+                loop {
+                    self.ws();
+                    match self.peek() {
+                        Some(b'}') => {
+                            self.at += 1;
+                            self.depth -= 1;
+                            return Ok(Value::Object(o));
+                        }
+                        Some(b',') => self.at += 1,
+                        _ => {
+                            let k = self.string(b":")?;
+                            self.eat(b':')?;
+                            o.push((k, self.value()?));
+                        }
+                    }
+                }
+            }
+            b'[' => {
+                self.deeper()?;
+                let mut a = vec![];
+                loop {
+                    self.ws();
+                    match self.peek() {
+                        Some(b']') => {
+                            self.at += 1;
+                            self.depth -= 1;
+                            return Ok(Value::Array(a));
+                        }
+                        Some(b',') => self.at += 1,
+                        _ => a.push(self.value()?),
+                    }
+                }
+            }
+            b'"' => Ok(Value::String(self.string(b"")?)),
+            _ => {
+                let s = self.string(b",}]")?;
+                Ok(match &*s {
+                    "true" => Value::Bool(true),
+                    "false" => Value::Bool(false),
+                    "null" => Value::Null,
+                    _ if number(&s) => s.parse().map_or(Value::String(s), Value::Number),
+                    _ => Value::String(s),
+                })
+            }
+        }
+    }
+
+    /// reads a quoted string, or a unquoted one ending at any of `end` (or a newline)
+    fn string(&mut self, end: &[u8]) -> Result<String, Error> {
+        self.ws();
+        if self.peek() != Some(b'"') {
+            let from = self.at;
+            while let Some(c) = self.peek() {
+                if end.contains(&c) || c == b'\n' {
+                    break;
+                }
+                self.at += 1;
+            }
+            let s = std::str::from_utf8(&self.s[from..self.at]).map_err(|_| Error::Escape(from))?;
+            return match s.trim() {
+                "" => match self.peek() {
+                    Some(c) => Err(Error::Unexpected(c as char, self.at)),
+                    None => Err(Error::Eof),
+                },
+                s => Ok(s.to_string()),
+            };
+        }
+        self.at += 1;
+        let mut o = vec![];
+        loop {
+            match self.peek().ok_or(Error::Eof)? {
+                b'"' => {
+                    self.at += 1;
+                    return String::from_utf8(o).map_err(|_| Error::Escape(self.at));
+                }
+                b'\\' => {
+                    self.at += 1;
+                    let c = match self.peek().ok_or(Error::Eof)? {
+                        b'n' => '\n',
+                        b't' => '\t',
+                        b'r' => '\r',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'u' => {
+                            let hex = self.s.get(self.at + 1..self.at + 5).ok_or(Error::Eof)?;
+                            let c = std::str::from_utf8(hex)
+                                .ok()
+                                .and_then(|h| u32::from_str_radix(h, 16).ok())
+                                .and_then(char::from_u32)
+                                .ok_or(Error::Escape(self.at))?;
+                            self.at += 4;
+                            c
+                        }
+                        c => c as char,
+                    };
+                    self.at += 1;
+                    o.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+                }
+                c => {
+                    self.at += 1;
+                    o.push(c);
+                }
+            }
+        }
+    }
+}
+
+/// is this a json number (so not `nan`, `inf`, `+1`, `.5`, ...)
+fn number(s: &str) -> bool {
+    let s = s.strip_prefix('-').unwrap_or(s).as_bytes();
+    let digits = |s: &[u8]| s.iter().take_while(|c| c.is_ascii_digit()).count();
+    let int = digits(s);
+    if int == 0 || (s[0] == b'0' && int > 1) {
+        return false;
+    }
+    let mut s = &s[int..];
+    if let Some(rest) = s.strip_prefix(b".") {
+        let frac = digits(rest);
+        if frac == 0 {
+            return false;
+        }
+        s = &rest[frac..];
+    }
+    if let Some(rest) = s.strip_prefix(b"e").or_else(|| s.strip_prefix(b"E")) {
+        let rest = rest
+            .strip_prefix(b"+")
+            .or_else(|| rest.strip_prefix(b"-"))
+            .unwrap_or(rest);
+        let exp = digits(rest);
+        if exp == 0 {
+            return false;
+        }
+        s = &rest[exp..];
+    }
+    s.is_empty()
+}
+
+/// can this be written without quotes
+fn bare(s: &str, key: bool) -> bool {
+    !s.is_empty()
+        && s.trim() == s
+        && !s.contains(|c| matches!(c, '"' | '\\' | ':' | ',' | '{' | '}' | '[' | ']' | '\n'))
+        && !s.starts_with('/')
+        && (key || !matches!(s, "true" | "false" | "null") && !number(s))
+}
+
+fn write_str(f: &mut fmt::Formatter<'_>, s: &str, key: bool) -> fmt::Result {
+    if bare(s, key) {
+        return f.write_str(s);
+    }
+    f.write_char('"')?;
+    for c in s.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c => f.write_char(c)?,
+        }
+    }
+    f.write_char('"')
+}
+
+/// writes minimal json, like the game does
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Null => f.write_str("null"),
+            Self::Bool(b) => write!(f, "{b}"),
+            Self::Number(n) => write!(f, "{n}"),
+            Self::String(s) => write_str(f, s, false),
+            Self::Array(a) => {
+                f.write_char('[')?;
+                for (i, v) in a.iter().enumerate() {
+                    if i != 0 {
+                        f.write_char(',')?;
+                    }
+                    write!(f, "{v}")?;
+                }
+                f.write_char(']')
+            }
+            Self::Object(o) => {
+                f.write_char('{')?;
+                for (i, (k, v)) in o.iter().enumerate() {
+                    if i != 0 {
+                        f.write_char(',')?;
+                    }
+                    write_str(f, k, true)?;
+                    write!(f, ":{v}")?;
+                }
+                f.write_char('}')
+            }
+        }
+    }
+}
+
+#[test]
+fn numbers() {
+    for n in ["0", "-1", "1.5", "2e10", "-0.5E-3"] {
+        assert!(matches!(Value::parse(n), Ok(Value::Number(_))), "{n}");
+    }
+    for s in ["nan", "inf", "-infinity", "+1", ".5", "1.", "01", "1e"] {
+        assert_eq!(Value::parse(s), Ok(Value::String(s.to_string())));
+    }
+    assert_eq!(Value::String("nan".into()).to_string(), "nan");
+}
+
+#[test]
+fn depth() {
+    let nested = |n| "[".repeat(n) + &"]".repeat(n);
+    assert!(Value::parse(&nested(MAX_DEPTH)).is_ok());
+    assert_eq!(
+        Value::parse(&nested(MAX_DEPTH + 1)),
+        Err(Error::TooDeep(MAX_DEPTH))
+    );
+    assert!(matches!(
+        Value::parse(&"{a:".repeat(100_000)),
+        Err(Error::TooDeep(_))
+    ));
+}
@@ -12,7 +12,7 @@
 //! - tag section `<u32>` [`MapReader::tags`]
 //!     - 1 byte of idk (skip)
 //!     - string map (`u16` for map len, iterate each, read `utf`)
-//! - content header section `<u32>`: [`MapReader::skip`]
+//! - content header section `<u32>`: [`MapReader::content`] (see [`ContentMap`])
 //!     - iterate `i8` (should = `8`)'
 //!         - the type: `i8` (0: item, block: 1, liquid: 4, status: 5, unit: 6, weather: 7, sector: 9, planet: 13
 //!         - item count: `u16` (item: 22, block: 412, liquid: 11, status: 21, unit: 66, weather: 6, sector: 35, planet: 7)
@@ -58,7 +58,7 @@
 //!     - team build plans
 //!         - for t in `teams<u32>`
 //!             - team = `team#<u32>`
-//!             - iterate `plans<u32>`
+//!             - iterate `plans<u32>` (see [`Plan`])
 //!                 - x: `u16`, y: `u16`, rot: `u16`, id: `u16`
 //!                 - o: `DynData` (refer to [`DynSerializer`])
 //!         - world entities
@@ -70,23 +70,38 @@
 //!                     - continue
 //!                 - id: `u32`
 //!                 - entity read
-use std::collections::HashMap;
+//! - custom chunks section `<u32>` (see [`CustomChunk`])
+//!     - iterate `u32`
+//!         - name: `utf`
+//!         - len: `u32`
+//!         - data (eg [`StaticFog`])
+//!
+//! [`Map::serialize`] writes the same format back out.
+use std::collections::{HashMap, HashSet};
 use std::ops::CoroutineState::*;
 use std::ops::{Coroutine, Index, IndexMut};
 use std::pin::Pin;
 use thiserror::Error;
 
 use crate::block::content::Type as BlockEnum;
+use crate::block::distribution::{ConveyorItem, ItemBuffer};
+use crate::block::payload::{PayloadBuild, PayloadEntity};
+use crate::block::ratios::{Ground, Io, IoBuilder};
 use crate::block::{Block, Rotation, State};
+use crate::content::Type as ContentType;
+use crate::data::command::UnitCommand;
 use crate::data::dynamic::DynData;
 use crate::data::renderer::*;
-use crate::data::DataRead;
+use crate::data::rules::{Error as RulesError, Rules};
+use crate::data::schematic::{PlaceError, Placement, PosError, Schematic};
+use crate::data::{DataRead, DataWrite, GridPos};
 use crate::fluid::Type as Fluid;
 use crate::item::{storage::Storage, Type as Item};
 use crate::team::{self, Team};
 use crate::unit::Unit;
 #[cfg(doc)]
-use crate::{block::content, data::*, fluid, item, modifier, unit};
+use crate::{block::content, data::*, fluid, item};
+use crate::{modifier, unit};
 
 use super::{entity_mapping, Serializable};
 use crate::content::Content;
@@ -164,15 +179,7 @@ impl Tile {
     }
 
     fn set_block(&mut self, block: &'static Block) {
-        self.build = Some(Build {
-            block,
-            state: None,
-            items: Storage::new(),
-            liquids: Storage::new(),
-            rotation: Rotation::Up,
-            team: crate::team::SHARDED,
-            data: 0,
-        });
+        self.build = Some(Build::new(block));
     }
 
     #[must_use]
@@ -281,10 +288,243 @@ pub struct Build {
     pub items: Storage<Item>,
     pub liquids: Storage<Fluid>,
     pub state: Option<State>,
-    // pub health: f32,
+    pub health: f32,
     pub rotation: Rotation,
     pub team: Team,
     pub data: i8,
+    pub enabled: bool,
+    pub power: Option<Power>,
+    /// in `0..=1`
+    pub efficiency: f32,
+    /// in `0..=1`
+    pub optional_efficiency: f32,
+    /// which teams can see this, when there is fog
+    pub visible_flags: Option<u64>,
+    /// the blocks own data, after the base
+    pub extra: Extra,
+    /// why [`extra`](Self::extra) is [`Extra::Raw`], if it was read that way
+    pub raw_reason: Option<RawReason>,
+    pub(crate) revision: i8,
+}
+
+/// why the chunk of a build was kept as [`Extra::Raw`]
+#[derive(Clone, Debug, PartialEq, Eq, Error)]
+pub enum RawReason {
+    #[error("failed to read the block data: {0}")]
+    Failed(String),
+    #[error("{0} bytes were left after the block data")]
+    Leftover(usize),
+}
+
+/// a buildings power module
+#[derive(Clone, Debug, PartialEq)]
+pub struct Power {
+    /// the positions of the buildings this is laser linked to
+    pub links: Vec<GridPos>,
+    /// how satisfied the power graph is, in `0..=1`
+    pub status: f32,
+}
+
+/// the data a block keeps in its building, after the base (what `write` writes, in the subclass).
+///
+/// blocks that have a [`State`] (sorters, processors, messages, ...) keep that there, and the rest here.
+#[derive(Clone, Debug, Default)]
+pub enum Extra {
+    /// nothing (more)
+    #[default]
+    None,
+    /// the chunk did not fit the blocks format (or it is not known): kept as read, and written back as is.
+    Raw(Box<[u8]>),
+    Conveyor(Vec<ConveyorItem>),
+    Duct {
+        /// the direction the item came from
+        rec_dir: i8,
+    },
+    /// a buffer for each direction
+    Junction([ItemBuffer; 4]),
+    /// plastanium and surge conveyors
+    StackConveyor {
+        link: i32,
+        cooldown: f32,
+    },
+    /// surge routers
+    Sort(Option<Item>),
+    CargoLoader {
+        /// id of the unit it made, or -1
+        unit: i32,
+    },
+    DuctUnloader {
+        offset: u16,
+    },
+    UnloadPoint {
+        stale: bool,
+    },
+    /// item and liquid bridges (bridge conveyors have a buffer too)
+    ItemBridge {
+        link: i32,
+        warmup: f32,
+        incoming: Vec<i32>,
+        moved: bool,
+        buffer: Option<ItemBuffer>,
+    },
+    MassDriver {
+        link: i32,
+        rotation: f32,
+        state: u8,
+    },
+    PayloadMassDriver {
+        payload: PayloadBuild,
+        link: i32,
+        rotation: f32,
+        state: u8,
+        reload: f32,
+        charge: f32,
+        loaded: bool,
+        charging: bool,
+    },
+    /// heat crafters have heat (the cultivator has a extra `f32` there too)
+    Crafter {
+        progress: f32,
+        warmup: f32,
+        heat: Option<f32>,
+    },
+    Separator {
+        progress: f32,
+        warmup: f32,
+        seed: i32,
+    },
+    /// reactors have heat (warmup, for the impact reactor)
+    Generator {
+        /// in `0..=1`
+        productivity: f32,
+        generate_time: f32,
+        heat: Option<f32>,
+    },
+    Drill {
+        progress: f32,
+        warmup: f32,
+    },
+    /// menders and overdrive projectors
+    Heated {
+        heat: f32,
+        phase_heat: f32,
+    },
+    Radar {
+        progress: f32,
+    },
+    Shield {
+        smoothing: f32,
+        broken: bool,
+    },
+    Turret {
+        reload: f32,
+        rotation: f32,
+    },
+    ItemTurret {
+        reload: f32,
+        rotation: f32,
+        /// in the order its used (last first)
+        ammo: Vec<(Item, u16)>,
+    },
+    ContinousTurret {
+        reload: f32,
+        rotation: f32,
+        last_length: f32,
+    },
+    /// point defense, tractor beam and repair turrets
+    Aim {
+        rotation: f32,
+    },
+    /// the code and links are in the [`ProcessorState`](crate::block::logic::ProcessorState)
+    Processor {
+        vars: Vec<(String, DynData)>,
+        memory: Vec<f64>,
+    },
+    Memory(Vec<f64>),
+    PayloadConveyor {
+        progress: f32,
+        rotation: f32,
+        payload: Option<PayloadEntity>,
+    },
+    PayloadRouter {
+        progress: f32,
+        rotation: f32,
+        payload: Option<PayloadEntity>,
+        /// [`ContentType`] of the sorted content, or -1
+        sort_type: i8,
+        sort: i16,
+        rec_dir: u8,
+    },
+    /// assembler modules
+    Payload(PayloadBuild),
+    UnitFactory {
+        payload: PayloadBuild,
+        progress: f32,
+        /// index of the unit being made, or -1
+        plan: i16,
+        command_pos: Option<(f32, f32)>,
+        command: Option<UnitCommand>,
+    },
+    Reconstructor {
+        payload: PayloadBuild,
+        progress: f32,
+        command_pos: Option<(f32, f32)>,
+        command: Option<UnitCommand>,
+    },
+    Assembler {
+        payload: PayloadBuild,
+        progress: f32,
+        /// ids of the units that are waiting
+        units: Vec<i32>,
+        blocks: Vec<(ContentType, u16, i32)>,
+        command_pos: Option<(f32, f32)>,
+    },
+}
+
+impl Extra {
+    /// where this bridge (or mass driver) is linked to
+    #[must_use]
+    pub fn link(&self) -> Option<GridPos> {
+        match self {
+            Self::ItemBridge { link, .. }
+            | Self::MassDriver { link, .. }
+            | Self::PayloadMassDriver { link, .. }
+                if *link != -1 =>
+            {
+                Some(GridPos::from(*link as u32))
+            }
+            _ => None,
+        }
+    }
+
+    /// a new bridge (or mass driver), linked to `link`
+    pub(crate) fn linked(block: &Block, link: GridPos) -> Self {
+        let link = u32::from(link) as i32;
+        match block.name() {
+            "mass-driver" => Self::MassDriver {
+                link,
+                rotation: 90.0,
+                state: 0,
+            },
+            "payload-mass-driver" | "large-payload-mass-driver" => Self::PayloadMassDriver {
+                payload: PayloadBuild::default(),
+                link,
+                rotation: 90.0,
+                state: 0,
+                reload: 0.0,
+                charge: 0.0,
+                loaded: false,
+                charging: false,
+            },
+            _ => Self::ItemBridge {
+                link,
+                warmup: 0.0,
+                incoming: vec![],
+                moved: false,
+                buffer: None,
+            },
+        }
+    }
 }
 
 impl std::fmt::Debug for Build {
@@ -301,15 +541,27 @@ impl Build {
             items: Storage::default(),
             liquids: Storage::default(),
             state: None,
+            // clamped to the blocks health when loaded
+            health: f32::MAX,
             rotation: Rotation::Up,
             team: team::SHARDED,
             data: 0,
+            enabled: true,
+            power: None,
+            efficiency: 0.0,
+            optional_efficiency: 0.0,
+            visible_flags: None,
+            extra: Extra::None,
+            raw_reason: None,
+            revision: 0,
         }
     }
 
     fn image(&self, context: Option<&RenderingContext>, s: Scale) -> ImageHolder<4> {
-        self.block
-            .image(self.state.as_ref(), context, self.rotation, s)
+        let img = self
+            .block
+            .image(self.state.as_ref(), context, self.rotation, s);
+        team_colored(img, self.block.name(), self.team, s)
     }
 
     #[must_use]
@@ -317,12 +569,55 @@ impl Build {
         self.block.name()
     }
 
+    /// read this build from its chunk (after the revision). `buff` should end where the chunk does.
+    ///
+    /// if the rest of the chunk does not fit the blocks format, it is kept as [`Extra::Raw`]
+    /// (and the [`State`] the block read is dropped), with the [`RawReason`].
+    /// ids are assumed to be ours (see [`Build::read_mapped`]).
     pub fn read(&mut self, buff: &mut DataRead<'_>) -> Result<(), ReadError> {
-        // health
-        let _ = buff.read_f32()?;
+        self.read_mapped(buff, &ContentMap::default())
+    }
+
+    /// [`Build::read`], with the items and liquids mapped through the maps [`ContentMap`].
+    pub fn read_mapped(
+        &mut self,
+        buff: &mut DataRead<'_>,
+        content: &ContentMap,
+    ) -> Result<(), ReadError> {
+        self.read_base(buff, content)?;
+        let rest = buff.data;
+        // "overridden by subclasses"
+        let reason = match self.block.read(self, buff, content) {
+            Err(e) => RawReason::Failed(e.to_string()),
+            Ok(()) if !buff.data.is_empty() => RawReason::Leftover(buff.data.len()),
+            Ok(()) => return Ok(()),
+        };
+        // what was read of it is not written
+        self.state = None;
+        self.extra = Extra::Raw(rest.into());
+        self.raw_reason = Some(reason);
+        buff.data = &[];
+        Ok(())
+    }
+
+    /// read this build, where nothing says where it ends (payloads).
+    pub(crate) fn read_all(
+        &mut self,
+        buff: &mut DataRead<'_>,
+        content: &ContentMap,
+    ) -> Result<(), ReadError> {
+        self.read_base(buff, content)?;
+        Ok(self.block.read(self, buff, content)?)
+    }
+
+    fn read_base(
+        &mut self,
+        buff: &mut DataRead<'_>,
+        content: &ContentMap,
+    ) -> Result<(), ReadError> {
+        self.health = buff.read_f32()?;
         let rot = buff.read_i8()? as i16;
-        // team
-        let _ = buff.read_i8()?;
+        self.team = Team::of(buff.read_u8()?);
         self.rotation = Rotation::try_from((rot & 127) as u8).unwrap_or(Rotation::Up);
         let mut mask = 0;
         let mut version = 0;
@@ -331,30 +626,67 @@ impl Build {
             if version < 3 {
                 return Err(ReadError::Version(version));
             }
-            buff.skip(1)?;
+            self.enabled = buff.read_bool()?;
             mask = buff.read_u8()?;
         }
 
         if mask & 1 != 0 {
-            read_items(buff, &mut self.items)?;
+            read_items(buff, &mut self.items, content)?;
         }
         if mask & 2 != 0 {
-            read_power(buff)?;
+            self.power = Some(read_power(buff)?);
         }
         if mask & 4 != 0 {
-            read_liquids(buff, &mut self.liquids)?;
+            read_liquids(buff, &mut self.liquids, content)?;
         }
-        // "efficiency"
-        _ = buff.read_u8()? as f64 / 255.;
-        _ = buff.read_u8()? as f64 / 255.;
+        self.efficiency = f32::from(buff.read_u8()?) / 255.;
+        self.optional_efficiency = f32::from(buff.read_u8()?) / 255.;
 
         if version == 4 {
-            // visible flags for fog
-            _ = buff.read_u64()?;
+            self.visible_flags = Some(buff.read_u64()?);
         }
-        // "overridden by subclasses"
-        self.block.read(self, buff)?;
+        Ok(())
+    }
+
+    /// write this build for its chunk (after the revision), in the format [`Build::read`] reads.
+    pub fn write(&self, buff: &mut DataWrite<'_>) -> Result<(), WriteError> {
+        self.write_mapped(buff, &ContentMap::default())
+    }
 
+    /// [`Build::write`], with ids from the maps [`ContentMap`].
+    pub(crate) fn write_mapped(
+        &self,
+        buff: &mut DataWrite<'_>,
+        content: &ContentMap,
+    ) -> Result<(), WriteError> {
+        buff.write_f32(self.health)?;
+        buff.write_u8(u8::from(self.rotation) | 128)?;
+        buff.write_u8(self.team.into())?;
+        buff.write_u8(if self.visible_flags.is_some() { 4 } else { 3 })?;
+        buff.write_bool(self.enabled)?;
+        let items = self.items.iter_nonzero().next().is_some();
+        let liquids = self.liquids.iter_nonzero().next().is_some();
+        buff.write_u8(
+            u8::from(items) | u8::from(self.power.is_some()) << 1 | u8::from(liquids) << 2,
+        )?;
+        if items {
+            write_items(buff, &self.items, content)?;
+        }
+        if let Some(power) = &self.power {
+            write_power(buff, power)?;
+        }
+        if liquids {
+            write_liquids(buff, &self.liquids, content)?;
+        }
+        buff.write_u8((self.efficiency.clamp(0.0, 1.0) * 255.).round() as u8)?;
+        buff.write_u8((self.optional_efficiency.clamp(0.0, 1.0) * 255.).round() as u8)?;
+        if let Some(flags) = self.visible_flags {
+            buff.write_u64(flags)?;
+        }
+        match &self.extra {
+            Extra::Raw(raw) => buff.write_bytes(raw)?,
+            _ => self.block.write(self, buff, content)?,
+        }
         Ok(())
     }
 }
@@ -364,14 +696,18 @@ impl Build {
 ///     - item: [`u16`] as [`Item`]
 ///     - amount: [`u32`]
 ///
-fn read_items(from: &mut DataRead, to: &mut Storage<Item>) -> Result<(), ReadError> {
+fn read_items(
+    from: &mut DataRead,
+    to: &mut Storage<Item>,
+    content: &ContentMap,
+) -> Result<(), ReadError> {
     to.clear();
     let n = from.read_u16()?;
     to.reserve(n as usize);
     for _ in 0..n {
         let item = from.read_u16()?;
         let amount = from.read_u32()?;
-        if let Ok(item) = Item::try_from(item) {
+        if let Some(item) = content.item(item) {
             to.set(item, amount);
         }
     }
@@ -382,14 +718,18 @@ fn read_items(from: &mut DataRead, to: &mut Storage<Item>) -> Result<(), ReadErr
 /// - iterate [`u16`]
 ///     - liquid: [`u16`] as [`Fluid`]
 ///     - amount: [`f32`]
-fn read_liquids(from: &mut DataRead, to: &mut Storage<Fluid>) -> Result<(), ReadError> {
+fn read_liquids(
+    from: &mut DataRead,
+    to: &mut Storage<Fluid>,
+    content: &ContentMap,
+) -> Result<(), ReadError> {
     to.clear();
     let n = from.read_u16()?;
     to.reserve(n as usize);
     for _ in 0..n {
         let fluid = from.read_u16()?;
         let amount = from.read_f32()?;
-        if let Ok(fluid) = Fluid::try_from(fluid) {
+        if let Some(fluid) = content.fluid(fluid) {
             to.set(fluid, (amount * 100.0) as u32);
         }
     }
@@ -400,12 +740,51 @@ fn read_liquids(from: &mut DataRead, to: &mut Storage<Fluid>) -> Result<(), Read
 /// - iterate [`u16`]
 ///     - link: [`i32`]
 /// - status: [`f32`]
-fn read_power(from: &mut DataRead) -> Result<(), ReadError> {
+fn read_power(from: &mut DataRead) -> Result<Power, ReadError> {
     let n = from.read_u16()? as usize;
-    from.skip((n + 1) * 4)?;
+    let mut links = Vec::with_capacity(n);
+    for _ in 0..n {
+        links.push(GridPos::from(from.read_u32()?));
+    }
+    let status = from.read_f32()?;
+    Ok(Power { links, status })
+}
+
+fn write_items(
+    to: &mut DataWrite,
+    from: &Storage<Item>,
+    content: &ContentMap,
+) -> Result<(), super::WriteError> {
+    to.write_u16(from.iter_nonzero().count() as u16)?;
+    for (item, amount) in from.iter_nonzero() {
+        to.write_u16(content.item_id(item))?;
+        to.write_u32(amount)?;
+    }
+    Ok(())
+}
+
+fn write_liquids(
+    to: &mut DataWrite,
+    from: &Storage<Fluid>,
+    content: &ContentMap,
+) -> Result<(), super::WriteError> {
+    to.write_u16(from.iter_nonzero().count() as u16)?;
+    for (fluid, amount) in from.iter_nonzero() {
+        to.write_u16(content.fluid_id(fluid))?;
+        // in the middle, so that the truncation in read_liquids gets the same amount back
+        to.write_f32((amount as f32 + 0.5) / 100.0)?;
+    }
     Ok(())
 }
 
+fn write_power(to: &mut DataWrite, from: &Power) -> Result<(), super::WriteError> {
+    to.write_u16(from.links.len() as u16)?;
+    for &link in &from.links {
+        to.write_u32(link.into())?;
+    }
+    to.write_f32(from.status)
+}
+
 #[test]
 fn test_read_items() {
     let mut s = Storage::new();
@@ -415,6 +794,7 @@ fn test_read_items() {
             0, 6, 0, 0, 1, 46, 0, 12, 0, 0, 1, 81, 255, 255,
         ]),
         &mut s,
+        &ContentMap::default(),
     )
     .unwrap();
     assert!(s.get_total() == 5983);
@@ -426,19 +806,65 @@ fn test_read_liquids() {
     read_liquids(
         &mut DataRead::new(&[0, 1, 0, 0, 67, 111, 247, 126, 255, 255]),
         &mut s,
+        &ContentMap::default(),
     )
     .unwrap();
     assert!(s.get(Fluid::Water) == 23996);
 }
 
+#[test]
+fn chunk_counts() {
+    // counts the data cannot hold error, instead of allocating them
+    let fog = [255, 0xff, 0xff, 0xff, 0xff, 0, 127];
+    assert!(StaticFog::read(&mut DataRead::new(&fog)).is_err());
+    let chunks = [0xff, 0xff, 0xff, 0xff, 0, 0, 0, 0, 0, 0];
+    assert!(CustomChunk::read_all(&mut DataRead::new(&chunks)).is_err());
+}
+
+#[test]
+fn content_map() {
+    let mut b = DataWrite::default();
+    b.write_u8(2).unwrap();
+    b.write_u8(ContentType::Block as u8).unwrap();
+    b.write_u16(3).unwrap();
+    for name in ["air", "modded-router", "router"] {
+        b.write_utf(name).unwrap();
+    }
+    b.write_u8(ContentType::Item as u8).unwrap();
+    b.write_u16(2).unwrap();
+    b.write_utf("lead").unwrap();
+    b.write_utf("copper").unwrap();
+    let c = ContentMap::read(&mut DataRead::new(b.get_written())).unwrap();
+    assert!(c.building(0).unwrap().is_none());
+    assert_eq!(c.building(1).unwrap().unwrap().name(), "modded-router");
+    assert_eq!(c.building(2).unwrap().unwrap().name(), "router");
+    assert!(c.building(3).is_err());
+    assert_eq!(c.block(1), None);
+    assert_eq!(c.item(0), Some(Item::Lead));
+    // not in the header, so ours
+    assert_eq!(c.fluid(0), Some(Fluid::Water));
+
+    let mut s = Storage::new();
+    read_items(&mut DataRead::new(&[0, 1, 0, 1, 0, 0, 0, 5]), &mut s, &c).unwrap();
+    assert_eq!(s.get(Item::Copper), 5);
+}
+
 /// a map.
-/// ## Does not support serialization yet!
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Map {
     pub width: usize,
     pub height: usize,
     pub tags: HashMap<String, String>,
+    /// the units. these are written back as they were read.
     pub entities: Vec<Unit>,
+    /// the content header the map was read with. what is kept as read
+    /// ([`Extra::Raw`], units, plan configs) uses its ids, so it is written back.
+    pub content: ContentMap,
+    /// the entities that are not units we know, as read, with how many [units](Map::entities) come before them.
+    /// these are written back in place.
+    pub unknown_entities: Vec<(usize, Box<[u8]>)>,
+    /// the rebuild queues (ghost blocks) of each team, in the order they were read.
+    pub plans: Vec<(Team, Vec<Plan>)>,
     /// row major 2d array
     /// ```rs
     /// (0, 0), (1, 0), (2, 0)
@@ -446,6 +872,124 @@ pub struct Map {
     /// (0, 2), (1, 2), (2, 2)
     /// ```
     pub tiles: Vec<Tile>,
+    /// the named custom chunks (eg markers, static fog)
+    pub custom: Vec<CustomChunk>,
+}
+
+/// a named chunk of the custom chunks section
+#[derive(Clone, Debug)]
+pub struct CustomChunk {
+    pub name: String,
+    pub data: ChunkData,
+}
+
+#[derive(Clone, Debug)]
+pub enum ChunkData {
+    /// `static-fog-data`
+    StaticFog(StaticFog),
+    /// chunks we dont know, as read (eg `markers`)
+    Raw(Box<[u8]>),
+}
+
+/// the revealed areas of the static fog, for each team.
+///
+/// format:
+/// - teams: `u8`
+/// - width: `u16`, height: `u16`
+/// - iterate teams
+///     - team: `u8`
+///     - while not `width * height` read
+///         - run: `u8` (revealed: `run & 128`, length: `run & 127`)
+#[derive(Clone, Debug)]
+pub struct StaticFog {
+    pub width: u16,
+    pub height: u16,
+    /// row major, `true` if revealed
+    pub teams: Vec<(Team, Vec<bool>)>,
+}
+
+impl StaticFog {
+    fn read(buff: &mut DataRead) -> Result<Self, super::ReadError> {
+        let n = buff.read_u8()?;
+        let (width, height) = (buff.read_u16()?, buff.read_u16()?);
+        let len = width as usize * height as usize;
+        let mut teams = Vec::with_capacity(n as usize);
+        for _ in 0..n {
+            let team = Team::of(buff.read_u8()?);
+            // a run is at most 127 long
+            let mut revealed = Vec::with_capacity(len.min(buff.remaining() * 127));
+            while revealed.len() < len {
+                let run = buff.read_u8()?;
+                let n = (run & 127) as usize;
+                if revealed.len() + n > len {
+                    return Err(super::ReadError::Expected("fog run in bounds"));
+                }
+                revealed.extend(std::iter::repeat(run & 128 != 0).take(n));
+            }
+            teams.push((team, revealed));
+        }
+        Ok(Self {
+            width,
+            height,
+            teams,
+        })
+    }
+
+    fn write(&self, buff: &mut DataWrite) -> Result<(), super::WriteError> {
+        buff.write_u8(self.teams.len() as u8)?;
+        buff.write_u16(self.width)?;
+        buff.write_u16(self.height)?;
+        for (team, revealed) in &self.teams {
+            buff.write_u8((*team).into())?;
+            let mut i = 0;
+            while i < revealed.len() {
+                let cur = revealed[i];
+                let n = revealed[i..]
+                    .iter()
+                    .take(127)
+                    .take_while(|&&x| x == cur)
+                    .count();
+                buff.write_u8(u8::from(cur) << 7 | n as u8)?;
+                i += n;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl CustomChunk {
+    fn read_all(buff: &mut DataRead) -> Result<Vec<Self>, super::ReadError> {
+        let n = buff.read_u32()?;
+        // a chunk is at least a name and a length
+        let mut chunks = Vec::with_capacity((n as usize).min(buff.remaining() / 6));
+        for _ in 0..n {
+            let name = buff.read_utf()?.to_string();
+            let len = buff.read_u32()? as usize;
+            let raw = buff.eat(len)?;
+            let mut chunk = DataRead::new(raw);
+            let data = match &*name {
+                "static-fog-data" => StaticFog::read(&mut chunk)
+                    .ok()
+                    .filter(|_| chunk.data.is_empty())
+                    .map(ChunkData::StaticFog),
+                _ => None,
+            }
+            .unwrap_or_else(|| ChunkData::Raw(raw.into()));
+            chunks.push(Self { name, data });
+        }
+        Ok(chunks)
+    }
+
+    fn write(&self, buff: &mut DataWrite) -> Result<(), super::WriteError> {
+        buff.write_utf(&self.name)?;
+        let mut data = DataWrite::default();
+        match &self.data {
+            ChunkData::StaticFog(fog) => fog.write(&mut data)?,
+            ChunkData::Raw(raw) => data.write_bytes(raw)?,
+        }
+        buff.write_u32(data.get_written().len() as u32)?;
+        buff.write_bytes(data.get_written())
+    }
 }
 
 macro_rules! cond {
@@ -459,19 +1003,17 @@ macro_rules! cond {
 }
 
 impl Crossable for Map {
-    fn cross(&self, j: usize, c: &PositionContext) -> Cross {
+    fn cross(&self, j: usize, _: &PositionContext) -> Cross {
         let get = |i| {
             let b = &self[i];
             Some((b.get_block()?, b.get_rotation()?))
         };
+        let (x, y) = (j % self.width, j / self.width);
         [
-            cond![
-                c.position.1 == 0 || c.position.1 >= c.height,
-                get(j + self.height)
-            ],
-            cond![c.position.0 >= (c.height - 1), get(j + 1)],
-            cond![c.position.1 >= (c.height - 1), get(j - self.width)],
-            cond![j < c.height, get(j - 1)],
+            cond![y + 1 >= self.height, get(j + self.width)],
+            cond![x + 1 >= self.width, get(j + 1)],
+            cond![y == 0, get(j - self.width)],
+            cond![x == 0, get(j - 1)],
         ]
     }
 }
@@ -485,12 +1027,580 @@ impl Map {
             width,
             tags,
             entities: vec![],
+            content: ContentMap::default(),
+            unknown_entities: vec![],
+            plans: vec![],
+            custom: vec![],
         }
     }
 
+    /// a map of this floor, with nothing on it
+    #[must_use]
+    pub fn filled(width: usize, height: usize, floor: BlockEnum) -> Self {
+        let mut m = Self::new(width, height, HashMap::new());
+        m.tiles
+            .resize(width * height, Tile::new(floor, BlockEnum::Air));
+        m
+    }
+
     fn push(&mut self, t: Tile) {
         self.tiles.push(t);
     }
+
+    /// parse the `rules` tag. maps without one have the default rules.
+    pub fn rules(&self) -> Result<Rules, RulesError> {
+        self.tags
+            .get("rules")
+            .map_or_else(|| Ok(Rules::default()), |r| Rules::parse(r))
+    }
+
+    /// write the `rules` tag
+    pub fn set_rules(&mut self, rules: &Rules) {
+        self.tags.insert("rules".to_string(), rules.to_string());
+    }
+
+    /// get a custom chunk by name
+    #[must_use]
+    pub fn custom_chunk(&self, name: &str) -> Option<&ChunkData> {
+        self.custom.iter().find(|c| c.name == name).map(|c| &c.data)
+    }
+
+    /// what every building takes and makes, with drills, pumps and extractors
+    /// getting what the tiles under them give (see [`Block::io_on`]).
+    /// ```
+    /// # use mindus::Map;
+    /// # use mindus::block::{content::Type, ratios::Resource, Rotation, PNEUMATIC_DRILL};
+    /// # use mindus::item::Type::Lead;
+    /// # use mindus::team::SHARDED;
+    /// let mut m = Map::filled(4, 4, Type::Stone);
+    /// m.set_ore(1, 1, Type::OreLead).unwrap();
+    /// m.set_ore(2, 1, Type::OreLead).unwrap();
+    /// m.set_block(1, 1, &PNEUMATIC_DRILL, Rotation::Up, SHARDED).unwrap();
+    /// // two of the four tiles are lead
+    /// assert_eq!(m.ratios(false).net(Resource::Item(Lead)), 0.27);
+    /// ```
+    #[must_use]
+    pub fn ratios(&self, boost: bool) -> Io {
+        let mut io = IoBuilder::default();
+        for (j, tile) in self.tiles.iter().enumerate() {
+            let Some(b) = tile.build() else { continue };
+            let (x, y) = (j % self.width, j / self.width);
+            let size = b.block.get_size() as usize;
+            let off = (size - 1) / 2;
+            let under = (y.saturating_sub(off)..(y + size - off).min(self.height))
+                .flat_map(|y| {
+                    (x.saturating_sub(off)..(x + size - off).min(self.width))
+                        .map(move |x| x + y * self.width)
+                })
+                .map(|j| Ground {
+                    floor: self.tiles[j].floor,
+                    ore: self.tiles[j].ore,
+                })
+                .collect::<Vec<_>>();
+            io += b.block.io_on(b.state.as_ref(), &under, boost);
+        }
+        io.into()
+    }
+}
+
+/// editing. positions are in game coordinates (`(0, 0)` is the bottom left), like [`Plan::position`].
+///
+/// buildings are stored at their center; the other tiles under them are empty.
+impl Map {
+    const fn pos(&self, x: usize, y: usize) -> Result<usize, PosError> {
+        if x >= self.width || y >= self.height {
+            return Err(PosError {
+                x,
+                y,
+                w: self.width,
+                h: self.height,
+            });
+        }
+        Ok(x + y * self.width)
+    }
+
+    /// get a tile
+    #[must_use]
+    pub fn get(&self, x: usize, y: usize) -> Option<&Tile> {
+        self.pos(x, y).ok().map(|i| &self.tiles[i])
+    }
+
+    pub fn set_floor(&mut self, x: usize, y: usize, floor: BlockEnum) -> Result<(), PosError> {
+        let i = self.pos(x, y)?;
+        self.tiles[i].floor = floor;
+        Ok(())
+    }
+
+    pub fn set_ore(&mut self, x: usize, y: usize, ore: BlockEnum) -> Result<(), PosError> {
+        let i = self.pos(x, y)?;
+        self.tiles[i].ore = ore;
+        Ok(())
+    }
+
+    /// the building covering this tile, and where its center is.
+    /// ```
+    /// # use mindus::Map;
+    /// # use mindus::block::{Rotation, CORE_SHARD};
+    /// # use mindus::data::GridPos;
+    /// # use mindus::team::SHARDED;
+    /// let mut m = Map::filled(10, 10, mindus::block::content::Type::Stone);
+    /// m.set_block(4, 4, &CORE_SHARD, Rotation::Up, SHARDED).unwrap();
+    /// assert_eq!(m.covering(3, 5).unwrap().0, GridPos(4, 4));
+    /// assert!(m.covering(2, 2).is_none());
+    /// ```
+    #[must_use]
+    pub fn covering(&self, x: usize, y: usize) -> Option<(GridPos, &Build)> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        // blocks are at most 9 big, so the center is at most 4 away
+        for cy in y.saturating_sub(4)..(y + 5).min(self.height) {
+            for cx in x.saturating_sub(4)..(x + 5).min(self.width) {
+                let Some(b) = self.tiles[cx + cy * self.width].build() else {
+                    continue;
+                };
+                let size = b.block.get_size() as usize;
+                let off = (size - 1) / 2;
+                if (cx - off..cx - off + size).contains(&x)
+                    && (cy - off..cy - off + size).contains(&y)
+                {
+                    return Some((GridPos(cx, cy), b));
+                }
+            }
+        }
+        None
+    }
+
+    /// place a block, centered on `x`, `y`. the whole block must fit, and not overlap another.
+    /// ```
+    /// # use mindus::Map;
+    /// # use mindus::block::{Rotation, ROUTER, CORE_SHARD};
+    /// # use mindus::team::SHARDED;
+    /// let mut m = Map::filled(10, 10, mindus::block::content::Type::Stone);
+    /// m.set_block(1, 1, &CORE_SHARD, Rotation::Up, SHARDED).unwrap();
+    /// assert!(m.set_block(2, 2, &ROUTER, Rotation::Up, SHARDED).is_err());
+    /// assert!(m.set_block(0, 0, &CORE_SHARD, Rotation::Up, SHARDED).is_err());
+    /// m.set_block(3, 3, &ROUTER, Rotation::Up, SHARDED).unwrap().data = 0;
+    /// ```
+    pub fn set_block(
+        &mut self,
+        x: usize,
+        y: usize,
+        block: &'static Block,
+        rotation: Rotation,
+        team: Team,
+    ) -> Result<&mut Build, PlaceError> {
+        self.check(x, y, block)?;
+        let tile = &mut self.tiles[x + y * self.width];
+        tile.set_block(block);
+        let build = tile.build.as_mut().unwrap();
+        build.rotation = rotation;
+        build.team = team;
+        Ok(build)
+    }
+
+    /// can `block` be placed here
+    fn check(&self, x: usize, y: usize, block: &Block) -> Result<(), PlaceError> {
+        let size = block.get_size() as usize;
+        let off = (size - 1) / 2;
+        if x < off || y < off || x - off + size > self.width || y - off + size > self.height {
+            return Err(PlaceError::Bounds {
+                x,
+                y,
+                sz: block.get_size(),
+                w: self.width,
+                h: self.height,
+            });
+        }
+        for py in y - off..y - off + size {
+            for px in x - off..x - off + size {
+                if self.covering(px, py).is_some() {
+                    return Err(PlaceError::Overlap { x: px, y: py });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// remove the building covering this tile
+    pub fn remove(&mut self, x: usize, y: usize) -> Result<Option<Build>, PosError> {
+        self.pos(x, y)?;
+        let Some((GridPos(cx, cy), _)) = self.covering(x, y) else {
+            return Ok(None);
+        };
+        Ok(self.tiles[cx + cy * self.width].build.take())
+    }
+
+    fn region(&self, x: usize, y: usize, w: usize, h: usize) -> Result<(), PosError> {
+        if w == 0 || h == 0 {
+            return Ok(());
+        }
+        self.pos(x, y)?;
+        self.pos(x + w - 1, y + h - 1)?;
+        Ok(())
+    }
+
+    /// set the floor of a `w`x`h` region
+    pub fn fill_floor(
+        &mut self,
+        x: usize,
+        y: usize,
+        w: usize,
+        h: usize,
+        floor: BlockEnum,
+    ) -> Result<(), PosError> {
+        self.region(x, y, w, h)?;
+        for py in y..y + h {
+            for t in &mut self.tiles[x + py * self.width..x + w + py * self.width] {
+                t.floor = floor;
+            }
+        }
+        Ok(())
+    }
+
+    /// set the ore of a `w`x`h` region
+    pub fn fill_ore(
+        &mut self,
+        x: usize,
+        y: usize,
+        w: usize,
+        h: usize,
+        ore: BlockEnum,
+    ) -> Result<(), PosError> {
+        self.region(x, y, w, h)?;
+        for py in y..y + h {
+            for t in &mut self.tiles[x + py * self.width..x + w + py * self.width] {
+                t.ore = ore;
+            }
+        }
+        Ok(())
+    }
+
+    /// fill a `w`x`h` region (from its bottom left) with a block, in a grid of the blocks size.
+    /// places that are taken, or that the block doesnt fit in, are skipped.
+    /// returns how many were placed.
+    /// ```
+    /// # use mindus::Map;
+    /// # use mindus::block::{Rotation, COPPER_WALL_LARGE};
+    /// # use mindus::team::SHARDED;
+    /// let mut m = Map::filled(10, 10, mindus::block::content::Type::Stone);
+    /// assert_eq!(m.fill_block(0, 0, 5, 4, &COPPER_WALL_LARGE, Rotation::Up, SHARDED).unwrap(), 4);
+    /// ```
+    #[allow(clippy::too_many_arguments)]
+    pub fn fill_block(
+        &mut self,
+        x: usize,
+        y: usize,
+        w: usize,
+        h: usize,
+        block: &'static Block,
+        rotation: Rotation,
+        team: Team,
+    ) -> Result<usize, PosError> {
+        self.region(x, y, w, h)?;
+        let size = block.get_size() as usize;
+        let off = (size - 1) / 2;
+        let mut n = 0;
+        for py in (y..y + h).step_by(size).filter(|py| py + size <= y + h) {
+            for px in (x..x + w).step_by(size).filter(|px| px + size <= x + w) {
+                if self
+                    .set_block(px + off, py + off, block, rotation, team)
+                    .is_ok()
+                {
+                    n += 1;
+                }
+            }
+        }
+        Ok(n)
+    }
+
+    /// resize the map, keeping the bottom left corner where it is.
+    /// new tiles get `floor`; buildings and plans that no longer fit are removed.
+    pub fn resize(&mut self, width: usize, height: usize, floor: BlockEnum) {
+        self.reframe(0, 0, width, height, floor);
+    }
+
+    /// crop the map to a `w`x`h` region, whose bottom left corner becomes `(0, 0)`.
+    /// buildings and plans that no longer fit are removed.
+    ///
+    /// as units are written back as they were read, they can not be moved:
+    /// if the region does not start at `(0, 0)`, they are removed.
+    pub fn crop(&mut self, x: usize, y: usize, w: usize, h: usize) -> Result<(), PosError> {
+        self.region(x, y, w, h)?;
+        self.reframe(x, y, w, h, BlockEnum::Stone);
+        Ok(())
+    }
+
+    fn reframe(&mut self, x: usize, y: usize, width: usize, height: usize, floor: BlockEnum) {
+        let fits = |GridPos(cx, cy): GridPos, size: u8| {
+            let size = size as usize;
+            let off = (size - 1) / 2;
+            cx >= x + off
+                && cy >= y + off
+                && cx - off + size <= x + width
+                && cy - off + size <= y + height
+        };
+        let mut tiles = Vec::with_capacity(width * height);
+        for ny in 0..height {
+            for nx in 0..width {
+                let (ox, oy) = (nx + x, ny + y);
+                if ox >= self.width || oy >= self.height {
+                    tiles.push(Tile::new(floor, BlockEnum::Air));
+                    continue;
+                }
+                let mut t = self.tiles[ox + oy * self.width].clone();
+                if t.build()
+                    .is_some_and(|b| !fits(GridPos(ox, oy), b.block.get_size()))
+                {
+                    t.build = None;
+                }
+                tiles.push(t);
+            }
+        }
+        for (_, plans) in &mut self.plans {
+            plans.retain(|p| fits(p.position, p.block.get_size()));
+            for p in plans {
+                p.position = GridPos(p.position.0 - x, p.position.1 - y);
+            }
+        }
+        if (x, y) == (0, 0) {
+            self.entities.retain(|u| {
+                (u.state.position.0 as usize) < width && (u.state.position.1 as usize) < height
+            });
+        } else {
+            self.entities.clear();
+        }
+        // where these are is not known
+        self.unknown_entities.clear();
+        for chunk in &mut self.custom {
+            if let ChunkData::StaticFog(fog) = &mut chunk.data {
+                let (ow, oh) = (fog.width as usize, fog.height as usize);
+                for (_, revealed) in &mut fog.teams {
+                    *revealed = (0..width * height)
+                        .map(|i| (i % width + x, i / width + y))
+                        .map(|(fx, fy)| fx < ow && fy < oh && revealed[fx + fy * ow])
+                        .collect();
+                }
+                fog.width = width as u16;
+                fog.height = height as u16;
+            }
+        }
+        self.tiles = tiles;
+        self.width = width;
+        self.height = height;
+    }
+}
+
+/// schematics. as in the game, links (bridges, power nodes, processors) are relative in a schematic,
+/// but absolute in a map (where they are kept in [`Extra`] and [`Power`], not the [`State`], but for processors).
+impl Map {
+    /// copy the buildings that are entirely inside a `w`x`h` region (from its bottom left) into a schematic.
+    /// links to buildings that are not copied are left out.
+    ///
+    /// panics if the region is bigger than a schematic can be.
+    /// ```
+    /// # use mindus::Map;
+    /// # use mindus::block::{Rotation, ROUTER, CORE_SHARD};
+    /// # use mindus::team::SHARDED;
+    /// let mut m = Map::filled(10, 10, mindus::block::content::Type::Stone);
+    /// m.set_block(1, 1, &CORE_SHARD, Rotation::Up, SHARDED).unwrap();
+    /// m.set_block(4, 3, &ROUTER, Rotation::Up, SHARDED).unwrap();
+    /// let s = m.extract_schematic(1, 1, 5, 5).unwrap();
+    /// // the core does not fit
+    /// assert_eq!(s.block_iter().count(), 1);
+    /// assert_eq!(s.get(3, 2).unwrap().unwrap().block.name(), "router");
+    /// ```
+    pub fn extract_schematic(
+        &self,
+        x: usize,
+        y: usize,
+        w: usize,
+        h: usize,
+    ) -> Result<Schematic, PosError> {
+        self.region(x, y, w, h)?;
+        let mut s = Schematic::new(w, h);
+        // the building at this (center) position is copied
+        let copied = |GridPos(cx, cy): GridPos| {
+            let Some(b) = self.get(cx, cy).and_then(Tile::build) else {
+                return false;
+            };
+            let size = b.block.get_size() as usize;
+            let off = (size - 1) / 2;
+            cx >= x + off && cy >= y + off && cx - off + size <= x + w && cy - off + size <= y + h
+        };
+        for cy in y..y + h {
+            for cx in x..x + w {
+                let Some(b) = self.tiles[cx + cy * self.width].build() else {
+                    continue;
+                };
+                if !copied(GridPos(cx, cy)) {
+                    continue;
+                }
+                let rel = |GridPos(lx, ly): GridPos| (lx as i32 - cx as i32, ly as i32 - cy as i32);
+                let mut p = Placement::new(b.block);
+                p.rot = b.rotation;
+                p.state = b.state.clone();
+                if let Some(link) = b.extra.link() {
+                    p.state = Some(State::Point(copied(link).then(|| rel(link))));
+                }
+                if let Some(State::Processor(state)) = &mut p.state {
+                    state.map_links(|lx, ly| {
+                        let link = GridPos(usize::try_from(lx).ok()?, usize::try_from(ly).ok()?);
+                        let (center, _) = self.covering(link.0, link.1)?;
+                        let (dx, dy) = rel(link);
+                        copied(center).then_some((dx as i16, dy as i16))
+                    });
+                }
+                if let (None, Some(power)) = (&p.state, &b.power) {
+                    let links = power
+                        .links
+                        .iter()
+                        .filter(|&&l| copied(l))
+                        .map(|&l| rel(l))
+                        .map(|(dx, dy)| (dx as i16, dy as i16))
+                        .collect();
+                    // only power nodes take their links as config
+                    if let Ok(Some(state @ State::Power(_))) =
+                        b.block.deserialize_state(DynData::Point2Array(links))
+                    {
+                        p.state = Some(state);
+                    }
+                }
+                s.blocks[cx - x][cy - y] = Some(p);
+            }
+        }
+        Ok(s)
+    }
+
+    /// place a schematic with its bottom left at `x`, `y`.
+    ///
+    /// if any block does not fit, or overlaps a building (or another block of the schematic), nothing is placed,
+    /// and every such block is reported.
+    /// links that point at nothing are left out.
+    /// ```
+    /// # use mindus::{Map, Schematic};
+    /// # use mindus::block::{Rotation, ROUTER, CORE_SHARD};
+    /// # use mindus::data::schematic::PlaceError;
+    /// # use mindus::team::SHARDED;
+    /// let mut m = Map::filled(10, 10, mindus::block::content::Type::Stone);
+    /// m.set_block(1, 1, &CORE_SHARD, Rotation::Up, SHARDED).unwrap();
+    /// let mut s = Schematic::new(3, 3);
+    /// s.put(0, 0, &ROUTER).put(2, 2, &ROUTER);
+    /// let errors = m.place_schematic(&s, 1, 1, SHARDED).unwrap_err();
+    /// assert!(matches!(errors[..], [PlaceError::Overlap { x: 1, y: 1 }]));
+    /// m.place_schematic(&s, 3, 3, SHARDED).unwrap();
+    /// assert_eq!(m.covering(5, 5).unwrap().1.block.name(), "router");
+    /// ```
+    pub fn place_schematic(
+        &mut self,
+        s: &Schematic,
+        x: usize,
+        y: usize,
+        team: Team,
+    ) -> Result<(), Vec<PlaceError>> {
+        let mut errors = vec![];
+        // tiles taken by the schematic so far
+        let mut taken = vec![false; s.width * s.height];
+        for (GridPos(px, py), p) in s.block_iter() {
+            if let Err(e) = self.check(x + px, y + py, p.block) {
+                errors.push(e);
+                continue;
+            }
+            let size = p.block.get_size() as usize;
+            let off = (size - 1) / 2;
+            let footprint = (py - off..py - off + size)
+                .flat_map(|ty| (px - off..px - off + size).map(move |tx| (tx, ty)));
+            for (tx, ty) in footprint {
+                if std::mem::replace(&mut taken[tx + ty * s.width], true) {
+                    errors.push(PlaceError::Overlap {
+                        x: x + tx,
+                        y: y + ty,
+                    });
+                    break;
+                }
+            }
+        }
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        let (mut bridges, mut nodes, mut processors) = (vec![], vec![], vec![]);
+        for (GridPos(px, py), p) in s.block_iter() {
+            let (cx, cy) = (x + px, y + py);
+            let at = |(dx, dy): (i32, i32)| {
+                let (lx, ly) = (cx as i32 + dx, cy as i32 + dy);
+                (lx >= 0 && ly >= 0).then_some(GridPos(lx as usize, ly as usize))
+            };
+            let b = self
+                .set_block(cx, cy, p.block, p.rot, team)
+                .expect("placement was checked");
+            match p.get_state() {
+                Some(State::Point(link)) => {
+                    if let Some(link) = link.and_then(at) {
+                        bridges.push((GridPos(cx, cy), link));
+                    }
+                }
+                Some(State::Power(links)) => nodes.push((
+                    GridPos(cx, cy),
+                    links
+                        .iter()
+                        .filter_map(|&(dx, dy)| at((i32::from(dx), i32::from(dy))))
+                        .collect::<Vec<_>>(),
+                )),
+                Some(State::Processor(state)) => {
+                    let mut state = state.clone();
+                    state.map_links(|dx, dy| {
+                        let GridPos(lx, ly) = at((i32::from(dx), i32::from(dy)))?;
+                        Some((i16::try_from(lx).ok()?, i16::try_from(ly).ok()?))
+                    });
+                    processors.push((GridPos(cx, cy), state));
+                }
+                state => b.state = state.cloned(),
+            }
+        }
+        // links are made once everything is placed, as they must point at a building
+        for (GridPos(cx, cy), GridPos(lx, ly)) in bridges {
+            if let Some((link, _)) = self.covering(lx, ly) {
+                let b = self.tiles[cx + cy * self.width].build.as_mut().unwrap();
+                b.extra = Extra::linked(b.block, link);
+            }
+        }
+        for (node, links) in nodes {
+            for GridPos(lx, ly) in links {
+                if let Some((other, _)) = self.covering(lx, ly) {
+                    self.link_power(node, other);
+                    self.link_power(other, node);
+                }
+            }
+        }
+        for (GridPos(cx, cy), mut state) in processors {
+            state.map_links(|lx, ly| {
+                self.covering(lx as usize, ly as usize)
+                    .is_some()
+                    .then_some((lx, ly))
+            });
+            self.tiles[cx + cy * self.width]
+                .build
+                .as_mut()
+                .unwrap()
+                .state = Some(State::Processor(state));
+        }
+        Ok(())
+    }
+
+    /// laser link `from` to `to` (one way)
+    fn link_power(&mut self, from: GridPos, to: GridPos) {
+        let b = self.tiles[from.0 + from.1 * self.width]
+            .build
+            .as_mut()
+            .unwrap();
+        let power = b.power.get_or_insert(Power {
+            links: vec![],
+            status: 0.0,
+        });
+        if !power.links.contains(&to) {
+            power.links.push(to);
+        }
+    }
 }
 
 impl Index<usize> for Map {
@@ -531,12 +1641,225 @@ pub struct MapReader {
     backing: Vec<u8>,
     // dataread references 'backing'
     buff: DataRead<'static>,
+    content: ContentMap,
+    /// the tiles covered by (but not the center of) [`Block::placeholder`]s, to find their size
+    placeholder_parts: Vec<usize>,
+    thin_links: ThinLinks,
+}
+
+/// a bridge or laser node: where, what, the bridge link, the power links and the power status
+pub(crate) type ThinLink = (GridPos, &'static Block, Option<GridPos>, Vec<GridPos>, f32);
+
+/// what [`MapReader::thin_map_within`] keeps to draw bridges and lasers
+#[derive(Debug, Default)]
+pub(crate) struct ThinLinks {
+    pub links: Vec<ThinLink>,
+    /// the size of every building, by the tile it is centered on (in order)
+    pub sizes: Vec<(usize, u8)>,
+}
+
+impl ThinLinks {
+    /// the size of the building centered at `(x, y)` of a `width` wide map
+    pub fn size(&self, GridPos(x, y): GridPos, width: usize) -> Option<u8> {
+        let i = x + y * width;
+        let at = self.sizes.binary_search_by_key(&i, |&(i, _)| i).ok()?;
+        Some(self.sizes[at].1)
+    }
+}
+
+/// maps the content ids a map was saved with (its content header) to ours.
+///
+/// maps from other versions, or with mods, can have different ids.
+/// without a content header (eg [`MapReader::skip`]ped), ids are assumed to be ours.
+#[derive(Clone, Debug, Default)]
+pub struct ContentMap {
+    /// [`Err`] holds the name of blocks we dont know
+    blocks: Option<Vec<Result<BlockEnum, String>>>,
+    items: Option<Vec<Option<Item>>>,
+    fluids: Option<Vec<Option<Fluid>>>,
+    units: Option<Vec<Option<unit::Type>>>,
+    /// the header, as read: the names of each content type
+    header: Vec<(u8, Vec<String>)>,
+    /// the ids of the names of each content type in the header
+    ids: HashMap<u8, HashMap<String, u16>>,
+}
+
+impl ContentMap {
+    /// read the content header (without the section length)
+    pub fn read(buff: &mut DataRead) -> Result<Self, super::ReadError> {
+        let mut header = vec![];
+        for _ in 0..buff.read_u8()? {
+            let ty = buff.read_u8()?;
+            let names = (0..buff.read_u16()?)
+                .map(|_| Ok(buff.read_utf()?.to_string()))
+                .collect::<Result<_, super::ReadError>>()?;
+            header.push((ty, names));
+        }
+        Ok(Self::from_header(header))
+    }
+
+    fn from_header(header: Vec<(u8, Vec<String>)>) -> Self {
+        fn names<T>(names: &[String], f: impl Fn(&str) -> T) -> Option<Vec<T>> {
+            Some(names.iter().map(|n| f(n)).collect())
+        }
+        let mut me = Self::default();
+        for (ty, n) in &header {
+            match ContentType::try_from(*ty) {
+                Ok(ContentType::Block) => {
+                    me.blocks = names(n, |n| BlockEnum::by_name(n).ok_or_else(|| n.to_string()));
+                }
+                Ok(ContentType::Item) => me.items = names(n, Item::by_name),
+                Ok(ContentType::Fluid) => me.fluids = names(n, Fluid::by_name),
+                Ok(ContentType::Unit) => me.units = names(n, unit::Type::by_name),
+                _ => (),
+            }
+            let ids = n.iter().enumerate().map(|(i, n)| (n.clone(), i as u16));
+            me.ids.entry(*ty).or_default().extend(ids);
+        }
+        me.header = header;
+        me
+    }
+
+    /// this header, with what it does not have of ours added at the end, so that anything we have can be written.
+    /// without a header, ours.
+    #[must_use]
+    pub(crate) fn with_ours(&self) -> Self {
+        fn names<T: Content>(all: &[T]) -> Vec<String> {
+            all.iter().map(|c| c.get_name().to_string()).collect()
+        }
+        let ours = [
+            (ContentType::Item, names(Item::ALL)),
+            (ContentType::Block, names(BlockEnum::ALL)),
+            (ContentType::Fluid, names(Fluid::ALL)),
+            (ContentType::Modifier, names(modifier::Type::ALL)),
+            (ContentType::Unit, names(unit::Type::ALL)),
+        ];
+        if self.header.is_empty() {
+            return Self::from_header(ours.map(|(ty, n)| (ty.into(), n)).into());
+        }
+        let mut header = self.header.clone();
+        for (ty, ours) in ours {
+            // types it does not have use our ids
+            let Some((_, names)) = header.iter_mut().find(|(t, _)| *t == u8::from(ty)) else {
+                continue;
+            };
+            let have = &self.ids[&u8::from(ty)];
+            names.extend(ours.into_iter().filter(|n| !have.contains_key(n)));
+        }
+        Self::from_header(header)
+    }
+
+    /// write the header (without the section length)
+    pub(crate) fn write(&self, buff: &mut DataWrite) -> Result<(), WriteError> {
+        buff.write_u8(self.header.len() as u8)?;
+        for (ty, names) in &self.header {
+            buff.write_u8(*ty)?;
+            buff.write_u16(names.len() as u16)?;
+            for name in names {
+                buff.write_utf(name)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// the id of this content in the header (ours, if it does not have the type)
+    fn id(&self, ty: ContentType, name: &str, ours: u16) -> Option<u16> {
+        match self.ids.get(&u8::from(ty)) {
+            Some(ids) => ids.get(name).copied(),
+            None => Some(ours),
+        }
+    }
+
+    /// the id to write this block with: the headers (so [`Block::placeholder`]s keep theirs), else ours.
+    pub(crate) fn block_id(&self, block: &'static Block) -> Result<u16, WriteError> {
+        let name = block.name();
+        match self
+            .ids
+            .get(&u8::from(ContentType::Block))
+            .and_then(|ids| ids.get(name))
+        {
+            Some(&id) => Ok(id),
+            None => BlockEnum::by_name(name)
+                .map(u16::from)
+                .ok_or(WriteError::NoSuchBlock(name)),
+        }
+    }
+
+    /// the id to write this floor (or ore) with. ours, if the header does not have it.
+    pub(crate) fn floor_id(&self, floor: BlockEnum) -> u16 {
+        self.id(ContentType::Block, floor.get_name(), floor.into())
+            .unwrap_or(floor.into())
+    }
+
+    /// the id to write this item with. ours, if the header does not have it (see [`ContentMap::with_ours`]).
+    pub(crate) fn item_id(&self, item: Item) -> u16 {
+        self.id(ContentType::Item, item.get_name(), item.into())
+            .unwrap_or(item.into())
+    }
+
+    /// the id to write this fluid with. ours, if the header does not have it.
+    pub(crate) fn fluid_id(&self, fluid: Fluid) -> u16 {
+        self.id(ContentType::Fluid, fluid.get_name(), fluid.into())
+            .unwrap_or(fluid.into())
+    }
+
+    /// the block (floor, ore, or building) for this id, if we know it
+    #[must_use]
+    pub fn block(&self, id: u16) -> Option<BlockEnum> {
+        match &self.blocks {
+            Some(b) => b.get(id as usize)?.as_ref().ok().copied(),
+            None => BlockEnum::try_from(id).ok(),
+        }
+    }
+
+    /// the building for this id. [`None`] for air and environment blocks.
+    ///
+    /// unknown blocks become a 1x1 [`Block::placeholder`] ([`MapReader::collect_map`] finds their size).
+    pub fn building(&self, id: u16) -> Result<Option<&'static Block>, ReadError> {
+        let block = match &self.blocks {
+            Some(b) => match b.get(id as usize) {
+                Some(Ok(b)) => *b,
+                Some(Err(name)) => {
+                    return Block::placeholder(name, 1)
+                        .map(Some)
+                        .ok_or_else(|| ReadError::NoSuchBlock(name.clone()))
+                }
+                None => return Err(ReadError::NoSuchBlock(id.to_string())),
+            },
+            None => BlockEnum::try_from(id).map_err(|_| ReadError::NoSuchBlock(id.to_string()))?,
+        };
+        Ok(block.to_block())
+    }
+
+    #[must_use]
+    pub fn item(&self, id: u16) -> Option<Item> {
+        match &self.items {
+            Some(i) => i.get(id as usize).copied().flatten(),
+            None => Item::try_from(id).ok(),
+        }
+    }
+
+    #[must_use]
+    pub fn fluid(&self, id: u16) -> Option<Fluid> {
+        match &self.fluids {
+            Some(f) => f.get(id as usize).copied().flatten(),
+            None => Fluid::try_from(id).ok(),
+        }
+    }
+
+    #[must_use]
+    pub fn unit(&self, id: u16) -> Option<unit::Type> {
+        match &self.units {
+            Some(u) => u.get(id as usize).copied().flatten(),
+            None => unit::Type::try_from(id).ok(),
+        }
+    }
 }
 
 #[derive(Debug)]
 pub enum ThinBloc {
     None(u8),
-    Build(Rotation, &'static Block),
+    Build(Rotation, &'static Block, Team),
     Many(&'static Block, u8),
 }
 
@@ -550,7 +1873,7 @@ pub enum ThinMapData {
 #[derive(Debug)]
 pub enum Bloc {
     None(u8),
-    Build(Build, &'static Block),
+    Build(Box<Build>, &'static Block),
     Data(&'static Block, i8),
     Many(&'static Block, u8),
 }
@@ -562,10 +1885,24 @@ pub enum MapData {
     Tile { floor: BlockEnum, ore: BlockEnum },
 }
 
+/// a planned placement (ghost block) in a teams rebuild queue.
+#[derive(Clone, Debug)]
+pub struct Plan {
+    pub position: GridPos,
+    pub rotation: Rotation,
+    pub block: &'static Block,
+    /// the config the block will be placed with (as read: content ids are the maps, see [`Map::content`])
+    pub config: DynData,
+}
+
 #[derive(Debug)]
 pub enum EntityData {
+    /// the plans of one team. yielded before [`EntityData::Length`].
+    Plans(Team, Vec<Plan>),
     Length(u32),
     Data(Unit),
+    /// an entity that is not a unit we know, as read
+    Raw(Box<[u8]>),
 }
 
 macro_rules! tiles {
@@ -574,8 +1911,8 @@ macro_rules! tiles {
         while i < $count {
             let floor_id = $me.buff.read_u16()?;
             let overlay_id = $me.buff.read_u16()?;
-            let floor = BlockEnum::try_from(floor_id).unwrap_or(BlockEnum::Stone);
-            let ore = BlockEnum::try_from(overlay_id).unwrap_or(BlockEnum::Air);
+            let floor = $me.content.block(floor_id).unwrap_or(BlockEnum::Stone);
+            let ore = $me.content.block(overlay_id).unwrap_or(BlockEnum::Air);
             yield $w::Tile { floor, ore };
             let consecutives = $me.buff.read_u8()? as usize;
             for _ in 0..consecutives {
@@ -595,6 +1932,9 @@ impl MapReader {
                 std::mem::transmute::<&'_ [u8], &'static [u8]>(&backing)
             }),
             backing,
+            content: ContentMap::default(),
+            placeholder_parts: vec![],
+            thin_links: ThinLinks::default(),
         })
     }
 
@@ -637,20 +1977,48 @@ impl MapReader {
             .map(|_| tags)
     }
 
+    /// read the content header section, which the map, and entity readers use from then on.
+    pub fn content(&mut self) -> Result<&ContentMap, ReadError> {
+        let len = self.buff.read_u32()? as usize;
+        self.content = ContentMap::read(&mut DataRead::new(self.buff.eat(len)?))?;
+        Ok(&self.content)
+    }
+
     pub fn skip(&mut self) -> Result<(), ReadError> {
         let len = self.buff.read_u32()? as usize;
         self.buff.skip(len)?;
         Ok(())
     }
 
+    /// get a section without reading it
+    pub fn raw(&mut self) -> Result<&[u8], ReadError> {
+        let len = self.buff.read_u32()? as usize;
+        Ok(self.buff.eat(len)?)
+    }
+
     pub fn thin_map(
         &mut self,
     ) -> Result<
         impl Coroutine<(), Return = Result<(), ReadError>, Yield = ThinMapData> + '_,
         ReadError,
+    > {
+        self.thin_map_within(|w, h| (0, 0, w, h))
+    }
+
+    /// [`MapReader::thin_map`], skipping the buildings centered outside of `(x, y, width, height)` (as [`ThinBloc::None`]),
+    /// without reading them. `within` gets the size of the map.
+    ///
+    /// the links of bridges and power nodes (anywhere) are read, and kept for drawing.
+    pub fn thin_map_within<'s>(
+        &'s mut self,
+        within: impl FnOnce(usize, usize) -> (usize, usize, usize, usize) + 's,
+    ) -> Result<
+        impl Coroutine<(), Return = Result<(), ReadError>, Yield = ThinMapData> + 's,
+        ReadError,
     > {
         let len = self.buff.read_u32()? as usize;
         let rb4 = self.buff.read;
+        self.thin_links = ThinLinks::default();
         let map = move || {
             let w = self.buff.read_u16()?;
             let h = self.buff.read_u16()?;
@@ -661,6 +2029,7 @@ impl MapReader {
             let w = w as usize;
             let h = h as usize;
             let count = w * h;
+            let (vx, vy, vw, vh) = within(w, h);
             tiles!(count, self, ThinMapData);
 
             let mut i = 0;
@@ -674,9 +2043,7 @@ impl MapReader {
                 } else {
                     false
                 };
-                let block = BlockEnum::try_from(block_id)
-                    .map_err(|_| ReadError::NoSuchBlock(block_id.to_string()))?;
-                let block = block.to_block();
+                let block = self.content.building(block_id)?;
                 let Some(block) = block else {
                     let consecutives = self.buff.read_u8()?;
                     yield ThinMapData::Bloc(ThinBloc::None(consecutives));
@@ -685,23 +2052,47 @@ impl MapReader {
                     continue;
                 };
                 yield if entity {
-                    if central {
-                        let len = self.buff.read_u16()? as usize;
-                        let rb4 = self.buff.read;
-
-                        #[cfg(debug_assertions)]
-                        println!("reading {block:?} ");
-                        let _ = self.buff.read_i8()?;
-                        let _ = self.buff.read_f32()?;
-                        let rot = self.buff.read_i8()?;
-                        let rot = Rotation::try_from((rot & 127) as u8).unwrap_or(Rotation::Up);
-                        let read = self.buff.read - rb4;
-                        let n = len - read;
-                        self.buff.skip(n)?;
-
-                        ThinMapData::Bloc(ThinBloc::Build(rot, block))
-                    } else {
+                    if !central {
                         ThinMapData::Bloc(ThinBloc::None(0))
+                    } else {
+                        let len = self.buff.read_u16()? as usize;
+                        let chunk = self.buff.eat(len)?;
+                        let pos = GridPos(i % w, i / w);
+                        self.thin_links.sizes.push((i, block.get_size()));
+                        // links can reach into the viewport from anywhere, so these are always read
+                        if is_bridge(block.name()) || is_laser_node(block.name()) {
+                            let mut b = Build::new(block);
+                            let mut chunk = DataRead::new(chunk);
+                            // (a chunk that cant be read has no links to draw)
+                            if chunk.read_i8().is_ok()
+                                && b.read_mapped(&mut chunk, &self.content).is_ok()
+                            {
+                                let (links, status) =
+                                    b.power.map_or((vec![], 0.0), |p| (p.links, p.status));
+                                self.thin_links.links.push((
+                                    pos,
+                                    block,
+                                    b.extra.link(),
+                                    links,
+                                    status,
+                                ));
+                            }
+                        }
+                        if (pos.0).checked_sub(vx).is_some_and(|x| x < vw)
+                            && (pos.1).checked_sub(vy).is_some_and(|y| y < vh)
+                        {
+                            #[cfg(debug_assertions)]
+                            println!("reading {block:?} ");
+                            let mut chunk = DataRead::new(chunk);
+                            let _ = chunk.read_i8()?;
+                            let _ = chunk.read_f32()?;
+                            let rot = chunk.read_i8()?;
+                            let rot = Rotation::try_from((rot & 127) as u8).unwrap_or(Rotation::Up);
+                            let team = Team::of(chunk.read_u8()?);
+                            ThinMapData::Bloc(ThinBloc::Build(rot, block, team))
+                        } else {
+                            ThinMapData::Bloc(ThinBloc::None(0))
+                        }
                     }
                 } else if data {
                     _ = self.buff.read_i8()?;
@@ -722,6 +2113,11 @@ impl MapReader {
         Ok(map)
     }
 
+    /// the links [`MapReader::thin_map_within`] read (once it is done)
+    pub(crate) fn take_thin_links(&mut self) -> ThinLinks {
+        std::mem::take(&mut self.thin_links)
+    }
+
     pub fn collect_map(&mut self, tags: HashMap<String, String>) -> Result<Map, ReadError> {
         let mut co = self.map()?;
         let (w, h) = match Pin::new(&mut co).resume(()) {
@@ -743,15 +2139,19 @@ impl MapReader {
                 Yielded(MapData::Bloc(Bloc::None(n))) => i += n as usize,
                 Yielded(MapData::Bloc(Bloc::Build(x, y))) => {
                     m[i].set_block(y);
-                    m[i].build = Some(x);
+                    m[i].build = Some(*x);
                 }
+                // blocks without buildings (boulders, walls) belong to no team
                 Yielded(MapData::Bloc(Bloc::Data(x, y))) => {
                     m[i].set_block(x);
-                    m[i].build.as_mut().unwrap().data = y;
+                    let build = m[i].build.as_mut().unwrap();
+                    build.data = y;
+                    build.team = team::DERELICT;
                 }
                 Yielded(MapData::Bloc(Bloc::Many(bloc, n))) => {
                     for i in i..=i + n as usize {
                         m[i].set_block(bloc);
+                        m[i].build.as_mut().unwrap().team = team::DERELICT;
                     }
                     i += n as usize;
                 }
@@ -764,6 +2164,31 @@ impl MapReader {
             Complete(Ok(())) => (),
             _ => unreachable!(),
         };
+        drop(co);
+        // placeholders are read as 1x1: grow them over the tiles they cover
+        let parts = std::mem::take(&mut self.placeholder_parts)
+            .into_iter()
+            .collect::<HashSet<_>>();
+        if !parts.is_empty() {
+            for i in 0..w * h {
+                let Some(b) = m[i].build.as_mut().filter(|b| b.block.is_placeholder()) else {
+                    continue;
+                };
+                let (x, y) = (i % w, i / w);
+                let covered = |size: usize| {
+                    let off = (size - 1) / 2;
+                    (x >= off && y >= off && x + size - off <= w && y + size - off <= h)
+                        && (y - off..y + size - off).all(|py| {
+                            (x - off..x + size - off)
+                                .all(|px| px + py * w == i || parts.contains(&(px + py * w)))
+                        })
+                };
+                let size = (2..=16).take_while(|&s| covered(s)).last().unwrap_or(1);
+                if let Some(block) = Block::placeholder(b.block.name(), size as u8) {
+                    b.block = block;
+                }
+            }
+        }
         Ok(m)
     }
 
@@ -796,9 +2221,7 @@ impl MapReader {
                 } else {
                     false
                 };
-                let block = BlockEnum::try_from(block_id)
-                    .map_err(|_| ReadError::NoSuchBlock(block_id.to_string()))?;
-                let block = block.to_block();
+                let block = self.content.building(block_id)?;
                 let Some(block) = block else {
                     let consecutives = self.buff.read_u8()?;
                     yield MapData::Bloc(Bloc::None(consecutives));
@@ -809,29 +2232,19 @@ impl MapReader {
                 yield if entity {
                     if central {
                         let len = self.buff.read_u16()? as usize;
-                        let rb4 = self.buff.read;
+                        let mut chunk = DataRead::new(self.buff.eat(len)?);
 
                         #[cfg(debug_assertions)]
                         println!("reading {block:?} ");
-                        let _ = self.buff.read_i8()?;
                         let mut b = Build::new(block);
-                        b.read(&mut self.buff)?;
-                        // implementation not complete, skip remaining bytes (TODO finish impl)
-                        let read = self.buff.read - rb4;
-
-                        // skip this chunk
-                        assert!(len >= read, "overread; supposed to read {len}; read {read}");
-                        let n = len - read;
-                        if n != 0 {
-                            #[cfg(debug_assertions)]
-                            println!(
-                                "({block:?}) supposed to read {len}; read {read} - skipping excess"
-                            );
-                            self.buff.skip(n)?;
-                        };
+                        b.revision = chunk.read_i8()?;
+                        b.read_mapped(&mut chunk, &self.content)?;
 
-                        MapData::Bloc(Bloc::Build(b, block))
+                        MapData::Bloc(Bloc::Build(Box::new(b), block))
                     } else {
+                        if block.is_placeholder() {
+                            self.placeholder_parts.push(i);
+                        }
                         MapData::Bloc(Bloc::None(0))
                     }
                 } else if data {
@@ -867,24 +2280,53 @@ impl MapReader {
             }
             // read team block plans (ghosts) (SaveVersion.java#389)
             for _ in 0..self.buff.read_u32()? {
-                self.buff.skip(4)?;
-                for _ in 0..self.buff.read_u32()? {
-                    self.buff.skip(8usize)?;
-                    let _ = DynData::deserialize(&mut self.buff)?;
+                let team = Team::of(self.buff.read_u32()? as u8);
+                let n = self.buff.read_u32()? as usize;
+                let mut plans = Vec::with_capacity(n.min(1000));
+                let mut taken = HashSet::with_capacity(n.min(1000));
+                for _ in 0..n {
+                    let x = self.buff.read_u16()?;
+                    let y = self.buff.read_u16()?;
+                    let rot = self.buff.read_u16()?;
+                    let id = self.buff.read_u16()?;
+                    let config = DynData::deserialize(&mut self.buff)?;
+                    let Some(block) = self.content.building(id)? else {
+                        continue;
+                    };
+                    let position = GridPos(x as usize, y as usize);
+                    // cannot have two in the same position
+                    if !taken.insert(position) {
+                        continue;
+                    }
+                    plans.push(Plan {
+                        position,
+                        rotation: Rotation::from((rot & 3) as u8),
+                        block,
+                        config,
+                    });
                 }
+                yield EntityData::Plans(team, plans);
             }
             // read world entities (#412). eg units
             let n = self.buff.read_u32()?;
             yield EntityData::Length(n);
             for _ in 0..n {
                 let len = self.buff.read_u16()? as usize;
-                let id = self.buff.read_u8()? as usize;
+                let raw = self.buff.eat(len)?;
+                let mut chunk = DataRead::new(raw);
+                let id = chunk.read_u8()? as usize;
                 let Some(&Some(u)) = entity_mapping::ID.get(id) else {
-                    self.buff.skip(len - 1)?;
+                    yield EntityData::Raw(raw.into());
                     continue;
                 };
-                self.buff.skip(4)?;
-                yield EntityData::Data(u.read(&mut self.buff)?);
+                chunk.skip(4)?;
+                // units of types we dont know are kept raw
+                let Some(mut unit) = u.read_mapped(&mut chunk, &self.content)? else {
+                    yield EntityData::Raw(raw.into());
+                    continue;
+                };
+                unit.raw = raw.into();
+                yield EntityData::Data(unit);
             }
             let read = self.buff.read - rb4;
             debug_assert!(len >= read, "overread; supposed to read {len}; read {read}");
@@ -894,16 +2336,40 @@ impl MapReader {
     }
 
     pub fn collect_entities(&mut self) -> Result<Vec<Unit>, ReadError> {
+        self.collect_entities_and_plans().map(|(_, units)| units)
+    }
+
+    /// read the entities section, keeping the team block plans.
+    #[allow(clippy::type_complexity)]
+    pub fn collect_entities_and_plans(
+        &mut self,
+    ) -> Result<(Vec<(Team, Vec<Plan>)>, Vec<Unit>), ReadError> {
+        self.collect_all_entities()
+            .map(|(plans, units, _)| (plans, units))
+    }
+
+    /// read the entities section, keeping the plans, and the entities that are not units we know
+    /// (with how many units come before them).
+    #[allow(clippy::type_complexity)]
+    fn collect_all_entities(
+        &mut self,
+    ) -> Result<(Vec<(Team, Vec<Plan>)>, Vec<Unit>, Vec<(usize, Box<[u8]>)>), ReadError> {
         let mut co = self.entities()?;
-        let n = match Pin::new(&mut co).resume(()) {
-            Yielded(EntityData::Length(x)) => x,
-            Complete(Err(e)) => return Err(e),
-            _ => unreachable!(),
+        let mut plans = vec![];
+        let n = loop {
+            match Pin::new(&mut co).resume(()) {
+                Yielded(EntityData::Plans(team, p)) => plans.push((team, p)),
+                Yielded(EntityData::Length(x)) => break x,
+                Complete(Err(e)) => return Err(e),
+                _ => unreachable!(),
+            }
         };
-        let mut o = Vec::with_capacity(n as usize);
+        let mut o = vec![];
+        let mut unknown = vec![];
         for _ in 0..n {
             match Pin::new(&mut co).resume(()) {
                 Yielded(EntityData::Data(x)) => o.push(x),
+                Yielded(EntityData::Raw(x)) => unknown.push((o.len(), x)),
                 Complete(Err(e)) => return Err(e),
                 _ => unreachable!(),
             }
@@ -912,14 +2378,188 @@ impl MapReader {
             Complete(Ok(())) => (),
             _ => unreachable!(),
         };
-        Ok(o)
+        Ok((plans, o, unknown))
+    }
+}
+
+/// error occurring when writing a map fails
+#[derive(Debug, Error)]
+pub enum WriteError {
+    #[error("failed to write data to buffer")]
+    Write(#[from] super::WriteError),
+    #[error(transparent)]
+    Compress(#[from] super::CompressError),
+    #[error("invalid map dimensions ({0} / {1})")]
+    Dimensions(usize, usize),
+    #[error("tag list too long ({0})")]
+    TagCount(usize),
+    #[error("unknown block {0:?}")]
+    NoSuchBlock(&'static str),
+    #[error("building chunk too long ({0})")]
+    ChunkLength(usize),
+    #[error(transparent)]
+    State(#[from] crate::block::SerializeError),
+    #[error(transparent)]
+    Dynamic(#[from] super::dynamic::WriteError),
+}
+
+/// write a section, prefixed by its length
+fn section(
+    to: &mut DataWrite,
+    f: impl FnOnce(&mut DataWrite) -> Result<(), WriteError>,
+) -> Result<(), WriteError> {
+    let mut buff = DataWrite::default();
+    f(&mut buff)?;
+    let buff = buff.get_written();
+    to.write_u32(buff.len() as u32)?;
+    to.write_bytes(buff)?;
+    Ok(())
+}
+
+/// environment blocks (static walls, boulders, ...) come before the graphite press, and have no building.
+///
+/// we cant tell for [`Block::placeholder`]s, so they are written as buildings, with their chunk as it was read
+/// (the game skips the chunk of blocks without buildings).
+fn has_building(block: &'static Block) -> bool {
+    BlockEnum::by_name(block.name()).map_or(true, |b| b >= BlockEnum::GraphitePress)
+}
+
+impl Map {
+    /// the names of every content id, so that the game can remap them.
+    /// (weathers, sectors and planets are left out, as they are not in the map section)
+    fn write_map(&self, buff: &mut DataWrite, content: &ContentMap) -> Result<(), WriteError> {
+        let (Ok(w), Ok(h)) = (u16::try_from(self.width), u16::try_from(self.height)) else {
+            return Err(WriteError::Dimensions(self.width, self.height));
+        };
+        if self.tiles.len() != self.width * self.height {
+            return Err(WriteError::Dimensions(self.width, self.height));
+        }
+        buff.write_u16(w)?;
+        buff.write_u16(h)?;
+        let mut i = 0;
+        while i < self.tiles.len() {
+            let Tile { floor, ore, .. } = self[i];
+            let consecutives = self.tiles[i + 1..]
+                .iter()
+                .take(255)
+                .take_while(|t| t.floor == floor && t.ore == ore)
+                .count();
+            buff.write_u16(content.floor_id(floor))?;
+            buff.write_u16(content.floor_id(ore))?;
+            buff.write_u8(consecutives as u8)?;
+            i += consecutives + 1;
+        }
+
+        // the tiles under a building, other than its center
+        let mut parts = vec![None; self.tiles.len()];
+        for (i, b) in self
+            .tiles
+            .iter()
+            .enumerate()
+            .filter_map(|(i, t)| Some((i, t.build()?)))
+        {
+            let size = b.block.get_size() as usize;
+            let off = (size - 1) / 2;
+            let (x, y) = (i % self.width, i / self.width);
+            for py in y.saturating_sub(off)..(y + size - off).min(self.height) {
+                for px in x.saturating_sub(off)..(x + size - off).min(self.width) {
+                    let j = px + py * self.width;
+                    if j != i && self[j].build.is_none() {
+                        parts[j] = Some(b.block);
+                    }
+                }
+            }
+        }
+        // the block of tiles that are written in runs (no building, no data)
+        let plain = |j: usize| match (&self[j].build, parts[j]) {
+            (None, None) => Some(None),
+            (Some(b), None) if !has_building(b.block) && b.data == 0 => Some(Some(b.block)),
+            _ => None,
+        };
+        let mut i = 0;
+        while i < self.tiles.len() {
+            match (&self[i].build, parts[i]) {
+                // (placeholders with data are environment blocks)
+                (Some(b), _)
+                    if has_building(b.block) && !(b.block.is_placeholder() && b.data != 0) =>
+                {
+                    buff.write_u16(content.block_id(b.block)?)?;
+                    buff.write_u8(1)?;
+                    buff.write_bool(true)?;
+                    let mut chunk = DataWrite::default();
+                    chunk.write_i8(b.revision)?;
+                    b.write_mapped(&mut chunk, content)?;
+                    let chunk = chunk.get_written();
+                    let len = u16::try_from(chunk.len())
+                        .map_err(|_| WriteError::ChunkLength(chunk.len()))?;
+                    buff.write_u16(len)?;
+                    buff.write_bytes(chunk)?;
+                }
+                (None, Some(block)) => {
+                    buff.write_u16(content.block_id(block)?)?;
+                    buff.write_u8(1)?;
+                    buff.write_bool(false)?;
+                }
+                (Some(b), _) if b.data != 0 => {
+                    buff.write_u16(content.block_id(b.block)?)?;
+                    buff.write_u8(2)?;
+                    buff.write_i8(b.data)?;
+                }
+                (b, _) => {
+                    let block = b.as_ref().map(|b| b.block);
+                    let consecutives = (i + 1..self.tiles.len())
+                        .take(255)
+                        .take_while(|&j| plain(j) == Some(block))
+                        .count();
+                    buff.write_u16(block.map_or(Ok(0), |b| content.block_id(b))?)?;
+                    buff.write_u8(0)?;
+                    buff.write_u8(consecutives as u8)?;
+                    i += consecutives;
+                }
+            }
+            i += 1;
+        }
+        Ok(())
+    }
+
+    fn write_entities(&self, buff: &mut DataWrite, content: &ContentMap) -> Result<(), WriteError> {
+        // entity mapping (none custom)
+        buff.write_u16(0)?;
+        // team block plans
+        buff.write_u32(self.plans.len() as u32)?;
+        for (team, plans) in &self.plans {
+            buff.write_u32(u8::from(*team) as u32)?;
+            buff.write_u32(plans.len() as u32)?;
+            for plan in plans {
+                buff.write_u16(plan.position.0 as u16)?;
+                buff.write_u16(plan.position.1 as u16)?;
+                buff.write_u16(u8::from(plan.rotation) as u16)?;
+                buff.write_u16(content.block_id(plan.block)?)?;
+                plan.config.serialize(buff)?;
+            }
+        }
+        buff.write_u32((self.entities.len() + self.unknown_entities.len()) as u32)?;
+        let mut unknown = self.unknown_entities.iter().peekable();
+        for i in 0..=self.entities.len() {
+            while let Some((_, raw)) =
+                unknown.next_if(|&&(before, _)| before <= i || i == self.entities.len())
+            {
+                buff.write_u16(raw.len() as u16)?;
+                buff.write_bytes(raw)?;
+            }
+            if let Some(unit) = self.entities.get(i) {
+                buff.write_u16(unit.raw.len() as u16)?;
+                buff.write_bytes(&unit.raw)?;
+            }
+        }
+        Ok(())
     }
 }
 
 /// serde map
 impl Serializable for Map {
     type ReadError = ReadError;
-    type WriteError = ();
+    type WriteError = WriteError;
     /// deserialize a map
     ///
     /// note: does not deserialize all data
@@ -928,18 +2568,419 @@ impl Serializable for Map {
         buff.header()?;
         buff.version()?;
         let tags = buff.tags_alloc()?;
-        buff.skip()?;
+        let content = buff.content()?.clone();
         let mut m = buff.collect_map(tags)?;
-        m.entities = buff.collect_entities()?;
+        m.content = content;
+        (m.plans, m.entities, m.unknown_entities) = buff.collect_all_entities()?;
 
-        // skip custom chunks
-        buff.skip()?;
+        m.custom = CustomChunk::read_all(&mut DataRead::new(buff.raw()?))?;
         Ok(m)
     }
 
-    /// serialize a map (todo)
-    /// panics: always
-    fn serialize(&self, _: &mut super::DataWrite<'_>) -> Result<(), Self::WriteError> {
-        unimplemented!()
+    /// serialize a map
+    ///
+    /// note: the building data that is not decoded, and the units, are written back as they were read.
+    fn serialize(&self, buff: &mut DataWrite<'_>) -> Result<(), Self::WriteError> {
+        let mut raw = DataWrite::default();
+        raw.write_bytes(&MAP_HEADER)?;
+        raw.write_u32(7)?;
+        section(&mut raw, |buff| {
+            if self.tags.len() > u16::MAX as usize {
+                return Err(WriteError::TagCount(self.tags.len()));
+            }
+            buff.write_u16(self.tags.len() as u16)?;
+            // sorted, so the output does not depend on the hash order
+            let mut tags = self.tags.iter().collect::<Vec<_>>();
+            tags.sort_unstable();
+            for (k, v) in tags {
+                buff.write_utf(k)?;
+                buff.write_utf(v)?;
+            }
+            Ok(())
+        })?;
+        // the header it was read with (things kept as read use its ids), and what it needs of ours
+        let content = self.content.with_ours();
+        section(&mut raw, |buff| content.write(buff))?;
+        section(&mut raw, |buff| self.write_map(buff, &content))?;
+        section(&mut raw, |buff| self.write_entities(buff, &content))?;
+        section(&mut raw, |buff| {
+            buff.write_u32(self.custom.len() as u32)?;
+            for chunk in &self.custom {
+                chunk.write(buff)?;
+            }
+            Ok(())
+        })?;
+        raw.inflate(buff)?;
+        Ok(())
+    }
+}
+
+#[test]
+fn editing() {
+    use crate::block::{CORE_SHARD, ROUTER};
+    let mut m = Map::filled(8, 6, BlockEnum::Stone);
+    m.set_block(4, 3, &CORE_SHARD, Rotation::Up, team::SHARDED)
+        .unwrap();
+    m.set_block(0, 0, &ROUTER, Rotation::Up, team::CRUX)
+        .unwrap();
+    m.fill_ore(0, 0, 2, 2, BlockEnum::OreCopper).unwrap();
+    assert!(m.fill_floor(7, 5, 2, 1, BlockEnum::Grass).is_err());
+    assert!(matches!(
+        m.set_block(5, 3, &ROUTER, Rotation::Up, team::SHARDED),
+        Err(PlaceError::Overlap { x: 5, y: 3 })
+    ));
+    m.set_block(6, 3, &ROUTER, Rotation::Up, team::SHARDED)
+        .unwrap();
+    assert_eq!(m.remove(5, 4).unwrap().unwrap().block.name(), "core-shard");
+    assert!(m.covering(4, 3).is_none());
+    m.set_block(4, 3, &CORE_SHARD, Rotation::Up, team::SHARDED)
+        .unwrap();
+
+    // the core no longer fits
+    let mut cropped = m.clone();
+    cropped.crop(0, 0, 4, 4).unwrap();
+    assert_eq!(
+        (cropped.width, cropped.height, cropped.tiles.len()),
+        (4, 4, 16)
+    );
+    assert!(cropped.covering(3, 3).is_none());
+    assert_eq!(cropped.get(0, 0).unwrap().ore, BlockEnum::OreCopper);
+
+    // moved
+    m.crop(2, 1, 5, 4).unwrap();
+    assert_eq!(m.covering(1, 1).unwrap().0, GridPos(2, 2));
+    assert!(m.get(0, 0).unwrap().build().is_none());
+
+    m.resize(10, 10, BlockEnum::Grass);
+    assert_eq!(m.get(9, 9).unwrap().floor, BlockEnum::Grass);
+    assert_eq!(m.covering(3, 3).unwrap().0, GridPos(2, 2));
+    let mut buff = DataWrite::default();
+    m.serialize(&mut buff).unwrap();
+    let read = Map::deserialize(&mut DataRead::new(buff.get_written())).unwrap();
+    assert_eq!(format!("{:?}", m.tiles), format!("{:?}", read.tiles));
+}
+
+#[test]
+fn schematics() {
+    use crate::block::{BRIDGE_CONVEYOR, CORE_SHARD, POWER_NODE, SORTER};
+    let mut m = Map::filled(12, 12, BlockEnum::Stone);
+    m.set_block(1, 1, &CORE_SHARD, Rotation::Up, team::SHARDED)
+        .unwrap();
+    m.set_block(4, 1, &BRIDGE_CONVEYOR, Rotation::Right, team::SHARDED)
+        .unwrap()
+        .extra = Extra::linked(&BRIDGE_CONVEYOR, GridPos(4, 3));
+    m.set_block(4, 3, &BRIDGE_CONVEYOR, Rotation::Up, team::SHARDED)
+        .unwrap();
+    m.set_block(5, 1, &SORTER, Rotation::Up, team::SHARDED)
+        .unwrap()
+        .state = Some(State::Item(Some(Item::Copper)));
+    m.set_block(3, 3, &POWER_NODE, Rotation::Up, team::SHARDED)
+        .unwrap();
+    m.link_power(GridPos(3, 3), GridPos(1, 1));
+    m.link_power(GridPos(1, 1), GridPos(3, 3));
+    m.link_power(GridPos(3, 3), GridPos(5, 1));
+    m.link_power(GridPos(5, 1), GridPos(3, 3));
+
+    let s = m.extract_schematic(3, 1, 3, 3).unwrap();
+    assert_eq!(s.block_iter().count(), 4);
+    let state = |x, y| format!("{:?}", s.get(x, y).unwrap().unwrap().get_state());
+    assert_eq!(state(1, 0), "Some(Point(Some((0, 2))))");
+    assert_eq!(state(1, 2), "None");
+    assert_eq!(state(2, 0), "Some(Item(Some(Copper)))");
+    // the core is not in the schematic
+    assert_eq!(state(0, 2), "Some(Power([(2, -2)]))");
+    assert_eq!(s.get(1, 0).unwrap().unwrap().rot, Rotation::Right);
+
+    let errors = m.place_schematic(&s, 2, 0, team::CRUX).unwrap_err();
+    assert_eq!(errors.len(), 1);
+    assert!(matches!(errors[0], PlaceError::Overlap { x: 2, y: 2 }));
+    m.place_schematic(&s, 7, 7, team::CRUX).unwrap();
+    let bridge = m.get(8, 7).unwrap().build().unwrap();
+    assert_eq!(bridge.team, team::CRUX);
+    assert_eq!(bridge.extra.link(), Some(GridPos(8, 9)));
+    assert_eq!(
+        m.get(7, 9)
+            .unwrap()
+            .build()
+            .unwrap()
+            .power
+            .as_ref()
+            .unwrap()
+            .links,
+        [GridPos(9, 7)]
+    );
+    assert_eq!(
+        m.get(9, 7)
+            .unwrap()
+            .build()
+            .unwrap()
+            .power
+            .as_ref()
+            .unwrap()
+            .links,
+        [GridPos(7, 9)]
+    );
+    assert_eq!(m.extract_schematic(7, 7, 3, 3).unwrap(), s);
+    assert!(m.place_schematic(&s, 10, 10, team::CRUX).is_err());
+}
+
+#[test]
+fn linked_processor() {
+    use crate::block::logic::ProcessorState;
+    use crate::block::{MEMORY_CELL, MICRO_PROCESSOR, ROUTER};
+    let mut m = Map::filled(10, 10, BlockEnum::Stone);
+    m.set_block(2, 2, &MEMORY_CELL, Rotation::Up, team::SHARDED)
+        .unwrap();
+    m.set_block(0, 0, &ROUTER, Rotation::Up, team::SHARDED)
+        .unwrap();
+    let mut state = ProcessorState::default();
+    state.create_link("cell1".into(), 2, 2).unwrap();
+    state.create_link("router1".into(), 0, 0).unwrap();
+    m.set_block(3, 2, &MICRO_PROCESSOR, Rotation::Up, team::SHARDED)
+        .unwrap()
+        .state = Some(State::Processor(state));
+
+    // the router is not in the schematic
+    let s = m.extract_schematic(2, 2, 2, 1).unwrap();
+    let links = |state: Option<&State>| match state {
+        Some(State::Processor(p)) => p
+            .get_links()
+            .iter()
+            .map(|l| (l.get_name().to_string(), l.get_pos()))
+            .collect::<Vec<_>>(),
+        _ => panic!("not a processor"),
+    };
+    let p = s.get(1, 0).unwrap().unwrap();
+    assert_eq!(links(p.get_state()), [("cell1".to_string(), (-1, 0))]);
+
+    m.place_schematic(&s, 5, 7, team::SHARDED).unwrap();
+    assert_eq!(
+        links(m.get(6, 7).unwrap().build().unwrap().state.as_ref()),
+        [("cell1".to_string(), (5, 7))]
+    );
+    assert_eq!(m.extract_schematic(5, 7, 2, 1).unwrap(), s);
+}
+
+#[test]
+fn round_trip() {
+    use crate::block::{CORE_SHARD, ROUTER, STONE_WALL};
+    let mut m = Map::new(5, 4, HashMap::from([("name".into(), "test".into())]));
+    for i in 0..20 {
+        let floor = if i < 7 {
+            BlockEnum::Grass
+        } else {
+            BlockEnum::Stone
+        };
+        let ore = if i == 3 {
+            BlockEnum::OreCopper
+        } else {
+            BlockEnum::Air
+        };
+        m.push(Tile::new(floor, ore));
+    }
+    m[6].set_block(&CORE_SHARD);
+    m[3].set_block(&ROUTER);
+    let router = m[3].build.as_mut().unwrap();
+    router.items.set(Item::Copper, 5);
+    router.liquids.set(Fluid::Water, 23996);
+    router.team = team::CRUX;
+    router.rotation = Rotation::Left;
+    router.power = Some(Power {
+        links: vec![GridPos(1, 1)],
+        status: 0.5,
+    });
+    m[18].set_block(&STONE_WALL);
+    m[19].set_block(&STONE_WALL);
+    let mut rules = m.rules().unwrap();
+    rules.waves = true;
+    rules.teams.push((team::CRUX, Default::default()));
+    rules.teams[0].1.infinite_resources = true;
+    m.set_rules(&rules);
+    m.custom = vec![
+        CustomChunk {
+            name: "static-fog-data".into(),
+            data: ChunkData::StaticFog(StaticFog {
+                width: 5,
+                height: 4,
+                teams: vec![(team::SHARDED, (0..20).map(|i| i % 7 < 3).collect())],
+            }),
+        },
+        CustomChunk {
+            name: "markers".into(),
+            data: ChunkData::Raw(Box::new([1, 2, 3])),
+        },
+    ];
+    m.plans.push((
+        team::SHARDED,
+        vec![Plan {
+            position: GridPos(2, 2),
+            rotation: Rotation::Down,
+            block: &ROUTER,
+            config: DynData::Int(4),
+        }],
+    ));
+    // entity class 1 is not a unit, and 200 does not exist
+    m.unknown_entities = vec![(0, Box::new([1, 7, 7])), (0, Box::new([200]))];
+
+    let mut buff = DataWrite::default();
+    m.serialize(&mut buff).unwrap();
+    let read = Map::deserialize(&mut DataRead::new(buff.get_written())).unwrap();
+    assert_eq!(format!("{:?}", m.tiles), format!("{:?}", read.tiles));
+    assert_eq!(read.tags, m.tags);
+    let router = read[3].build().unwrap();
+    assert_eq!(router.items.get(Item::Copper), 5);
+    assert_eq!(router.liquids.get(Fluid::Water), 23996);
+    assert_eq!(router.team, team::CRUX);
+    assert_eq!(router.rotation, Rotation::Left);
+    assert_eq!(router.power.as_ref().unwrap().links, [GridPos(1, 1)]);
+    // the core is only at its center
+    assert!(read[0].build().is_none() && read[6].build().is_some());
+    let [(team, plans)] = &read.plans[..] else {
+        panic!("expected one team of plans")
+    };
+    assert_eq!(*team, team::SHARDED);
+    assert_eq!(plans[0].position, GridPos(2, 2));
+    assert_eq!(plans[0].rotation, Rotation::Down);
+    assert_eq!(plans[0].block.name(), "router");
+    assert_eq!(plans[0].config, DynData::Int(4));
+    assert_eq!(read.rules().unwrap(), rules);
+    let Some(ChunkData::StaticFog(fog)) = read.custom_chunk("static-fog-data") else {
+        panic!("expected static fog")
+    };
+    assert_eq!(
+        fog.teams[0].1[..8],
+        [true, true, true, false, false, false, false, true]
+    );
+    assert!(matches!(read.custom_chunk("markers"), Some(ChunkData::Raw(x)) if **x == [1, 2, 3]));
+    assert_eq!(read.unknown_entities, m.unknown_entities);
+    let mut img = Image::<_, 3>::alloc(5 * 8, 4 * 8);
+    draw_plan(img.as_mut(), &plans[0], (5, 4), Scale::Quarter);
+    // the ghost is drawn
+    assert!(img.chunked().any(|&[r, g, b]| (r, g, b) != (0, 0, 0)));
+
+    let mut again = DataWrite::default();
+    read.serialize(&mut again).unwrap();
+    assert_eq!(buff.get_written(), again.get_written());
+}
+
+#[test]
+fn permuted_content() {
+    use crate::block::{CONVEYOR, DUO, ROUTER};
+    let mut m = Map::filled(3, 1, BlockEnum::Stone);
+    m[0].set_block(&ROUTER);
+    let router = m[0].build.as_mut().unwrap();
+    router.items.set(Item::Copper, 3);
+    router.liquids.set(Fluid::Slag, 500);
+    m[1].set_block(&CONVEYOR);
+    m[1].build.as_mut().unwrap().extra = Extra::Conveyor(vec![ConveyorItem {
+        item: Item::Lead,
+        x: 0.0,
+        y: 0.0,
+    }]);
+    m[2].set_block(&DUO);
+    m[2].build.as_mut().unwrap().extra = Extra::ItemTurret {
+        reload: 0.0,
+        rotation: 90.0,
+        ammo: vec![(Item::Graphite, 10)],
+    };
+    // a map saved with every id backwards
+    let header = ContentMap::default().with_ours().header;
+    m.content = ContentMap::from_header(
+        header
+            .into_iter()
+            .map(|(ty, names)| (ty, names.into_iter().rev().collect()))
+            .collect(),
+    );
+    // the builds are written with its ids, not ours
+    for b in m.tiles.iter().filter_map(Tile::build) {
+        let mut w = DataWrite::default();
+        b.write_mapped(&mut w, &m.content).unwrap();
+        let mut ours = Build::new(b.block);
+        ours.read(&mut DataRead::new(w.get_written())).unwrap();
+        let what = |b: &Build| format!("{:?}", (&b.items, &b.liquids, &b.extra));
+        assert_ne!(what(&ours), what(b));
     }
+
+    let mut buff = DataWrite::default();
+    m.serialize(&mut buff).unwrap();
+    let read = Map::deserialize(&mut DataRead::new(buff.get_written())).unwrap();
+    assert_eq!(read.content.item(0), Item::ALL.last().copied());
+    assert_eq!(format!("{:?}", m.tiles), format!("{:?}", read.tiles));
+    let router = read[0].build().unwrap();
+    assert_eq!(router.items.get(Item::Copper), 3);
+    assert_eq!(router.liquids.get(Fluid::Slag), 500);
+    assert!(
+        matches!(&read[1].build().unwrap().extra, Extra::Conveyor(i) if i[0].item == Item::Lead)
+    );
+    assert!(
+        matches!(&read[2].build().unwrap().extra, Extra::ItemTurret { ammo, .. } if ammo[..] == [(Item::Graphite, 10)])
+    );
+
+    let mut again = DataWrite::default();
+    read.serialize(&mut again).unwrap();
+    assert_eq!(buff.get_written(), again.get_written());
+}
+
+#[test]
+fn modded_blocks() {
+    // a map saved with a mod, that adds a 3x3 building and a boulder
+    let mut header = ContentMap::default().with_ours().header;
+    let (_, blocks) = header
+        .iter_mut()
+        .find(|(ty, _)| *ty == u8::from(ContentType::Block))
+        .unwrap();
+    blocks.extend(["modded-drill".to_string(), "modded-boulder".to_string()]);
+    let mut m = Map::filled(5, 5, BlockEnum::Stone);
+    m.content = ContentMap::from_header(header);
+    m.set_block(
+        2,
+        2,
+        Block::placeholder("modded-drill", 3).unwrap(),
+        Rotation::Left,
+        team::SHARDED,
+    )
+    .unwrap();
+    m[12].build.as_mut().unwrap().extra = Extra::Raw(vec![1, 2, 3].into());
+    m[0].set_block(Block::placeholder("modded-boulder", 1).unwrap());
+    m[0].build.as_mut().unwrap().data = 2;
+
+    let mut buff = DataWrite::default();
+    m.serialize(&mut buff).unwrap();
+    let read = Map::deserialize(&mut DataRead::new(buff.get_written())).unwrap();
+    let drill = read[12].build().unwrap();
+    assert_eq!(drill.block.name(), "modded-drill");
+    assert_eq!(drill.block.get_size(), 3);
+    assert!(matches!(&drill.extra, Extra::Raw(r) if r[..] == [1, 2, 3]));
+    assert_eq!(read.covering(1, 3).unwrap().0, GridPos(2, 2));
+    assert_eq!(read[0].build().unwrap().data, 2);
+    assert_eq!(format!("{:?}", m.tiles), format!("{:?}", read.tiles));
+
+    let mut again = DataWrite::default();
+    read.serialize(&mut again).unwrap();
+    assert_eq!(buff.get_written(), again.get_written());
+}
+
+#[test]
+fn raw_fallback() {
+    use crate::block::SORTER;
+    let mut b = Build::new(&SORTER);
+    b.state = Some(State::Item(Some(Item::Copper)));
+    let mut w = DataWrite::default();
+    b.write(&mut w).unwrap();
+    let mut read = Build::new(&SORTER);
+    read.read(&mut DataRead::new(w.get_written())).unwrap();
+    assert!(matches!(read.state, Some(State::Item(Some(Item::Copper)))));
+    assert_eq!(read.raw_reason, None);
+
+    // more than a sorter has
+    w.write_bytes(&[1, 2]).unwrap();
+    let mut read = Build::new(&SORTER);
+    read.read(&mut DataRead::new(w.get_written())).unwrap();
+    assert!(read.state.is_none());
+    assert!(matches!(read.extra, Extra::Raw(_)));
+    assert_eq!(read.raw_reason, Some(RawReason::Leftover(2)));
+    // written back as read
+    let mut again = DataWrite::default();
+    read.write(&mut again).unwrap();
+    assert_eq!(again.get_written(), w.get_written());
 }
@@ -0,0 +1,195 @@
+//! one pixel per tile pictures of maps
+use std::ops::{Coroutine, CoroutineState::*};
+use std::pin::Pin;
+
+use super::map::{Map, MapReader, ReadError, ThinBloc, ThinMapData};
+use crate::block::{content::Type, ratios::ore_item, Block};
+use crate::color_mapping::{BLOCK2COLOR, ENV2COLOR};
+use crate::content::Content;
+use crate::team::Team;
+use crate::utils::Image;
+
+/// environment blocks that the floor shows through (boulders, bushes)
+fn solid(name: &str) -> bool {
+    !(name.ends_with("boulder") || matches!(name, "pur-bush" | "redweed" | "spore-cluster"))
+}
+
+/// the color of a tile without a building, like the games `MapIO.colorFor`
+fn ground(floor: Type, ore: Type, wall: Option<&Block>) -> (u8, u8, u8) {
+    if let Some(&c) = wall
+        .filter(|b| solid(b.name()))
+        .and_then(|b| ENV2COLOR.get(b.name()))
+    {
+        return c;
+    }
+    if let Some(item) = ore_item(ore) {
+        return item.color();
+    }
+    BLOCK2COLOR
+        .get(floor.get_name())
+        .copied()
+        .unwrap_or((0, 0, 0))
+}
+
+struct Minimap {
+    width: usize,
+    height: usize,
+    buf: Vec<u8>,
+}
+
+impl Minimap {
+    fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            buf: vec![0; width * height * 3],
+        }
+    }
+
+    /// colors tile `j` (`x + y * width`, y up)
+    fn put(&mut self, j: usize, (r, g, b): (u8, u8, u8)) {
+        let (x, y) = (j % self.width, j / self.width);
+        let at = ((self.height - 1 - y) * self.width + x) * 3;
+        self.buf[at..at + 3].copy_from_slice(&[r, g, b]);
+    }
+
+    /// draws a block at tile `j`: environment blocks over the ground they are on, buildings over their whole footprint
+    fn block(&mut self, j: usize, block: &Block, team: Team, floor: (Type, Type)) {
+        if ENV2COLOR.contains_key(block.name()) {
+            self.put(j, ground(floor.0, floor.1, Some(block)));
+            return;
+        }
+        let (x, y) = (j % self.width, j / self.width);
+        let size = block.get_size() as usize;
+        let off = (size - 1) / 2;
+        for y in y.saturating_sub(off)..(y + size - off).min(self.height) {
+            for x in x.saturating_sub(off)..(x + size - off).min(self.width) {
+                self.put(x + y * self.width, team.color());
+            }
+        }
+    }
+
+    fn finish(self) -> Image<Vec<u8>, 3> {
+        Image::build(self.width as u32, self.height as u32).buf(self.buf)
+    }
+}
+
+impl Map {
+    /// draws the map at one pixel per tile, in the colors of the games minimap:
+    /// buildings in their team color, then walls, ores, and floors.
+    /// ```
+    /// # use mindus::Map;
+    /// # use mindus::block::{content::Type, Rotation, ROUTER};
+    /// # use mindus::team::SHARDED;
+    /// let mut m = Map::filled(4, 2, Type::Stone);
+    /// m.set_block(3, 1, &ROUTER, Rotation::Up, SHARDED).unwrap();
+    /// let img = m.render_minimap();
+    /// assert_eq!((img.width(), img.height()), (4, 2));
+    /// // y goes up, pictures go down
+    /// assert_eq!(&img.buffer()[9..12], &[255, 211, 127]);
+    /// ```
+    #[must_use]
+    pub fn render_minimap(&self) -> Image<Vec<u8>, 3> {
+        let mut img = Minimap::new(self.width, self.height);
+        for (j, tile) in self.tiles.iter().enumerate() {
+            img.put(j, ground(tile.floor, tile.ore, None));
+        }
+        for (j, tile) in self.tiles.iter().enumerate() {
+            if let Some(b) = tile.build() {
+                img.block(j, b.block, b.team, (tile.floor, tile.ore));
+            }
+        }
+        img.finish()
+    }
+}
+
+impl MapReader {
+    /// [`Map::render_minimap`], straight from the stream, without reading the buildings.
+    /// call after [`MapReader::content`].
+    pub fn render_minimap(&mut self) -> Result<Image<Vec<u8>, 3>, ReadError> {
+        let mut co = self.thin_map()?;
+        let (w, h) = match Pin::new(&mut co).resume(()) {
+            Yielded(ThinMapData::Init { width, height }) => (width as usize, height as usize),
+            Complete(Err(x)) => return Err(x),
+            _ => unreachable!(),
+        };
+        let mut img = Minimap::new(w, h);
+        let mut tiles = Vec::with_capacity(w * h);
+        for j in 0..w * h {
+            match Pin::new(&mut co).resume(()) {
+                Yielded(ThinMapData::Tile { floor, ore }) => {
+                    img.put(j, ground(floor, ore, None));
+                    tiles.push((floor, ore));
+                }
+                Complete(Err(x)) => return Err(x),
+                _ => unreachable!(),
+            }
+        }
+        let mut j = 0;
+        while j < w * h {
+            match Pin::new(&mut co).resume(()) {
+                Yielded(ThinMapData::Bloc(ThinBloc::None(n))) => j += n as usize + 1,
+                Yielded(ThinMapData::Bloc(ThinBloc::Build(_, block, team))) => {
+                    img.block(j, block, team, tiles[j]);
+                    j += 1;
+                }
+                Yielded(ThinMapData::Bloc(ThinBloc::Many(block, n))) => {
+                    let run = j..(j + n as usize + 1).min(w * h);
+                    for (j, &tile) in run.clone().zip(&tiles[run]) {
+                        img.block(j, block, crate::team::DERELICT, tile);
+                    }
+                    j += n as usize + 1;
+                }
+                Complete(Err(x)) => return Err(x),
+                x => unreachable!("{x:?}"),
+            }
+        }
+        match Pin::new(&mut co).resume(()) {
+            Complete(Ok(())) => (),
+            Complete(Err(x)) => return Err(x),
+            f => unreachable!("{f:?}"),
+        };
+        Ok(img.finish())
+    }
+}
+
+#[test]
+fn stream() {
+    use crate::block::{content::Type, Rotation, BOULDER, CORE_SHARD, ROUTER, STONE_WALL};
+    use crate::data::{DataRead, DataWrite, Serializable};
+    use crate::{item, team};
+    let mut m = Map::filled(6, 4, Type::Stone);
+    m.set_ore(0, 3, Type::OreCopper).unwrap();
+    m.set_ore(1, 3, Type::Pebbles).unwrap();
+    m.set_block(4, 1, &CORE_SHARD, Rotation::Up, team::CRUX)
+        .unwrap();
+    m.set_block(0, 0, &ROUTER, Rotation::Up, team::SHARDED)
+        .unwrap();
+    m.set_block(1, 0, &STONE_WALL, Rotation::Up, team::DERELICT)
+        .unwrap();
+    m.set_block(2, 0, &BOULDER, Rotation::Up, team::DERELICT)
+        .unwrap();
+    let img = m.render_minimap();
+    let px = |x: usize, y: usize| {
+        let at = ((3 - y) * 6 + x) * 3;
+        <[u8; 3]>::try_from(&img.buffer()[at..at + 3]).unwrap()
+    };
+    let stone = BLOCK2COLOR["stone"];
+    assert_eq!(px(0, 3), <[u8; 3]>::from(item::Type::Copper.color()));
+    assert_eq!(px(1, 3), <[u8; 3]>::from(stone));
+    assert_eq!(px(0, 0), <[u8; 3]>::from(team::SHARDED.color()));
+    assert_eq!(px(1, 0), <[u8; 3]>::from(ENV2COLOR["stone-wall"]));
+    assert_eq!(px(2, 0), <[u8; 3]>::from(stone));
+    for (x, y) in [(3, 0), (5, 0), (4, 1), (3, 2), (5, 2)] {
+        assert_eq!(px(x, y), <[u8; 3]>::from(team::CRUX.color()));
+    }
+
+    let mut buff = DataWrite::default();
+    m.serialize(&mut buff).unwrap();
+    let mut r = MapReader::new(&mut DataRead::new(buff.get_written())).unwrap();
+    r.header().unwrap();
+    r.version().unwrap();
+    r.tags().unwrap();
+    r.content().unwrap();
+    assert_eq!(r.render_minimap().unwrap().buffer(), img.buffer());
+}
@@ -9,16 +9,24 @@ use std::fmt;
 use std::str::Utf8Error;
 use thiserror::Error;
 
+pub mod annotate;
 pub(crate) mod autotile;
 mod base64;
 pub mod command;
+pub mod diff;
 pub mod dynamic;
 pub mod entity_mapping;
+pub mod json;
 pub mod map;
+pub mod minimap;
 pub mod planet;
 pub mod renderer;
+pub mod rules;
 pub mod schematic;
 pub mod sector;
+pub mod tiled;
+#[cfg(feature = "lemu")]
+pub mod validate;
 pub mod weather;
 
 #[derive(Debug)]
@@ -100,6 +108,11 @@ impl<'d> DataRead<'d> {
         Ok(())
     }
 
+    /// how many bytes are left. use this to bound preallocations by counts that were read.
+    pub(crate) const fn remaining(&self) -> usize {
+        self.data.len()
+    }
+
     pub fn read_chunk<E: Error + From<ReadError>, T>(
         &mut self,
         big: bool,
@@ -427,7 +440,7 @@ where
     fn serialize(&self, buff: &mut DataWrite<'_>) -> Result<(), Self::WriteError>;
 }
 
-#[derive(Clone, Copy, Eq, PartialEq)]
+#[derive(Clone, Copy, Eq, PartialEq, Hash)]
 pub struct GridPos(pub usize, pub usize);
 
 impl From<u32> for GridPos {
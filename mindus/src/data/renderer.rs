@@ -2,19 +2,22 @@
 use std::ops::Coroutine;
 use std::pin::Pin;
 
+use super::annotate::Layout;
 pub(crate) use super::autotile::*;
-use super::schematic::Schematic;
+use super::schematic::{Placement, Schematic};
 use super::GridPos;
 use crate::block::content::Type;
+use crate::team::Team;
 pub(crate) use crate::utils::*;
 use crate::Map;
 use crate::{
     block::Rotation,
-    data::map::{ThinBloc, ThinMapData},
+    data::map::{Plan, ThinBloc, ThinMapData},
 };
 use fimg::{uninit, BlendingOverlay};
 
 include!(concat!(env!("OUT_DIR"), "/full.rs"));
+include!(concat!(env!("OUT_DIR"), "/half.rs"));
 include!(concat!(env!("OUT_DIR"), "/quar.rs"));
 include!(concat!(env!("OUT_DIR"), "/eigh.rs"));
 
@@ -22,7 +25,7 @@ include!(concat!(env!("OUT_DIR"), "/eigh.rs"));
 #[repr(u8)]
 pub enum Scale {
     Full,
-    // Half,
+    Half,
     Quarter,
     Eigth,
 }
@@ -32,10 +35,21 @@ impl Scale {
     pub const fn px(self) -> u8 {
         match self {
             Self::Full => 32,
+            Self::Half => 32 / 2,
             Self::Quarter => 32 / 4,
             Self::Eigth => 32 / 8,
         }
     }
+
+    /// the next scale down
+    const fn smaller(self) -> Option<Self> {
+        match self {
+            Self::Full => Some(Self::Half),
+            Self::Half => Some(Self::Quarter),
+            Self::Quarter => Some(Self::Eigth),
+            Self::Eigth => None,
+        }
+    }
 }
 
 impl std::ops::Mul<u32> for Scale {
@@ -49,6 +63,7 @@ impl std::ops::Mul<u32> for Scale {
 macro_rules! load {
 	(raw $name: literal, $scale:expr) => {
 		paste::paste! { match $scale {
+            $crate::data::renderer::Scale::Half => $crate::data::renderer::half::[<$name:snake:upper>],
             $crate::data::renderer::Scale::Quarter => $crate::data::renderer::quar::[<$name:snake:upper>],
             $crate::data::renderer::Scale::Eigth => $crate::data::renderer::eigh::[<$name:snake:upper>],
             $crate::data::renderer::Scale::Full => $crate::data::renderer::full::[<$name:snake:upper>],
@@ -56,13 +71,14 @@ macro_rules! load {
     } };
     ($name:literal, $scale:expr) => { paste::paste! {
         $crate::utils::image::ImageHolder::from(match $scale {
+            $crate::data::renderer::Scale::Half => &$crate::data::renderer::half::[<$name:snake:upper>],
             $crate::data::renderer::Scale::Quarter => &$crate::data::renderer::quar::[<$name:snake:upper>],
             $crate::data::renderer::Scale::Eigth => &$crate::data::renderer::eigh::[<$name:snake:upper>],
             $crate::data::renderer::Scale::Full => &$crate::data::renderer::full::[<$name:snake:upper>],
         }.copy())
     } };
     ($name: literal) => { paste::paste! {
-        [$crate::data::renderer::full::[<$name:snake:upper>].copy(), $crate::data::renderer::quar::[<$name:snake:upper>].copy(), $crate::data::renderer::eigh::[<$name:snake:upper>].copy()]
+        [$crate::data::renderer::full::[<$name:snake:upper>].copy(), $crate::data::renderer::half::[<$name:snake:upper>].copy(), $crate::data::renderer::quar::[<$name:snake:upper>].copy(), $crate::data::renderer::eigh::[<$name:snake:upper>].copy()]
     } };
     (from $v:ident which is [$($k:literal $(|)?)+], $scale: ident) => {
         $crate::data::renderer::load!($scale -> match $v {
@@ -81,6 +97,7 @@ macro_rules! load {
         match $v {
             $($k =>
                 ImageHolder::from(match $scale {
+                    $crate::data::renderer::Scale::Half => &$crate::data::renderer::half::[<$k:snake:upper _ $x:snake:upper>],
                     $crate::data::renderer::Scale::Quarter => &$crate::data::renderer::quar::[<$k:snake:upper _ $x:snake:upper>],
                     $crate::data::renderer::Scale::Eigth => &$crate::data::renderer::eigh::[<$k:snake:upper _ $x:snake:upper>],
                     $crate::data::renderer::Scale::Full => &$crate::data::renderer::full::[<$k:snake:upper _ $x:snake:upper>],
@@ -97,11 +114,223 @@ pub(crate) use load;
 pub trait Renderable {
     /// create a picture
     #[must_use = "i did so much work for you"]
-    fn render(&self) -> Image<Vec<u8>, 3>;
+    fn render(&self) -> Image<Vec<u8>, 3> {
+        self.render_with(&RenderOptions::default())
+    }
+
+    /// create a picture, the way you like it
+    #[must_use = "i did so much work for you"]
+    fn render_with(&self, opts: &RenderOptions) -> Image<Vec<u8>, 3>;
+}
+
+/// what goes behind things
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum Background {
+    /// metal floor behind schematics, and the floor of maps
+    #[default]
+    Floor,
+    /// a solid color
+    Color(u8, u8, u8),
+}
+
+/// how to render something. the default is what [`Renderable::render`] does.
+/// ```
+/// # use mindus::*;
+/// # use mindus::block::*;
+/// # use mindus::data::renderer::{Background, RenderOptions, Scale};
+/// let mut s = Schematic::new(4, 4);
+/// s.put(1, 1, &ROUTER);
+/// let opts = RenderOptions {
+///     scale: Some(Scale::Half),
+///     viewport: Some((1, 1, 2, 2)),
+///     background: Background::Color(0, 0, 0),
+///     ..Default::default()
+/// };
+/// let output = s.render_with(&opts);
+/// assert_eq!((output.width(), output.height()), (2 * 16, 2 * 16));
+/// let output = s.render_with(&RenderOptions { max_size: Some(100), ..Default::default() });
+/// assert_eq!(output.width(), 6 * 16);
+/// ```
+#[derive(Debug, Copy, Clone, Default)]
+pub struct RenderOptions {
+    /// the scale to draw at. picked from the size if `None`.
+    pub scale: Option<Scale>,
+    /// the tiles to draw, as `(x, y, width, height)` from the bottom left, cut down to fit.
+    /// schematics lose their border.
+    pub viewport: Option<(usize, usize, usize, usize)>,
+    /// the largest the width or height of the picture may be, in pixels.
+    /// the scale is lowered until it fits, and if it still does not fit at [`Scale::Eigth`], the picture is shrunk.
+    pub max_size: Option<u32>,
+    pub background: Background,
+}
+
+impl RenderOptions {
+    /// the viewport of something `width`x`height`, or all of it
+    fn view(&self, width: usize, height: usize) -> (usize, usize, usize, usize) {
+        // nothing to see
+        if width == 0 || height == 0 {
+            return (0, 0, 0, 0);
+        }
+        let Some((x, y, w, h)) = self.viewport else {
+            return (0, 0, width, height);
+        };
+        let (x, y) = (x.min(width - 1), y.min(height - 1));
+        (x, y, w.clamp(1, width - x), h.clamp(1, height - y))
+    }
+
+    /// the scale for a picture `width`x`height` tiles big (`fallback`, if none was asked for)
+    fn scale(&self, width: usize, height: usize, fallback: Scale) -> Scale {
+        let mut scale = self.scale.unwrap_or(fallback);
+        if let Some(max) = self.max_size {
+            while scale * width.max(height) as u32 > max
+                && let Some(smaller) = scale.smaller()
+            {
+                scale = smaller;
+            }
+        }
+        scale
+    }
+
+    /// the viewport of a map `width`x`height`, and the scale to draw it at
+    pub(crate) fn map_view(
+        &self,
+        width: usize,
+        height: usize,
+    ) -> ((usize, usize, usize, usize), Scale) {
+        let view @ (_, _, w, h) = self.view(width, height);
+        let fallback = if width + height < 2000 {
+            Scale::Quarter
+        } else {
+            Scale::Eigth
+        };
+        (view, self.scale(w, h, fallback))
+    }
+
+    /// the buildings that can be seen (or seen by what can be seen) in the viewport of a map `width`x`height`:
+    /// the biggest blocks reach 4 tiles past their center, and look a tile further for their neighbors
+    pub(crate) fn map_around(&self, width: usize, height: usize) -> (usize, usize, usize, usize) {
+        let ((x, y, w, h), _) = self.map_view(width, height);
+        (
+            x.saturating_sub(5),
+            y.saturating_sub(5),
+            w + x.min(5) + 5,
+            h + y.min(5) + 5,
+        )
+    }
+
+    /// shrinks a picture that is bigger than [`max_size`](Self::max_size).
+    /// [`Renderable::render_with`] does this for you.
+    #[must_use]
+    pub fn fit(&self, img: Image<Vec<u8>, 3>) -> Image<Vec<u8>, 3> {
+        let Some((w, h)) = self.fitted(img.width(), img.height()) else {
+            return img;
+        };
+        let img = img.scale::<fimg::scale::Nearest>(w, h);
+        Image::build(img.width(), img.height()).buf(img.take_buffer().into_vec())
+    }
+
+    /// the size [`fit`](Self::fit) shrinks a `width`x`height` picture to, if it does
+    pub(crate) fn fitted(&self, width: u32, height: u32) -> Option<(u32, u32)> {
+        let max = u64::from(self.max_size?.max(1));
+        let (w, h) = (u64::from(width), u64::from(height));
+        if w.max(h) <= max {
+            return None;
+        }
+        let (w, h) = if w > h {
+            (max, (h * max / w).max(1))
+        } else {
+            ((w * max / h).max(1), max)
+        };
+        Some((w as u32, h as u32))
+    }
+}
+
+/// a picture of one color
+pub(crate) fn solid(width: u32, height: u32, (r, g, b): (u8, u8, u8)) -> Image<Vec<u8>, 3> {
+    Image::build(width, height).buf([r, g, b].repeat(width as usize * height as usize))
+}
+
+/// what an empty viewport draws: a image can not be empty, so a pixel of the background (black for floors)
+pub(crate) fn nothing(background: Background) -> Image<Vec<u8>, 3> {
+    match background {
+        Background::Color(r, g, b) => solid(1, 1, (r, g, b)),
+        Background::Floor => solid(1, 1, (0, 0, 0)),
+    }
+}
+
+/// cuts `(x, y, width, height)` (in pixels, from the top left) out of a picture
+fn crop(img: &Image<Vec<u8>, 3>, x: u32, y: u32, width: u32, height: u32) -> Image<Vec<u8>, 3> {
+    let mut out = Vec::with_capacity(width as usize * height as usize * 3);
+    for row in img
+        .buffer()
+        .chunks_exact(img.width() as usize * 3)
+        .skip(y as usize)
+        .take(height as usize)
+    {
+        out.extend_from_slice(&row[x as usize * 3..(x + width) as usize * 3]);
+    }
+    Image::build(width, height).buf(out)
+}
+
+/// where the tiles of a schematic-like thing `width`x`height` big are in [`render_schematic`]
+pub(crate) fn schematic_layout((width, height): (usize, usize), opts: &RenderOptions) -> Layout {
+    let fallback = if width + height > 500 {
+        Scale::Quarter
+    } else {
+        Scale::Full
+    };
+    match opts.viewport.map(|_| opts.view(width, height)) {
+        Some((x, y, w, h)) => Layout {
+            scale: opts.scale(w, h, fallback),
+            left: x as i64,
+            top: (y + h) as i64 - 1,
+            width: w,
+            height: h,
+        },
+        None => Layout {
+            scale: opts.scale(width + 2, height + 2, fallback),
+            left: -1,
+            top: height as i64,
+            width: width + 2,
+            height: height + 2,
+        },
+    }
+}
+
+/// renders a schematic-like thing `width`x`height` big, with a border of a tile (unless there is a viewport),
+/// with `draw` putting the blocks on a transparent canvas, and `over` drawing over the backdrop.
+pub(crate) fn render_schematic(
+    (width, height): (usize, usize),
+    opts: &RenderOptions,
+    draw: impl FnOnce(&mut Image<Vec<u8>, 4>, Scale),
+    over: impl FnOnce(&mut Image<Vec<u8>, 3>, Frame),
+) -> Image<Vec<u8>, 3> {
+    let view = opts.viewport.map(|_| opts.view(width, height));
+    let scale = schematic_layout((width, height), opts).scale;
+    let mut canvas = Image::alloc(scale * (width + 2) as u32, scale * (height + 2) as u32);
+    draw(&mut canvas, scale);
+    let mut img = backdrop(canvas, scale, opts.background);
+    let frame = Frame {
+        height,
+        border: 1,
+        left: 0,
+        scale,
+    };
+    over(&mut img, frame);
+    if let Some((x, y, w, h)) = view {
+        img = crop(
+            &img,
+            scale * (x + 1) as u32,
+            scale * (height - y - h + 1) as u32,
+            scale * w as u32,
+            scale * h as u32,
+        );
+    }
+    opts.fit(img)
 }
 
 impl Renderable for Schematic {
-    /// creates a picture of a schematic. Bridges and node connections are not drawn.
+    /// creates a picture of a schematic, with the bridges and power lasers over it.
     /// ```
     /// # use mindus::*;
     /// # use mindus::block::*;
@@ -110,156 +339,325 @@ impl Renderable for Schematic {
     /// s.put(0, 2, &ROUTER);
     /// s.put(1, 2, &COPPER_WALL);
     /// let output /*: Image */ = s.render();
+    /// // with links
+    /// # use mindus::data::dynamic::DynData;
+    /// let mut s = Schematic::new(4, 2);
+    /// s.set(0, 0, &BRIDGE_CONVEYOR, DynData::Point2(3, 0), Rotation::Right).unwrap();
+    /// s.put(3, 0, &BRIDGE_CONVEYOR);
+    /// s.set(0, 1, &POWER_NODE, DynData::Point2Array(vec![(2, 0)]), Rotation::Up).unwrap();
+    /// s.put(2, 1, &BATTERY);
+    /// let output = s.render();
     /// ```
-    fn render(&self) -> Image<Vec<u8>, 3> {
-        let scale = if self.width + self.height > 500 {
-            Scale::Quarter
-        } else {
-            Scale::Full
-        };
-        // fill background
-        // SAFETY: metal-floor is scalexscale, the output is a multiple of scale
-        let mut bg = unsafe {
-            load!("metal-floor", scale).borrow().repeated(
-                scale * (self.width + 2) as u32,
-                scale * (self.height + 2) as u32,
-            )
+    fn render_with(&self, opts: &RenderOptions) -> Image<Vec<u8>, 3> {
+        render_schematic(
+            (self.width, self.height),
+            opts,
+            |canvas, scale| {
+                draw_placements(
+                    canvas,
+                    self,
+                    self.height,
+                    scale,
+                    self.block_iter().map(|(p, tile)| (p, tile, None)),
+                );
+            },
+            |img, frame| draw_schematic_links(&mut img.as_mut(), self, frame),
+        )
+    }
+}
+
+/// where a schematic's placement goes, in tiles from the top left of a render with a border of a tile
+const fn placement_at(GridPos(x, y): GridPos, size: u8, height: usize) -> (u32, u32) {
+    (
+        x as u32 - ((size - 1) / 2) as u32 + 1,
+        height as u32 - y as u32 - ((size / 2) + 1) as u32 + 1,
+    )
+}
+
+/// the image of a placement of `s`, in context
+fn placement_image(s: &Schematic, pos: GridPos, tile: &Placement, scale: Scale) -> ImageHolder<4> {
+    let ctx = tile.block.wants_context().then(|| {
+        let pctx = PositionContext {
+            position: pos,
+            width: s.width,
+            height: s.height,
         };
-        let mut canvas = Image::alloc(
-            scale * (self.width + 2) as u32,
-            scale * (self.height + 2) as u32,
-        );
-        for (GridPos(x, y), tile) in self.block_iter() {
-            let ctx = tile.block.wants_context().then(|| {
-                let pctx = PositionContext {
-                    position: GridPos(x, y),
-                    width: self.width,
-                    height: self.height,
-                };
-                RenderingContext {
-                    cross: self.cross(&pctx),
-                    position: pctx,
-                }
-            });
-            let x = x as u32 - ((tile.block.get_size() - 1) / 2) as u32;
-            let y = self.height as u32 - y as u32 - ((tile.block.get_size() / 2) + 1) as u32;
+        RenderingContext {
+            cross: s.cross(&pctx),
+            position: pctx,
+        }
+    });
+    tile.image(
+        ctx.as_ref(),
+        tile.get_rotation().unwrap_or(Rotation::Up),
+        scale,
+    )
+}
+
+/// how many sprites are made (in parallel, maybe) before they are drawn, in order
+const BATCH: usize = 1024;
+
+/// draws placements of `s` (tinted, maybe) onto a canvas for a schematic `height` tall, with a border of a tile.
+pub(crate) fn draw_placements<'s>(
+    canvas: &mut Image<Vec<u8>, 4>,
+    s: &'s Schematic,
+    height: usize,
+    scale: Scale,
+    placements: impl Iterator<Item = (GridPos, &'s Placement, Option<(u8, u8, u8)>)>,
+) {
+    let placements = placements.collect::<Vec<_>>();
+    for batch in placements.chunks(BATCH) {
+        let sprites = par::map(batch, |&(pos, tile, tint)| {
+            let mut img = placement_image(s, pos, tile, scale);
+            if let Some(tint) = tint {
+                img.tint(tint);
+            }
+            (placement_at(pos, tile.block.get_size(), height), img)
+        });
+        for ((x, y), img) in sprites {
             unsafe {
-                canvas.as_mut().overlay_at(
-                    &tile
-                        .image(
-                            ctx.as_ref(),
-                            tile.get_rotation().unwrap_or(Rotation::Up),
-                            scale,
-                        )
-                        .borrow(),
-                    scale * (x + 1),
-                    scale * (y + 1),
-                )
+                canvas
+                    .as_mut()
+                    .overlay_at(&img.borrow(), scale * x, scale * y)
             };
         }
+    }
+}
 
-        if matches!(scale, Scale::Full) {
-            canvas.as_mut().shadow();
-            unsafe { bg.overlay_blended(&canvas) };
-        } else {
-            unsafe { bg.overlay(&canvas) };
-        }
-        bg
+/// draws a translucent placement of `s` onto a finished render for a schematic `height` tall
+pub(crate) fn draw_placement_ghost(
+    img: Image<&mut [u8], 3>,
+    s: &Schematic,
+    (pos, tile): (GridPos, &Placement),
+    height: usize,
+    scale: Scale,
+    tint: (u8, u8, u8),
+) {
+    let mut ghost = placement_image(s, pos, tile, scale);
+    ghost.tint(tint);
+    let (x, y) = placement_at(pos, tile.block.get_size(), height);
+    draw_ghost(
+        img,
+        ghost.borrow(),
+        i64::from(scale * x),
+        i64::from(scale * y),
+    );
+}
+
+/// puts the background behind a canvas with a border of a tile (and a shadow, at full scale)
+pub(crate) fn backdrop(
+    mut canvas: Image<Vec<u8>, 4>,
+    scale: Scale,
+    background: Background,
+) -> Image<Vec<u8>, 3> {
+    let mut bg = match background {
+        // SAFETY: metal-floor is scalexscale, the output is a multiple of scale
+        Background::Floor => unsafe {
+            load!("metal-floor", scale)
+                .borrow()
+                .repeated(canvas.width(), canvas.height())
+        },
+        Background::Color(r, g, b) => solid(canvas.width(), canvas.height(), (r, g, b)),
+    };
+    if matches!(scale, Scale::Full) {
+        canvas.as_mut().shadow();
+        unsafe { bg.overlay_blended(&canvas) };
+    } else {
+        unsafe { bg.overlay(&canvas) };
     }
+    bg
 }
 
 impl Renderable for Map {
     /// Draws a map
-    fn render(&self) -> Image<Vec<u8>, 3> {
-        let scale = if self.width + self.height < 2000 {
-            Scale::Quarter
-        } else {
-            Scale::Eigth
-        };
-        let mut img = uninit::Image::<_, 3>::new(
-            (scale * self.width as u32).try_into().unwrap(),
-            (scale * self.height as u32).try_into().unwrap(),
-        );
-        // loop1 draws the floor
-        for y in 0..self.height {
-            for x in 0..self.width {
-                // Map::new() allocates w*h items
-                let j = x + self.width * y;
-                let tile = unsafe { self.tiles.get_unchecked(j) };
-                let y = self.height - y - 1;
-                // println!("draw {tile:?} ({x}, {y})");
-                unsafe { img.overlay_at(&tile.floor(scale), scale * x as u32, scale * y as u32) };
-                if tile.has_ore() {
-                    unsafe { img.overlay_at(&tile.ore(scale), scale * x as u32, scale * y as u32) };
+    /// ```
+    /// # use mindus::*;
+    /// # use mindus::data::map::Map;
+    /// # use mindus::data::renderer::{RenderOptions, Scale};
+    /// let m = Map::filled(100, 50, mindus::block::content::Type::Grass);
+    /// assert_eq!(m.render().width(), 100 * 8);
+    /// let opts = RenderOptions {
+    ///     scale: Some(Scale::Half),
+    ///     viewport: Some((90, 0, 20, 10)),
+    ///     ..Default::default()
+    /// };
+    /// assert_eq!(m.render_with(&opts).width(), 10 * 16);
+    /// ```
+    fn render_with(&self, opts: &RenderOptions) -> Image<Vec<u8>, 3> {
+        let ((vx, vy, vw, vh), scale) = opts.map_view(self.width, self.height);
+        if vw == 0 || vh == 0 {
+            return nothing(opts.background);
+        }
+        // the rows above the viewport
+        let above = self.height - vy - vh;
+        let mut img = match opts.background {
+            Background::Floor => {
+                let mut img = uninit::Image::<_, 3>::new(
+                    (scale * vw as u32).try_into().unwrap(),
+                    (scale * vh as u32).try_into().unwrap(),
+                );
+                // loop1 draws the floor
+                for y in vy..vy + vh {
+                    for x in vx..vx + vw {
+                        // Map::new() allocates w*h items
+                        let j = x + self.width * y;
+                        let tile = unsafe { self.tiles.get_unchecked(j) };
+                        let (x, y) = (x - vx, self.height - y - 1 - above);
+                        // println!("draw {tile:?} ({x}, {y})");
+                        unsafe {
+                            img.overlay_at(&tile.floor(scale), scale * x as u32, scale * y as u32)
+                        };
+                        if tile.has_ore() {
+                            unsafe {
+                                img.overlay_at(&tile.ore(scale), scale * x as u32, scale * y as u32)
+                            };
+                        }
+                    }
                 }
+                unsafe { img.assume_init() }
             }
-        }
-        let mut img = unsafe { img.assume_init() };
-        // loop2 draws the buildings
-        for y in 0..self.height {
-            for x in 0..self.width {
+            Background::Color(r, g, b) => solid(scale * vw as u32, scale * vh as u32, (r, g, b)),
+        };
+        // loop2 draws the buildings (the biggest reach 4 tiles past their center)
+        let builds = (vy.saturating_sub(4)..(vy + vh + 4).min(self.height))
+            .flat_map(|y| {
+                (vx.saturating_sub(4)..(vx + vw + 4).min(self.width)).map(move |x| (x, y))
+            })
+            .filter(|&(x, y)| self.tiles[x + self.width * y].build().is_some())
+            .collect::<Vec<_>>();
+        for batch in builds.chunks(BATCH) {
+            let sprites = par::map(batch, |&(x, y)| {
                 let j = x + self.width * y;
-                let tile = unsafe { self.tiles.get_unchecked(j) };
+                let tile = &self.tiles[j];
+                let build = tile.build().unwrap();
                 let y = self.height - y - 1;
-                if let Some(build) = tile.build() {
-                    let s = build.block.get_size();
-                    let x = x
-                        - (match s {
-                            1 | 2 => 0,
-                            3 | 4 => 1,
-                            5 | 6 => 2,
-                            7 | 8 => 3,
-                            9 => 4,
-                            // SAFETY: no block too big
-                            _ => unsafe { std::hint::unreachable_unchecked() },
-                        }) as usize;
-                    let y = y
-                        - (match s {
-                            1 => 0,
-                            2 | 3 => 1,
-                            4 | 5 => 2,
-                            6 | 7 => 3,
-                            8 | 9 => 4,
-                            // SAFETY: no block too big
-                            _ => unsafe { std::hint::unreachable_unchecked() },
-                        }) as usize;
-                    let ctx = build.block.wants_context().then(|| {
-                        let pctx = PositionContext {
-                            position: GridPos(x, y),
-                            width: self.width,
-                            height: self.height,
-                        };
-                        RenderingContext {
-                            cross: self.cross(j, &pctx),
-                            position: pctx,
-                        }
-                    });
-                    unsafe {
-                        img.as_mut().overlay_at(
-                            &tile.build_image(ctx.as_ref(), scale).borrow(),
-                            scale * x as u32,
-                            scale * y as u32,
-                        )
+                let s = build.block.get_size();
+                let x = x
+                    - (match s {
+                        1 | 2 => 0,
+                        3 | 4 => 1,
+                        5 | 6 => 2,
+                        7 | 8 => 3,
+                        9 => 4,
+                        // SAFETY: no block too big
+                        _ => unsafe { std::hint::unreachable_unchecked() },
+                    }) as usize;
+                let y = y
+                    - (match s {
+                        1 => 0,
+                        2 | 3 => 1,
+                        4 | 5 => 2,
+                        6 | 7 => 3,
+                        8 | 9 => 4,
+                        // SAFETY: no block too big
+                        _ => unsafe { std::hint::unreachable_unchecked() },
+                    }) as usize;
+                let ctx = build.block.wants_context().then(|| {
+                    let pctx = PositionContext {
+                        position: GridPos(x, y),
+                        width: self.width,
+                        height: self.height,
                     };
-                }
+                    RenderingContext {
+                        cross: self.cross(j, &pctx),
+                        position: pctx,
+                    }
+                });
+                (x, y, tile.build_image(ctx.as_ref(), scale))
+            });
+            for (x, y, sprite) in sprites {
+                overlay_clipped(
+                    &mut img.as_mut(),
+                    sprite.borrow(),
+                    i64::from(scale * x as u32) - i64::from(scale * vx as u32),
+                    i64::from(scale * y as u32) - i64::from(scale * above as u32),
+                );
             }
         }
-        // loop3 draws the units
+        // then the bridges and lasers, over the buildings
+        let frame = Frame {
+            height: vy + vh,
+            border: 0,
+            left: vx,
+            scale,
+        };
+        let linked = self.tiles.iter().enumerate().filter_map(|(j, tile)| {
+            let build = tile.build()?;
+            let (links, status) = build
+                .power
+                .as_ref()
+                .map_or((&[][..], 0.0), |p| (&p.links[..], p.status));
+            Some((
+                GridPos(j % self.width, j / self.width),
+                build.block,
+                build.extra.link(),
+                links,
+                status,
+            ))
+        });
+        draw_links(
+            &mut img.as_mut(),
+            linked,
+            (self.width, self.height),
+            (vx, vy, vw, vh),
+            frame,
+            |to| {
+                self.get(to.0, to.1)
+                    .and_then(super::map::Tile::build)
+                    .map(|b| b.block.get_size())
+            },
+        );
+        // loop3 draws the team block plans, as ghosts
+        for (_, plans) in &self.plans {
+            for plan in plans {
+                draw_plan_in(img.as_mut(), plan, (vx, vy, vw, vh), scale, 0);
+            }
+        }
+        // loop4 draws the units
         for entity in &self.entities {
-            // bounds checks
             let (x, y) = (
-                entity.state.position.0 as u32,
-                self.height as u32 - entity.state.position.1 as u32 - 1,
+                i64::from(entity.state.position.0 as u32) - vx as i64,
+                (vy + vh) as i64 - i64::from(entity.state.position.1 as u32) - 1,
+            );
+            let px = i64::from(scale.px());
+            overlay_clipped(
+                &mut img.as_mut(),
+                entity.draw(scale).borrow(),
+                px * x,
+                px * y,
             );
-            if x < 10 || x as usize > self.width - 10 || y < 10 || y as usize > self.height - 10 {
+        }
+        opts.fit(img)
+    }
+}
+
+/// overlays `sprite` with its top left at `x`, `y` (in pixels), cutting off what does not fit
+pub(crate) fn overlay_clipped(
+    img: &mut Image<&mut [u8], 3>,
+    sprite: Image<&[u8], 4>,
+    x: i64,
+    y: i64,
+) {
+    let (w, h) = (i64::from(sprite.width()), i64::from(sprite.height()));
+    if x >= 0 && y >= 0 && x + w <= i64::from(img.width()) && y + h <= i64::from(img.height()) {
+        // SAFETY: it fits
+        unsafe { img.overlay_at(&sprite, x as u32, y as u32) };
+        return;
+    }
+    for sy in 0..sprite.height() {
+        for sx in 0..sprite.width() {
+            let (px, py) = (x + i64::from(sx), y + i64::from(sy));
+            if px < 0 || py < 0 || px >= i64::from(img.width()) || py >= i64::from(img.height()) {
                 continue;
             }
-            unsafe {
-                img.as_mut()
-                    .overlay_at(&entity.draw(scale).borrow(), scale * x, scale * y)
-            };
+            // SAFETY: in bounds
+            let [r, g, b, a] = unsafe { sprite.pixel::<u8>(sx, sy) };
+            // like overlay_at
+            if a >= 128 {
+                // SAFETY: in bounds
+                unsafe { img.set_pixel(px as u32, py as u32, [r, g, b]) };
+            }
         }
-        img
     }
 }
 
@@ -297,45 +695,440 @@ fn all_blocks() {
     }
 }
 
+/// the part of a block drawn in its teams color, if it has one
+fn team_region(name: &str, scale: Scale) -> Option<ImageHolder<4>> {
+    macro_rules! regions {
+        ($($k:literal)|+) => {
+            match name {
+                $($k)|+ => Some(load!(concat "team" => name which is [$($k)|+], scale)),
+                _ => None,
+            }
+        };
+    }
+    regions!(
+        "force-projector"
+            | "shield-projector"
+            | "large-shield-projector"
+            | "container"
+            | "vault"
+            | "reinforced-container"
+            | "reinforced-vault"
+            | "core-shard"
+            | "core-foundation"
+            | "core-nucleus"
+            | "core-bastion"
+            | "core-citadel"
+            | "core-acropolis"
+    )
+}
+
+/// puts a teams colors on the picture of a building or unit called `name`:
+/// its team region in the teams color, if it has one, or else a bit of the teams color over all of it (except for derelicts).
+pub(crate) fn team_colored(
+    mut img: ImageHolder<4>,
+    name: &str,
+    team: Team,
+    scale: Scale,
+) -> ImageHolder<4> {
+    if let Some(mut region) = team_region(name, scale) {
+        region.tint(team.color());
+        // SAFETY: the region is as big as the block
+        unsafe { img.overlay(&region) };
+    } else if team != crate::team::DERELICT {
+        let (tr, tg, tb) = team.color();
+        for [r, g, b, _] in img.borrow_mut().chunked_mut() {
+            // a third of the tinted color
+            for (c, t) in [(r, tr), (g, tg), (b, tb)] {
+                *c = (u32::from(*c) * (2 * 255 + u32::from(t)) / (3 * 255)) as u8;
+            }
+        }
+    }
+    img
+}
+
+/// Draws a [`Plan`] translucently, like the game draws ghost blocks.
+/// Plans that would not fit in the image are skipped.
+pub fn draw_plan(
+    img: Image<&mut [u8], 3>,
+    plan: &Plan,
+    (width, height): (usize, usize),
+    scale: Scale,
+) {
+    draw_plan_in(img, plan, (0, 0, width, height), scale, 0);
+}
+
+/// [`draw_plan`], for a render of the `(x, y, width, height)` part of a map, whose rows from `top` (in pixels) are in `img`
+pub(crate) fn draw_plan_in(
+    img: Image<&mut [u8], 3>,
+    plan: &Plan,
+    (vx, vy, width, height): (usize, usize, usize, usize),
+    scale: Scale,
+    top: u32,
+) {
+    let s = plan.block.get_size() as usize;
+    let GridPos(x, y) = plan.position;
+    let (Some(x), Some(y)) = (
+        x.checked_sub((s - 1) / 2 + vx),
+        (vy + height - 1).checked_sub(y + s / 2),
+    ) else {
+        return;
+    };
+    if x + s > width || y + s > height {
+        return;
+    }
+    let ghost = plan.block.image(None, None, plan.rotation, scale);
+    draw_ghost(
+        img,
+        ghost.borrow(),
+        i64::from(scale * x as u32),
+        i64::from(scale * y as u32) - i64::from(top),
+    );
+}
+
+/// draws `ghost` at half opacity, clipped to the image.
+fn draw_ghost(mut img: Image<&mut [u8], 3>, ghost: Image<&[u8], 4>, x: i64, y: i64) {
+    for gy in 0..ghost.height() {
+        for gx in 0..ghost.width() {
+            let (px, py) = (x + i64::from(gx), y + i64::from(gy));
+            if px < 0 || py < 0 || px >= i64::from(img.width()) || py >= i64::from(img.height()) {
+                continue;
+            }
+            // SAFETY: in bounds
+            let [r, g, b, a] = unsafe { ghost.pixel::<u8>(gx, gy) };
+            // half opacity
+            blend(&mut img, px as u32, py as u32, [r, g, b], a as u16 / 2);
+        }
+    }
+}
+
+/// blend a color onto a pixel, with `a` in `0..=255`. the pixel must be in bounds.
+pub(crate) fn blend(img: &mut Image<&mut [u8], 3>, x: u32, y: u32, color: [u8; 3], a: u16) {
+    if a == 0 {
+        return;
+    }
+    debug_assert!(x < img.width() && y < img.height());
+    // SAFETY: callers check bounds
+    let px = unsafe { img.pixel_mut::<u8>(x, y) };
+    for (to, from) in px.iter_mut().zip(color) {
+        *to = ((*to as u16 * (255 - a) + from as u16 * a) / 255) as u8;
+    }
+}
+
+/// draws a sprite with its top left at `x`, `y`, cutting off what does not fit
+fn draw_sprite(img: &mut Image<&mut [u8], 3>, sprite: Image<&[u8], 4>, x: i64, y: i64) {
+    for sy in 0..sprite.height() {
+        for sx in 0..sprite.width() {
+            let (px, py) = (x + i64::from(sx), y + i64::from(sy));
+            if px < 0 || py < 0 || px >= i64::from(img.width()) || py >= i64::from(img.height()) {
+                continue;
+            }
+            // SAFETY: in bounds
+            let [r, g, b, a] = unsafe { sprite.pixel::<u8>(sx, sy) };
+            blend(img, px as u32, py as u32, [r, g, b], u16::from(a));
+        }
+    }
+}
+
+/// where blocks are, in pixels, in a render of something `height` tall (with a border of `border` tiles),
+/// that starts `left` tiles in
+#[derive(Clone, Copy)]
+pub(crate) struct Frame {
+    pub height: usize,
+    pub border: u32,
+    pub left: usize,
+    pub scale: Scale,
+}
+
+impl Frame {
+    /// the center of a block of `size` at `pos`, in pixels
+    fn center(self, GridPos(x, y): GridPos, size: u8) -> (f32, f32) {
+        let (size, off) = (f32::from(size), f32::from((size - 1) / 2));
+        let px = f32::from(self.scale.px());
+        (
+            (x as f32 - self.left as f32 - off + size / 2.0 + self.border as f32) * px,
+            (self.height as f32 - y as f32 + off - size / 2.0 + self.border as f32) * px,
+        )
+    }
+}
+
+/// the blocks that link with bridges drawn between them (`ItemBridge`)
+pub(crate) fn is_bridge(name: &str) -> bool {
+    matches!(
+        name,
+        "bridge-conveyor" | "phase-conveyor" | "bridge-conduit" | "phase-conduit"
+    )
+}
+
+/// the blocks that link with lasers (`PowerNode`). beam nodes link on their own, and draw no lasers.
+pub(crate) fn is_laser_node(name: &str) -> bool {
+    matches!(
+        name,
+        "power-node" | "power-node-large" | "surge-tower" | "beam-link" | "power-source"
+    )
+}
+
+/// a building that may link: where it is, what it is, its bridge link, its power links and its power status
+pub(crate) type Linked<'l> = (
+    GridPos,
+    &'static crate::block::Block,
+    Option<GridPos>,
+    &'l [GridPos],
+    f32,
+);
+
+/// draws the bridges and lasers (of a `w`x`h` map) that cross the `view`, over the buildings.
+/// `size` is the size of the building centered at a tile, if there is one.
+pub(crate) fn draw_links<'l>(
+    img: &mut Image<&mut [u8], 3>,
+    linked: impl IntoIterator<Item = Linked<'l>>,
+    (w, h): (usize, usize),
+    (vx, vy, vw, vh): (usize, usize, usize, usize),
+    frame: Frame,
+    size: impl Fn(GridPos) -> Option<u8>,
+) {
+    // does a link between these cross the viewport
+    let seen = |GridPos(ax, ay): GridPos, GridPos(bx, by): GridPos| {
+        ax.min(bx) < vx + vw + 4
+            && ax.max(bx) + 4 >= vx
+            && ay.min(by) < vy + vh + 4
+            && ay.max(by) + 4 >= vy
+    };
+    let mut lasers = std::collections::HashSet::new();
+    for (pos, block, link, links, status) in linked {
+        let name = block.name();
+        if is_bridge(name) {
+            if let Some(to) = link.filter(|&to @ GridPos(x, y)| x < w && y < h && seen(pos, to)) {
+                draw_bridge(img, name, pos, to, frame, false);
+            }
+        }
+        if !is_laser_node(name) {
+            continue;
+        }
+        for &to in links.iter().filter(|&&to| seen(pos, to)) {
+            let Some(other) = size(to) else {
+                continue;
+            };
+            let (a, b) = ((pos.0, pos.1), (to.0, to.1));
+            if lasers.insert((a.min(b), a.max(b))) {
+                draw_laser(img, (pos, block.get_size()), (to, other), status, frame);
+            }
+        }
+    }
+}
+
+/// draws the bridge between two bridges (`ItemBridge.drawBridge`), over the blocks.
+///
+/// plans (as in schematics) are drawn from center to center with an arrow in the middle,
+/// and buildings from edge to edge with end caps.
+pub(crate) fn draw_bridge(
+    img: &mut Image<&mut [u8], 3>,
+    name: &str,
+    from: GridPos,
+    to: GridPos,
+    frame: Frame,
+    plan: bool,
+) {
+    let s = frame.scale;
+    let px = f32::from(s.px());
+    let (x1, y1) = frame.center(from, 1);
+    let (x2, y2) = frame.center(to, 1);
+    if x1 != x2 && y1 != y2 {
+        // bridges only go straight
+        return;
+    }
+    let horizontal = y1 == y2;
+    // the direction, as clockwise rotations from right (in image coordinates, so y is down)
+    let dir = match (x2 - x1, y2 - y1) {
+        (dx, _) if dx > 0.0 => 0,
+        (_, dy) if dy > 0.0 => 1,
+        (dx, _) if dx < 0.0 => 2,
+        _ => 3,
+    };
+    let bridge = load!(concat "bridge" => name which is ["bridge-conveyor" | "phase-conveyor" | "bridge-conduit" | "phase-conduit"], s);
+    let bridge = bridge.borrow();
+    // bridgeWidth is 6.5 of 8
+    let width = px * 6.5 / 8.0;
+    let inset = if plan { 0.0 } else { px / 2.0 };
+    let (along, across) = if horizontal {
+        (x1.min(x2), y1)
+    } else {
+        (y1.min(y2), x1)
+    };
+    let length = (x2 - x1).abs() + (y2 - y1).abs() - 2.0 * inset;
+    for a in 0..length.max(0.0) as u32 {
+        for c in 0..width as u32 {
+            let u = (a as f32 / length * bridge.width() as f32) as u32;
+            let v = (c as f32 / width * bridge.height() as f32) as u32;
+            // SAFETY: u, v are in bounds
+            let [r, g, b, alpha] = unsafe {
+                bridge.pixel::<u8>(u.min(bridge.width() - 1), v.min(bridge.height() - 1))
+            };
+            let (a, c) = (
+                (along + inset) as i64 + i64::from(a),
+                (across - width / 2.0) as i64 + i64::from(c),
+            );
+            let (x, y) = if horizontal { (a, c) } else { (c, a) };
+            if x >= 0 && y >= 0 && x < i64::from(img.width()) && y < i64::from(img.height()) {
+                blend(img, x as u32, y as u32, [r, g, b], u16::from(alpha));
+            }
+        }
+    }
+    let at = |(x, y): (f32, f32)| ((x - px / 2.0) as i64, (y - px / 2.0) as i64);
+    if plan {
+        let mut arrow = load!(concat "arrow" => name which is ["bridge-conveyor" | "phase-conveyor" | "bridge-conduit" | "phase-conduit"], s);
+        // SAFETY: square
+        unsafe { arrow.rotate(dir) };
+        let (x, y) = at(((x1 + x2) / 2.0, (y1 + y2) / 2.0));
+        draw_sprite(img, arrow.borrow(), x, y);
+    } else {
+        // the ends face each other; the cap of the end sprite is at its bottom
+        for (pos, facing) in [((x1, y1), dir), ((x2, y2), (dir + 2) % 4)] {
+            let mut end = load!(concat "end" => name which is ["bridge-conveyor" | "phase-conveyor" | "bridge-conduit" | "phase-conduit"], s);
+            // SAFETY: square
+            unsafe { end.rotate((facing + 3) % 4) };
+            let (x, y) = at(pos);
+            draw_sprite(img, end.borrow(), x, y);
+        }
+    }
+}
+
+/// draws the laser between two power nodes (or a node and what it powers) (`PowerNode.drawLaser`), over the blocks.
+/// `satisfaction`, in `0..=1`, is how powered the graph is: lasers are white when it is, and yellow when not.
+pub(crate) fn draw_laser(
+    img: &mut Image<&mut [u8], 3>,
+    (from, from_size): (GridPos, u8),
+    (to, to_size): (GridPos, u8),
+    satisfaction: f32,
+    frame: Frame,
+) {
+    let px = f32::from(frame.scale.px());
+    let (x1, y1) = frame.center(from, from_size);
+    let (x2, y2) = frame.center(to, to_size);
+    let (dx, dy) = (x2 - x1, y2 - y1);
+    let len = dx.hypot(dy);
+    if len == 0.0 {
+        return;
+    }
+    let (vx, vy) = (dx / len, dy / len);
+    // from the edges of the blocks (less 1.5 of 8)
+    let (l1, l2) = (
+        (f32::from(from_size) / 2.0 - 1.5 / 8.0) * px,
+        (f32::from(to_size) / 2.0 - 1.5 / 8.0) * px,
+    );
+    if l1 + l2 >= len {
+        return;
+    }
+    let (x1, y1, x2, y2) = (x1 + vx * l1, y1 + vy * l1, x2 - vx * l2, y2 - vy * l2);
+    // laserColor1 (white) to laserColor2 (Pal.powerLight)
+    let t = (1.0 - satisfaction.clamp(0.0, 1.0)) * 0.86;
+    let color = [251.0, 211.0, 103.0].map(|c: f32| (255.0 + (c - 255.0) * t) as u8);
+    // laserWidth is 0.4, for a stroke of 12 (of 8 a tile)
+    let radius = px * 0.6 / 2.0;
+    let (lx, ly) = (x2 - x1, y2 - y1);
+    let l = lx * lx + ly * ly;
+    let clip = |v: f32, max: u32| (v.max(0.0) as u32).min(max);
+    for y in clip(y1.min(y2) - radius, img.height())..clip(y1.max(y2) + radius + 1.0, img.height())
+    {
+        for x in
+            clip(x1.min(x2) - radius, img.width())..clip(x1.max(x2) + radius + 1.0, img.width())
+        {
+            let (cx, cy) = (x as f32 + 0.5, y as f32 + 0.5);
+            let t = (((cx - x1) * lx + (cy - y1) * ly) / l).clamp(0.0, 1.0);
+            let d = (cx - (x1 + lx * t)).hypot(cy - (y1 + ly * t));
+            // solid in the middle, fading out at the edges
+            let a = ((radius - d) / (radius / 2.0)).clamp(0.0, 1.0);
+            blend(img, x, y, color, (a * 255.0) as u16);
+        }
+    }
+}
+
+/// draws the bridges and lasers of a schematic (drawn onto a render with a border of a tile)
+pub(crate) fn draw_schematic_links(img: &mut Image<&mut [u8], 3>, s: &Schematic, frame: Frame) {
+    let mut lasers = std::collections::HashSet::new();
+    for (pos @ GridPos(x, y), p) in s.block_iter() {
+        let at = |(dx, dy): (i32, i32)| {
+            let (x, y) = (x as i32 + dx, y as i32 + dy);
+            (x >= 0 && y >= 0)
+                .then_some(GridPos(x as usize, y as usize))
+                .filter(|&GridPos(x, y)| x < s.width && y < s.height)
+        };
+        match p.get_state() {
+            Some(crate::block::State::Point(Some(link))) if is_bridge(p.block.name()) => {
+                if let Some(to) = at(*link) {
+                    draw_bridge(img, p.block.name(), pos, to, frame, true);
+                }
+            }
+            Some(crate::block::State::Power(links)) if is_laser_node(p.block.name()) => {
+                for &(dx, dy) in links {
+                    let Some(to) = at((i32::from(dx), i32::from(dy))) else {
+                        continue;
+                    };
+                    let Ok(Some(other)) = s.get(to.0, to.1) else {
+                        continue;
+                    };
+                    // each pair once
+                    let (a, b) = ((pos.0, pos.1), (to.0, to.1));
+                    if lasers.insert((a.min(b), a.max(b))) {
+                        draw_laser(
+                            img,
+                            (pos, p.block.get_size()),
+                            (to, other.block.get_size()),
+                            1.0,
+                            frame,
+                        );
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Draws the plans and units of a map, over what [`draw_map_single`] drew.
 pub fn draw_units(
+    map: &mut crate::data::map::MapReader,
+    img: Image<&mut [u8], 3>,
+    size: (u16, u16),
+) -> Result<(), super::map::ReadError> {
+    draw_units_with(map, img, size, &RenderOptions::default())
+}
+
+/// [`draw_units`], over what [`draw_map_single_with`] drew with the same options.
+pub fn draw_units_with(
     map: &mut crate::data::map::MapReader,
     mut img: Image<&mut [u8], 3>,
     size: (u16, u16),
+    opts: &RenderOptions,
 ) -> Result<(), super::map::ReadError> {
     use std::ops::CoroutineState::*;
-    let scale = if size.0 + size.1 < 2000 {
-        Scale::Quarter
-    } else {
-        Scale::Eigth
-    };
+    let (view @ (vx, vy, _, vh), scale) = opts.map_view(size.0 as usize, size.1 as usize);
 
     let mut co = map.entities()?;
-    let n = match Pin::new(&mut co).resume(()) {
-        Yielded(crate::data::map::EntityData::Length(x)) => x,
-        Complete(Err(e)) => return Err(e),
-        _ => unreachable!(),
+    let n = loop {
+        match Pin::new(&mut co).resume(()) {
+            Yielded(crate::data::map::EntityData::Plans(_, plans)) => {
+                for plan in &plans {
+                    draw_plan_in(img.as_mut(), plan, view, scale, 0);
+                }
+            }
+            Yielded(crate::data::map::EntityData::Length(x)) => break x,
+            Complete(Err(e)) => return Err(e),
+            _ => unreachable!(),
+        }
     };
     'out: {
         for _ in 0..n {
             match Pin::new(&mut co).resume(()) {
                 Yielded(crate::data::map::EntityData::Data(entity)) => {
-                    // bounds checks
                     let (x, y) = (
-                        entity.state.position.0 as u32,
-                        size.1 as u32 - entity.state.position.1 as u32 - 1,
+                        i64::from(entity.state.position.0 as u32) - vx as i64,
+                        (vy + vh) as i64 - i64::from(entity.state.position.1 as u32) - 1,
                     );
-                    if x < 10
-                        || x as usize > size.0 as usize - 10
-                        || y < 10
-                        || y as usize > size.1 as usize - 10
-                    {
-                        continue;
-                    }
-                    unsafe {
-                        img.as_mut()
-                            .overlay_at(&entity.draw(scale).borrow(), scale * x, scale * y)
-                    };
+                    let px = i64::from(scale.px());
+                    overlay_clipped(&mut img, entity.draw(scale).borrow(), px * x, px * y);
                 }
+                // nothing to draw
+                Yielded(crate::data::map::EntityData::Raw(_)) => (),
                 Complete(Err(e)) => return Err(e),
                 Complete(Ok(())) => break 'out,
                 x => unreachable!("{x:?}"),
@@ -349,39 +1142,119 @@ pub fn draw_units(
     Ok(())
 }
 
+/// a block as [`ThinMapData`] has it
+pub(crate) type ThinTile = Option<(&'static crate::block::Block, Rotation, Team)>;
+
+/// the sprite of the block at `(x, y)` of a `w`x`h` map (which sees its neighbors through `get`),
+/// and where it goes (in pixels, from the top left of the map)
+pub(crate) fn thin_block_sprite(
+    (b, r, team): (&'static crate::block::Block, Rotation, Team),
+    (x, y): (usize, usize),
+    (w, h): (usize, usize),
+    get: &impl Fn(usize, usize) -> Option<(&'static crate::block::Block, Rotation)>,
+    scale: Scale,
+) -> (ImageHolder<4>, (i64, i64)) {
+    let s = b.get_size();
+    let ix = x
+        - (match s {
+            1 | 2 => 0,
+            3 | 4 => 1,
+            5 | 6 => 2,
+            7 | 8 => 3,
+            9 => 4,
+            // SAFETY: no block too big
+            _ => unsafe { std::hint::unreachable_unchecked() },
+        }) as usize;
+    let iy = h
+        - y
+        - 1
+        - (match s {
+            1 => 0,
+            2 | 3 => 1,
+            4 | 5 => 2,
+            6 | 7 => 3,
+            8 | 9 => 4,
+            // SAFETY: no block too big
+            _ => unsafe { std::hint::unreachable_unchecked() },
+        }) as usize;
+    let ctx = b.wants_context().then(|| RenderingContext {
+        cross: [
+            if y + 1 < h { get(x, y + 1) } else { None },
+            if x + 1 < w { get(x + 1, y) } else { None },
+            if y > 0 { get(x, y - 1) } else { None },
+            if x > 0 { get(x - 1, y) } else { None },
+        ],
+        position: PositionContext {
+            position: GridPos(ix, iy),
+            width: w,
+            height: h,
+        },
+    });
+    (
+        team_colored(b.image(None, ctx.as_ref(), r, scale), b.name(), team, scale),
+        (i64::from(scale * ix as u32), i64::from(scale * iy as u32)),
+    )
+}
+
 /// Draws a map in a single pass.
-/// This is quite fast, but, unfortunately, has no memory, so conveyors will be drawing imporperly. As will sorters.
+/// Only a few rows of blocks are held at a time, with the rows around them, so conveyors and the like can still see their neighbors.
 ///
 /// Reader must have read to the map section.
 /// Will walk through the map section. use [`draw_units`] after, if you like.
+#[allow(clippy::type_complexity)]
 pub fn draw_map_single(
     map: &mut crate::data::map::MapReader,
+) -> Result<(Image<Box<[u8]>, 3>, (u16, u16)), super::map::ReadError> {
+    draw_map_single_with(map, &RenderOptions::default())
+}
+
+/// [`draw_map_single`], with options.
+/// use [`draw_units_with`] after (with the same options), if you like, and then [`RenderOptions::fit`].
+#[allow(clippy::type_complexity)]
+pub fn draw_map_single_with(
+    map: &mut crate::data::map::MapReader,
+    opts: &RenderOptions,
 ) -> Result<(Image<Box<[u8]>, 3>, (u16, u16)), super::map::ReadError> {
     use std::ops::CoroutineState::*;
-    let mut co = map.thin_map()?;
+    let mut co = map.thin_map_within(|w, h| opts.map_around(w, h))?;
     let (w, h) = match Pin::new(&mut co).resume(()) {
-        Yielded(ThinMapData::Init { width, height }) => (width, height),
+        Yielded(ThinMapData::Init { width, height }) => (width as usize, height as usize),
         Complete(Err(x)) => return Err(x),
         _ => unreachable!(),
     };
-    let scale = if w + h < 2000 {
-        Scale::Quarter
-    } else {
-        Scale::Eigth
+    let ((vx, vy, vw, vh), scale) = opts.map_view(w, h);
+    if vw == 0 || vh == 0 {
+        // no tiles: the map section ends here
+        return match Pin::new(&mut co).resume(()) {
+            Complete(Ok(())) => Ok((nothing(opts.background).boxed(), (w as u16, h as u16))),
+            Complete(Err(x)) => Err(x),
+            f => unreachable!("{f:?}"),
+        };
+    }
+    // the rows above the viewport
+    let above = h - vy - vh;
+    let mut canvas = match opts.background {
+        Background::Floor => Some(uninit::Image::<_, 3>::new(
+            (scale * vw as u32).try_into().unwrap(),
+            (scale * vh as u32).try_into().unwrap(),
+        )),
+        Background::Color(..) => None,
     };
-    let mut img = uninit::Image::<_, 3>::new(
-        (scale * w as u32).try_into().unwrap(),
-        (scale * h as u32).try_into().unwrap(),
-    );
     // loop1 draws the floor
-    for y in 0..w {
-        for x in 0..h {
+    for y in 0..h {
+        for x in 0..w {
             let (floor, ore) = match Pin::new(&mut co).resume(()) {
                 Yielded(ThinMapData::Tile { floor, ore }) => (floor, ore),
                 Complete(Err(x)) => return Err(x),
                 _ => unreachable!(),
             };
-            let y = h - y - 1;
+            let Some(img) = canvas.as_mut() else {
+                continue;
+            };
+            if !(vx..vx + vw).contains(&x) || !(vy..vy + vh).contains(&y) {
+                continue;
+            }
+            let (x, y) = (x - vx, h - y - 1 - above);
             // println!("draw {tile:?} ({x}, {y})");
             unsafe {
                 img.overlay_at(
@@ -401,75 +1274,326 @@ pub fn draw_map_single(
             }
         }
     }
-    let mut img = unsafe { img.assume_init() }.boxed();
+    let mut img = match (canvas, opts.background) {
+        (Some(img), _) => unsafe { img.assume_init() }.boxed(),
+        (None, Background::Color(r, g, b)) => {
+            solid(scale * vw as u32, scale * vh as u32, (r, g, b)).boxed()
+        }
+        (None, Background::Floor) => unreachable!(),
+    };
+    // rows are drawn this many at a time (with one call to [`par::map`])
+    const BATCH: usize = 16;
+    // the row below a batch, the batch, and the row above; indexed by `y % RING`
+    const RING: usize = BATCH + 2;
+    let mut rows: Box<[ThinTile]> = vec![None; RING * w].into_boxed_slice();
+    // draws rows `ys`, once the row above them has been read
+    let mut draw = |rows: &[ThinTile], ys: std::ops::Range<usize>| {
+        let get = |x: usize, y: usize| rows[(y % RING) * w + x].map(|(b, r, _)| (b, r));
+        let tiles = ys
+            // the biggest blocks reach 4 tiles past their center
+            .filter(|&y| y + 4 >= vy && y < vy + vh + 4)
+            .flat_map(|y| {
+                (vx.saturating_sub(4)..(vx + vw + 4).min(w))
+                    .filter_map(move |x| Some(((x, y), rows[(y % RING) * w + x]?)))
+            })
+            .collect::<Vec<_>>();
+        let (left, top) = (
+            i64::from(scale * vx as u32),
+            i64::from(scale * above as u32),
+        );
+        for (sprite, (x, y)) in par::map(&tiles, |&(at, tile)| {
+            thin_block_sprite(tile, at, (w, h), &get, scale)
+        }) {
+            overlay_clipped(&mut img.as_mut(), sprite.borrow(), x - left, y - top);
+        }
+    };
     let mut i = 0;
-    while i < (w as usize * h as usize) {
-        let mut draw = |i, r, b: &'static crate::block::Block| {
-            let x = i % w as usize;
-            let y = i / w as usize;
-            let y = h as usize - y - 1;
-            let s = b.get_size();
-            let x = x
-                - (match s {
-                    1 | 2 => 0,
-                    3 | 4 => 1,
-                    5 | 6 => 2,
-                    7 | 8 => 3,
-                    9 => 4,
-                    // SAFETY: no block too big
-                    _ => unsafe { std::hint::unreachable_unchecked() },
-                }) as usize;
-            let y = y
-                - (match s {
-                    1 => 0,
-                    2 | 3 => 1,
-                    4 | 5 => 2,
-                    6 | 7 => 3,
-                    8 | 9 => 4,
-                    // SAFETY: no block too big
-                    _ => unsafe { std::hint::unreachable_unchecked() },
-                }) as usize;
-            let ctx = b.wants_context().then(|| {
-                let pctx = PositionContext {
-                    position: GridPos(x, y),
-                    width: w as usize,
-                    height: h as usize,
-                };
-                RenderingContext {
-                    cross: [None; 4], // woe
-                    position: pctx,
-                }
-            });
-            unsafe {
-                img.as_mut().overlay_at(
-                    &b.image(None, ctx.as_ref(), r, scale).borrow(),
-                    scale * x as u32,
-                    scale * y as u32,
-                )
-            };
-        };
+    let mut drawn = 0;
+    // runs may span rows, so every tile goes through here
+    let mut put = |i: &mut usize, b| {
+        if *i == w * h {
+            return;
+        }
+        rows[(*i / w % RING) * w + *i % w] = b;
+        *i += 1;
+        if *i == (drawn + BATCH + 1) * w {
+            draw(&rows, drawn..drawn + BATCH);
+            drawn += BATCH;
+        }
+    };
+    while i < w * h {
         match Pin::new(&mut co).resume(()) {
             Yielded(ThinMapData::Bloc(ThinBloc::None(n))) => {
-                i += n as usize;
+                for _ in 0..=n {
+                    put(&mut i, None);
+                }
             }
-            Yielded(ThinMapData::Bloc(ThinBloc::Build(r, bloc))) => {
-                draw(i, r, bloc);
+            Yielded(ThinMapData::Bloc(ThinBloc::Build(r, bloc, team))) => {
+                put(&mut i, Some((bloc, r, team)));
             }
             Yielded(ThinMapData::Bloc(ThinBloc::Many(bloc, n))) => {
-                for i in i..=i + n as usize {
-                    draw(i, Rotation::Up, bloc);
+                for _ in 0..=n {
+                    put(&mut i, Some((bloc, Rotation::Up, crate::team::DERELICT)));
                 }
-                i += n as usize;
             }
             Complete(Err(x)) => return Err(x),
             x => unreachable!("{x:?}"),
         }
-        i += 1;
     }
     match Pin::new(&mut co).resume(()) {
         Complete(Ok(())) => (),
         f => unreachable!("{f:?}"),
     };
+    // the top rows have nothing above them
+    draw(&rows, drawn.min(h)..h);
+    drop(co);
+    // then the bridges and lasers, over the buildings
+    let links = map.take_thin_links();
+    draw_links(
+        &mut img.as_mut(),
+        links
+            .links
+            .iter()
+            .map(|(pos, block, link, links, status)| (*pos, *block, *link, &links[..], *status)),
+        (w, h),
+        (vx, vy, vw, vh),
+        Frame {
+            height: vy + vh,
+            border: 0,
+            left: vx,
+            scale,
+        },
+        |to| links.size(to, w),
+    );
+
+    Ok((img, (w as u16, h as u16)))
+}
 
-    Ok((img, (w, h)))
+#[test]
+fn map_single() {
+    use crate::block::{BRIDGE_CONVEYOR, CONVEYOR, CORE_SHARD, POWER_NODE, STONE_WALL};
+    use crate::data::map::{Extra, Map, MapReader, Power};
+    use crate::data::{DataRead, DataWrite, Serializable};
+    use crate::team;
+    let mut m = Map::filled(9, 5, crate::block::content::Type::Stone);
+    m.set_block(7, 2, &CORE_SHARD, Rotation::Up, team::CRUX)
+        .unwrap()
+        .power = Some(Power {
+        links: vec![GridPos(5, 4)],
+        status: 1.0,
+    });
+    // ─┐
+    //  └─┘
+    for (x, y, r) in [
+        (0, 3, Rotation::Right),
+        (1, 3, Rotation::Down),
+        (1, 2, Rotation::Right),
+        (2, 2, Rotation::Right),
+        (3, 2, Rotation::Up),
+        (3, 3, Rotation::Up),
+    ] {
+        m.set_block(x, y, &CONVEYOR, r, team::SHARDED).unwrap();
+    }
+    for x in 0..5 {
+        m.set_block(x, 0, &STONE_WALL, Rotation::Up, team::DERELICT)
+            .unwrap();
+    }
+    // a bridge, and a power node linked to the core
+    let unlinked = {
+        let mut m = m.clone();
+        for (x, block) in [
+            (0, &BRIDGE_CONVEYOR),
+            (3, &BRIDGE_CONVEYOR),
+            (5, &POWER_NODE),
+        ] {
+            m.set_block(x, 4, block, Rotation::Up, team::SHARDED)
+                .unwrap();
+        }
+        m.render()
+    };
+    m.set_block(0, 4, &BRIDGE_CONVEYOR, Rotation::Up, team::SHARDED)
+        .unwrap()
+        .extra = Extra::linked(&BRIDGE_CONVEYOR, GridPos(3, 4));
+    m.set_block(3, 4, &BRIDGE_CONVEYOR, Rotation::Up, team::SHARDED)
+        .unwrap();
+    m.set_block(5, 4, &POWER_NODE, Rotation::Up, team::SHARDED)
+        .unwrap()
+        .power = Some(Power {
+        links: vec![GridPos(7, 2)],
+        status: 1.0,
+    });
+    assert!(**m.render().buffer() != **unlinked.buffer());
+    let mut buff = DataWrite::default();
+    m.serialize(&mut buff).unwrap();
+    for opts in [
+        RenderOptions::default(),
+        RenderOptions {
+            scale: Some(Scale::Half),
+            viewport: Some((2, 1, 6, 3)),
+            background: Background::Color(0, 0, 255),
+            ..Default::default()
+        },
+        RenderOptions {
+            viewport: Some((5, 2, 100, 100)),
+            max_size: Some(16),
+            ..Default::default()
+        },
+    ] {
+        let mut r = MapReader::new(&mut DataRead::new(buff.get_written())).unwrap();
+        r.header().unwrap();
+        r.version().unwrap();
+        r.tags().unwrap();
+        r.content().unwrap();
+        let (img, size) = draw_map_single_with(&mut r, &opts).unwrap();
+        assert_eq!(size, (9, 5));
+        let img = Image::build(img.width(), img.height()).buf(img.take_buffer().into_vec());
+        assert!(**opts.fit(img).buffer() == **m.render_with(&opts).buffer());
+    }
+}
+
+#[test]
+fn map_single_batches() {
+    use crate::block::{CONVEYOR, CORE_SHARD};
+    use crate::data::map::{Map, MapReader};
+    use crate::data::{DataRead, DataWrite, Serializable};
+    use crate::team;
+    // taller than a few batches, with blocks that look at the rows around them on every row
+    let mut m = Map::filled(5, 40, crate::block::content::Type::Stone);
+    for y in 0..40 {
+        let r = if y % 3 == 0 {
+            Rotation::Right
+        } else {
+            Rotation::Up
+        };
+        m.set_block(y % 2, y, &CONVEYOR, r, team::SHARDED).unwrap();
+    }
+    m.set_block(3, 17, &CORE_SHARD, Rotation::Up, team::CRUX)
+        .unwrap();
+    let mut buff = DataWrite::default();
+    m.serialize(&mut buff).unwrap();
+    for opts in [
+        RenderOptions::default(),
+        RenderOptions {
+            viewport: Some((1, 10, 3, 25)),
+            ..Default::default()
+        },
+    ] {
+        let mut r = MapReader::new(&mut DataRead::new(buff.get_written())).unwrap();
+        r.header().unwrap();
+        r.version().unwrap();
+        r.tags().unwrap();
+        r.content().unwrap();
+        let (img, _) = draw_map_single_with(&mut r, &opts).unwrap();
+        assert!(**img.buffer() == **m.render_with(&opts).buffer());
+    }
+}
+
+#[test]
+fn empty_view() {
+    let opts = RenderOptions {
+        viewport: Some((2, 1, 6, 3)),
+        ..Default::default()
+    };
+    assert_eq!(opts.view(0, 5), (0, 0, 0, 0));
+    assert_eq!(opts.view(5, 0), (0, 0, 0, 0));
+    assert_eq!(opts.view(4, 4), (2, 1, 2, 3));
+    assert_eq!(RenderOptions::default().view(0, 5), (0, 0, 0, 0));
+
+    use crate::data::map::{Map, MapReader};
+    use crate::data::tiled::TiledMap;
+    use crate::data::{DataRead, DataWrite, Serializable};
+    for (w, h) in [(0, 5), (5, 0), (0, 0)] {
+        let m = Map::filled(w, h, crate::block::content::Type::Stone);
+        for opts in [
+            RenderOptions::default(),
+            RenderOptions {
+                background: Background::Color(0, 0, 255),
+                ..opts
+            },
+        ] {
+            let img = m.render_with(&opts);
+            assert_eq!((img.width(), img.height()), (1, 1));
+
+            let mut buff = DataWrite::default();
+            m.serialize(&mut buff).unwrap();
+            let reader = || {
+                let mut r = MapReader::new(&mut DataRead::new(buff.get_written())).unwrap();
+                r.header().unwrap();
+                r.version().unwrap();
+                r.tags().unwrap();
+                r.content().unwrap();
+                r
+            };
+            let mut r = reader();
+            let (mut img, size) = draw_map_single_with(&mut r, &opts).unwrap();
+            assert_eq!(size, (w as u16, h as u16));
+            draw_units_with(&mut r, img.as_mut(), size, &opts).unwrap();
+
+            let tiled = TiledMap::read(&mut reader(), &opts).unwrap();
+            assert_eq!(tiled.size(), (0, 0));
+            assert_eq!(tiled.bands(4).count(), 0);
+        }
+    }
+}
+
+#[test]
+fn team_colors() {
+    use crate::block::{CORE_SHARD, ROUTER};
+    use crate::data::map::Map;
+    use crate::team;
+    let mut m = Map::filled(6, 3, crate::block::content::Type::Stone);
+    m.set_block(0, 0, &ROUTER, Rotation::Up, team::SHARDED)
+        .unwrap();
+    m.set_block(1, 0, &ROUTER, Rotation::Up, team::CRUX)
+        .unwrap();
+    m.set_block(3, 1, &CORE_SHARD, Rotation::Up, team::SHARDED)
+        .unwrap();
+    let opts = |x| RenderOptions {
+        scale: Some(Scale::Full),
+        viewport: Some((x, 0, 1, 1)),
+        ..Default::default()
+    };
+    let (sharded, crux) = (m.render_with(&opts(0)), m.render_with(&opts(1)));
+    assert!(sharded.buffer() != crux.buffer());
+    // the cores team region
+    let before = m.render_with(&opts(3));
+    m.remove(3, 1).unwrap();
+    m.set_block(3, 1, &CORE_SHARD, Rotation::Up, team::CRUX)
+        .unwrap();
+    assert!(before.buffer() != m.render_with(&opts(3)).buffer());
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn parallel() {
+    use crate::block::{CONVEYOR, CORE_SHARD, ROUTER, SORTER, TITANIUM_CONVEYOR};
+    use crate::data::{dynamic::DynData, map::Map};
+    use crate::team;
+    let blocks = [&CONVEYOR, &ROUTER, &TITANIUM_CONVEYOR, &SORTER];
+    let rotations = [
+        Rotation::Up,
+        Rotation::Right,
+        Rotation::Down,
+        Rotation::Left,
+    ];
+    let mut s = Schematic::new(30, 30);
+    let mut m = Map::filled(30, 30, Type::Stone);
+    for y in 0..30 {
+        for x in 0..30 {
+            let (b, r) = (blocks[(x * 7 + y) % 4], rotations[(x + y * 3) % 4]);
+            s.set(x, y, b, DynData::Empty, r).unwrap();
+            if !(10..13).contains(&x) || !(10..13).contains(&y) {
+                m.set_block(x, y, b, r, team::SHARDED).unwrap();
+            }
+        }
+    }
+    m.set_block(11, 11, &CORE_SHARD, Rotation::Up, team::CRUX)
+        .unwrap();
+    let render = |threads| {
+        par::THREADS.with(|t| t.set(Some(threads)));
+        (s.render(), m.render())
+    };
+    let (serial, parallel) = (render(1), render(8));
+    assert!(serial.0.buffer() == parallel.0.buffer());
+    assert!(serial.1.buffer() == parallel.1.buffer());
 }
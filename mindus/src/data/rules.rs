@@ -0,0 +1,337 @@
+//! the map rules (the `rules` tag)
+//!
+//! the game only writes the fields that differ from the defaults, so do we.
+//! fields that arent typed here are kept in `other`, and written back as they were read.
+use thiserror::Error;
+
+use super::json::{self, Value};
+use crate::team::{self, Team};
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error(transparent)]
+    Json(#[from] json::Error),
+    #[error("expected a object")]
+    Object,
+    #[error("invalid value for field {0:?}")]
+    Field(&'static str),
+}
+
+/// a value of a rule
+trait Field: Sized {
+    fn from_value(v: &Value) -> Option<Self>;
+    fn to_value(&self) -> Value;
+}
+
+impl Field for bool {
+    fn from_value(v: &Value) -> Option<Self> {
+        v.as_bool()
+    }
+
+    fn to_value(&self) -> Value {
+        Value::Bool(*self)
+    }
+}
+
+impl Field for f32 {
+    fn from_value(v: &Value) -> Option<Self> {
+        v.as_f64().map(|n| n as f32)
+    }
+
+    fn to_value(&self) -> Value {
+        // go through the shortest representation, so 1.2 stays 1.2
+        Value::Number(self.to_string().parse().unwrap_or(f64::from(*self)))
+    }
+}
+
+impl Field for i32 {
+    fn from_value(v: &Value) -> Option<Self> {
+        v.as_f64().map(|n| n as i32)
+    }
+
+    fn to_value(&self) -> Value {
+        Value::Number(f64::from(*self))
+    }
+}
+
+impl Field for String {
+    fn from_value(v: &Value) -> Option<Self> {
+        v.as_str().map(str::to_string)
+    }
+
+    fn to_value(&self) -> Value {
+        Value::String(self.clone())
+    }
+}
+
+/// teams are written as their id
+impl Field for Team {
+    fn from_value(v: &Value) -> Option<Self> {
+        v.as_f64().map(|n| Team::of(n as u8))
+    }
+
+    fn to_value(&self) -> Value {
+        Value::Number(f64::from(u8::from(*self)))
+    }
+}
+
+impl Field for Env {
+    fn from_value(v: &Value) -> Option<Self> {
+        v.as_f64().map(|n| Env(n as i64 as u32))
+    }
+
+    fn to_value(&self) -> Value {
+        Value::Number(f64::from(self.0 as i32))
+    }
+}
+
+impl<T: Field> Field for Vec<T> {
+    fn from_value(v: &Value) -> Option<Self> {
+        match v {
+            Value::Array(a) => a.iter().map(T::from_value).collect(),
+            _ => None,
+        }
+    }
+
+    fn to_value(&self) -> Value {
+        Value::Array(self.iter().map(T::to_value).collect())
+    }
+}
+
+impl<T: Field> Field for Option<T> {
+    fn from_value(v: &Value) -> Option<Self> {
+        match v {
+            Value::Null => Some(None),
+            v => T::from_value(v).map(Some),
+        }
+    }
+
+    fn to_value(&self) -> Value {
+        self.as_ref().map_or(Value::Null, T::to_value)
+    }
+}
+
+/// team rules are a object keyed by team id
+impl Field for Vec<(Team, TeamRules)> {
+    fn from_value(v: &Value) -> Option<Self> {
+        let Value::Object(o) = v else { return None };
+        o.iter()
+            .map(|(k, v)| Some((Team::of(k.parse().ok()?), TeamRules::from_value(v)?)))
+            .collect()
+    }
+
+    fn to_value(&self) -> Value {
+        Value::Object(
+            self.iter()
+                .map(|(t, r)| (u8::from(*t).to_string(), r.to_value()))
+                .collect(),
+        )
+    }
+}
+
+macro_rules! rules {
+    ($(#[$attr:meta])* pub struct $name:ident { $($(#[doc = $doc:literal])* $field:ident: $ty:ty = $default:expr => $key:literal,)+ }) => {
+        $(#[$attr])*
+        #[derive(Clone, Debug, PartialEq)]
+        pub struct $name {
+            $($(#[doc = $doc])* pub $field: $ty,)+
+            /// the fields that are not typed, as read
+            pub other: Vec<(String, Value)>,
+        }
+
+        impl Default for $name {
+            fn default() -> Self {
+                Self {
+                    $($field: $default,)+
+                    other: vec![],
+                }
+            }
+        }
+
+        impl $name {
+            /// read from a json object
+            pub fn from_json(v: &Value) -> Result<Self, Error> {
+                let Value::Object(o) = v else { return Err(Error::Object) };
+                let mut me = Self::default();
+                for (k, v) in o {
+                    match &**k {
+                        $($key => me.$field = Field::from_value(v).ok_or(Error::Field($key))?,)+
+                        _ => me.other.push((k.clone(), v.clone())),
+                    }
+                }
+                Ok(me)
+            }
+
+            /// write to a json object, leaving out defaults
+            #[must_use]
+            pub fn to_json(&self) -> Value {
+                let default = Self::default();
+                let mut o = vec![];
+                $(if self.$field != default.$field {
+                    o.push(($key.to_string(), self.$field.to_value()));
+                })+
+                o.extend(self.other.iter().cloned());
+                Value::Object(o)
+            }
+        }
+
+        impl Field for $name {
+            fn from_value(v: &Value) -> Option<Self> {
+                Self::from_json(v).ok()
+            }
+
+            fn to_value(&self) -> Value {
+                self.to_json()
+            }
+        }
+    };
+}
+
+/// environment flags (`Env.java`)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Env(pub u32);
+
+impl Env {
+    pub const NONE: Self = Self(0);
+    pub const TERRESTRIAL: Self = Self(1);
+    pub const SPACE: Self = Self(1 << 1);
+    pub const UNDERWATER: Self = Self(1 << 2);
+    pub const SPORES: Self = Self(1 << 3);
+    pub const SCORCHING: Self = Self(1 << 4);
+    pub const GROUND_OIL: Self = Self(1 << 5);
+    pub const GROUND_WATER: Self = Self(1 << 6);
+    pub const OXYGEN: Self = Self(1 << 7);
+    pub const ANY: Self = Self(u32::MAX);
+    /// the default environment of a map (`Vars.defaultEnv`)
+    pub const DEFAULT: Self = Self(
+        Self::TERRESTRIAL.0
+            | Self::SPORES.0
+            | Self::GROUND_OIL.0
+            | Self::GROUND_WATER.0
+            | Self::OXYGEN.0,
+    );
+
+    #[must_use]
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl std::ops::BitOr for Env {
+    type Output = Self;
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+rules! {
+    /// rules for a single team (`Rules.TeamRule`)
+    pub struct TeamRules {
+        /// enemy rts ai, which commands units
+        rts_ai: bool = false => "rtsAi",
+        rts_min_weight: f32 = 1.2 => "rtsMinWeight",
+        rts_min_squad: i32 = 4 => "rtsMinSquad",
+        /// builds enemy bases
+        build_ai: bool = false => "buildAi",
+        build_ai_tier: f32 = 1.0 => "buildAiTier",
+        infinite_resources: bool = false => "infiniteResources",
+        infinite_ammo: bool = false => "infiniteAmmo",
+        cheat: bool = false => "cheat",
+        unit_build_speed_multiplier: f32 = 1.0 => "unitBuildSpeedMultiplier",
+        unit_cost_multiplier: f32 = 1.0 => "unitCostMultiplier",
+        unit_damage_multiplier: f32 = 1.0 => "unitDamageMultiplier",
+        unit_health_multiplier: f32 = 1.0 => "unitHealthMultiplier",
+        unit_crash_damage_multiplier: f32 = 1.0 => "unitCrashDamageMultiplier",
+        block_health_multiplier: f32 = 1.0 => "blockHealthMultiplier",
+        block_damage_multiplier: f32 = 1.0 => "blockDamageMultiplier",
+        build_speed_multiplier: f32 = 1.0 => "buildSpeedMultiplier",
+    }
+}
+
+rules! {
+    /// a group of units spawned by waves (`SpawnGroup.java`)
+    pub struct SpawnGroup {
+        /// the unit name
+        unit: String = "dagger".to_string() => "type",
+        /// first wave (0 indexed)
+        begin: i32 = 0 => "begin",
+        /// last wave
+        end: i32 = i32::MAX => "end",
+        /// waves between spawns
+        spacing: i32 = 1 => "spacing",
+        max: i32 = 100 => "max",
+        /// waves per extra unit
+        scaling: f32 = 0.0 => "scaling",
+        shields: f32 = 0.0 => "shields",
+        shield_scaling: f32 = 0.0 => "shieldScaling",
+        unit_amount: i32 = 1 => "amount",
+        /// status effect name
+        effect: Option<String> = None => "effect",
+        /// packed spawn position, -1 for all spawns
+        spawn: i32 = -1 => "spawn",
+    }
+}
+
+rules! {
+    /// the map rules (`Rules.java`).
+    /// ```
+    /// # use mindus::data::rules::*;
+    /// let mut r = Rules::parse("{waves:true,spawns:[{type:flare,end:10}],bannedBlocks:[router]}").unwrap();
+    /// assert!(r.waves);
+    /// assert_eq!(r.spawns.as_ref().unwrap()[0].end, 10);
+    /// r.wave_spacing = 60.0;
+    /// assert_eq!(r.to_string(), "{waves:true,waveSpacing:60,spawns:[{type:flare,end:10}],bannedBlocks:[router]}");
+    /// ```
+    pub struct Rules {
+        // waves
+        waves: bool = false => "waves",
+        /// count down to the next wave
+        wave_timer: bool = true => "waveTimer",
+        /// allow skipping waves
+        wave_sending: bool = true => "waveSending",
+        /// wait for the enemies to die before counting down
+        wait_enemies: bool = false => "waitEnemies",
+        /// ticks between waves
+        wave_spacing: f32 = 2.0 * 60.0 * 60.0 => "waveSpacing",
+        initial_wave_spacing: f32 = 0.0 => "initialWaveSpacing",
+        /// wave to win at, 0 to disable
+        win_wave: i32 = 0 => "winWave",
+        wave_team: Team = team::CRUX => "waveTeam",
+        /// the wave spawn groups. [`None`] for the default waves.
+        spawns: Option<Vec<SpawnGroup>> = None => "spawns",
+        // modes
+        attack_mode: bool = false => "attackMode",
+        pvp: bool = false => "pvp",
+        editor: bool = false => "editor",
+        default_team: Team = team::SHARDED => "defaultTeam",
+        unit_cap: i32 = 0 => "unitCap",
+        // bans
+        /// names of the banned blocks
+        banned_blocks: Vec<String> = vec![] => "bannedBlocks",
+        /// names of the banned units
+        banned_units: Vec<String> = vec![] => "bannedUnits",
+        teams: Vec<(Team, TeamRules)> = vec![] => "teams",
+        // environment
+        env: Env = Env::DEFAULT => "env",
+        lighting: bool = false => "lighting",
+        fog: bool = false => "fog",
+        /// fog that is revealed once, and stays revealed (see [`StaticFog`](crate::data::map::StaticFog))
+        static_fog: bool = true => "staticFog",
+        /// the name of the planet
+        planet: String = "serpulo".to_string() => "planet",
+    }
+}
+
+impl Rules {
+    /// parse the `rules` tag
+    pub fn parse(s: &str) -> Result<Self, Error> {
+        Self::from_json(&Value::parse(s)?)
+    }
+}
+
+/// writes the `rules` tag
+impl std::fmt::Display for Rules {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_json())
+    }
+}
@@ -4,7 +4,7 @@ use std::collections::HashMap;
 use std::fmt::{self, Write};
 use thiserror::Error;
 
-use crate::block::ratios::{Io, IoBuilder};
+use crate::block::ratios::{Ground, Io, IoBuilder};
 use crate::block::{self, Block, Rotation, State, BLOCK_REGISTRY};
 use crate::data::base64;
 use crate::data::dynamic::{self, DynData};
@@ -23,7 +23,7 @@ pub const MAX_BLOCKS: u32 = 1024 * 1024;
 pub struct Placement {
     pub block: &'static Block,
     pub rot: Rotation,
-    state: Option<State>,
+    pub(crate) state: Option<State>,
 }
 
 impl PartialEq for Placement {
@@ -167,12 +167,22 @@ impl Schematic {
         ]
     }
 
-    /// Ratios of this schematic.
+    /// Ratios of this schematic, with the net power balance.
     /// ```
     /// # use mindus::Schematic;
     /// # use mindus::block::ratios::ratios;
     /// assert_eq!(Schematic::deserialize_base64("bXNjaAF4nEWMSw7CMAxEh9REVSqx5hKcCLFISxaR0o9Sg8rtSTpFePPkmWfDwTWQyY8B5z75VdE9wzrkuGicJwA2+T6kFeb+aNDtym2MW8i4LJ/sNWo4dje8ksa31zmXuyv+Y4BTgRD2iIi9M+xM7WrUgnoNhYpQESpCxfKLrUo9FsISLX6vKgwhhCVK+wX5/BtM").unwrap().ratios(),
-    ///     ratios![[Coal: 5.25, Lead: 10.5, Sand: 10.5, Water: 180] => [BlastCompound: 5.25, SporePod: 0.75]]);
+    ///     ratios![[Coal: 5.25, Lead: 10.5, Sand: 10.5, Water: 180, Power: 1052] => [BlastCompound: 5.25, SporePod: 0.75]]);
+    /// ```
+    /// power is a resource too:
+    /// ```
+    /// # use mindus::Schematic;
+    /// # use mindus::block::{ratios::Resource, SILICON_SMELTER, SOLAR_PANEL, BATTERY};
+    /// let mut s = Schematic::new(4, 4);
+    /// s.put(1, 1, &SILICON_SMELTER).put(3, 3, &SOLAR_PANEL).put(3, 2, &BATTERY);
+    /// let io = s.ratios();
+    /// assert_eq!(io.net(Resource::Power), -24.0);
+    /// assert_eq!(io.net(Resource::Battery), 1000.0);
     /// ```
     pub fn ratios(&self) -> Io {
         let mut io = IoBuilder::default();
@@ -182,6 +192,36 @@ impl Schematic {
         io.into()
     }
 
+    /// [`Schematic::ratios`], as if every tile were `ground`, so drills, pumps and extractors make something.
+    /// see [`Block::io_on`].
+    /// ```
+    /// # use mindus::Schematic;
+    /// # use mindus::block::{content::Type, ratios::{Ground, Resource}, LASER_DRILL, MECHANICAL_PUMP};
+    /// # use mindus::item::Type::Titanium;
+    /// # use mindus::fluid::Type::Water;
+    /// let mut s = Schematic::new(4, 3);
+    /// s.put(1, 1, &LASER_DRILL).put(3, 0, &MECHANICAL_PUMP);
+    /// let io = s.ratios_on(Ground::ore(Type::OreTitanium), false);
+    /// // nine tiles of titanium, at 280 + 3 * 50 ticks
+    /// assert_eq!(io.net(Resource::Item(Titanium)), 1.26);
+    /// // no water under the pump
+    /// assert_eq!(io.net(Resource::Fluid(Water)), 0.0);
+    /// let io = s.ratios_on(Ground::floor(Type::DeepWater), true);
+    /// // the pump has the drill boosted (deep water has no ore, so it drills nothing)
+    /// assert_eq!(io.net(Resource::Fluid(Water)), 10.5 - 4.8);
+    /// ```
+    #[must_use]
+    pub fn ratios_on(&self, ground: Ground, boost: bool) -> Io {
+        let mut io = IoBuilder::default();
+        for p in self.blocks.iter().filter_map(|o| o.as_ref()) {
+            let size = p.block.get_size() as usize;
+            io += p
+                .block
+                .io_on(p.state.as_ref(), &vec![ground; size * size], boost);
+        }
+        io.into()
+    }
+
     /// create a new schematic, erroring if too big
     /// ```
     /// # use mindus::Schematic;
@@ -273,6 +313,28 @@ impl Schematic {
         Ok(self.blocks[x][y].as_mut())
     }
 
+    /// the block that covers this tile (blocks are stored at their center, but can be up to 6 tiles big)
+    #[cfg(feature = "lemu")]
+    pub(crate) fn covering(&self, x: usize, y: usize) -> Option<(GridPos, &Placement)> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        for cx in x.saturating_sub(3)..(x + 4).min(self.width) {
+            for cy in y.saturating_sub(3)..(y + 4).min(self.height) {
+                let Some(p) = &self.blocks[cx][cy] else {
+                    continue;
+                };
+                let sz = usize::from(p.block.get_size());
+                let off = (sz - 1) / 2;
+                if (cx - off..cx - off + sz).contains(&x) && (cy - off..cy - off + sz).contains(&y)
+                {
+                    return Some((GridPos(cx, cy), p));
+                }
+            }
+        }
+        None
+    }
+
     /// put a block in (same as [`Schematic::set`], but less arguments and builder-ness). panics!!!
     /// ```
     /// # use mindus::Schematic;
@@ -389,6 +451,125 @@ impl Schematic {
         }
         (cost, sandbox)
     }
+
+    /// move every block to a new `width`x`height` schematic.
+    /// `to` maps the (doubled, so that blocks of even size have a whole center) center of a block's footprint,
+    /// and `fix` fixes up the placement (rotation, relative configs).
+    fn transform(
+        &mut self,
+        width: usize,
+        height: usize,
+        to: impl Fn(i32, i32) -> (i32, i32),
+        fix: impl Fn(&mut Placement),
+    ) {
+        let mut blocks = Array2D::new(None, width, height);
+        for x in 0..self.width {
+            for y in 0..self.height {
+                let Some(mut p) = self.blocks[x][y].take() else {
+                    continue;
+                };
+                let size = i32::from(p.block.get_size());
+                let off = (size - 1) / 2;
+                let (cx, cy) = to(
+                    2 * (x as i32 - off) + size - 1,
+                    2 * (y as i32 - off) + size - 1,
+                );
+                fix(&mut p);
+                blocks[((cx - size + 1) / 2 + off) as usize]
+                    [((cy - size + 1) / 2 + off) as usize] = Some(p);
+            }
+        }
+        self.width = width;
+        self.height = height;
+        self.blocks = blocks;
+    }
+
+    /// rotate this schematic by 90 degrees, along with the blocks in it (and their links).
+    /// ```
+    /// # use mindus::Schematic;
+    /// # use mindus::block::{Rotation, CONVEYOR, DISTRIBUTOR};
+    /// let mut s = Schematic::new(4, 3);
+    /// s.put(0, 0, &DISTRIBUTOR);
+    /// s.set(3, 0, &CONVEYOR, mindus::data::dynamic::DynData::Empty, Rotation::Right).unwrap();
+    /// s.rotate(true);
+    /// assert_eq!((s.width, s.height), (3, 4));
+    /// assert_eq!(s.get(0, 2).unwrap().unwrap().block.name(), "distributor");
+    /// assert_eq!(s.get(0, 0).unwrap().unwrap().rot, Rotation::Down);
+    /// ```
+    pub fn rotate(&mut self, clockwise: bool) {
+        let (w, h) = (self.width as i32, self.height as i32);
+        self.transform(
+            self.height,
+            self.width,
+            |x, y| {
+                if clockwise {
+                    (y, 2 * (w - 1) - x)
+                } else {
+                    (2 * (h - 1) - y, x)
+                }
+            },
+            |p| {
+                p.rot = p.rot.rotated(clockwise);
+                if let Some(state) = &mut p.state {
+                    p.block.rotate_state(state, clockwise);
+                }
+            },
+        );
+    }
+
+    /// mirror this schematic, along with the blocks in it (and their links).
+    /// `horizontally` swaps left and right, `vertically` swaps up and down.
+    /// ```
+    /// # use mindus::Schematic;
+    /// # use mindus::block::{Rotation, CONVEYOR};
+    /// let mut s = Schematic::new(3, 1);
+    /// s.set(0, 0, &CONVEYOR, mindus::data::dynamic::DynData::Empty, Rotation::Right).unwrap();
+    /// s.mirror(true, false);
+    /// assert_eq!(s.get(2, 0).unwrap().unwrap().rot, Rotation::Left);
+    /// ```
+    pub fn mirror(&mut self, horizontally: bool, vertically: bool) {
+        let (w, h) = (self.width as i32, self.height as i32);
+        self.transform(
+            self.width,
+            self.height,
+            |x, y| {
+                (
+                    if horizontally { 2 * (w - 1) - x } else { x },
+                    if vertically { 2 * (h - 1) - y } else { y },
+                )
+            },
+            |p| {
+                p.rot = p.rot.mirrored(horizontally, vertically);
+                if let Some(state) = &mut p.state {
+                    p.block.mirror_state(state, horizontally, vertically);
+                }
+            },
+        );
+    }
+
+    /// shrink this schematic to the smallest size that still fits all its blocks.
+    /// empty schematics are left as they are.
+    /// ```
+    /// # use mindus::Schematic;
+    /// # use mindus::block::{ROUTER, DISTRIBUTOR};
+    /// let mut s = Schematic::new(10, 10);
+    /// s.put(3, 2, &ROUTER).put(5, 6, &DISTRIBUTOR);
+    /// s.crop_to_content();
+    /// assert_eq!((s.width, s.height), (4, 6));
+    /// assert_eq!(s.get(2, 4).unwrap().unwrap().block.name(), "distributor");
+    /// ```
+    pub fn crop_to_content(&mut self) {
+        let Some((x0, y0, x1, y1)) = self.block_iter().fold(None, |b, (GridPos(x, y), p)| {
+            let size = usize::from(p.block.get_size());
+            let (x, y) = (x - (size - 1) / 2, y - (size - 1) / 2);
+            let (x0, y0, x1, y1) = b.unwrap_or((x, y, x + size, y + size));
+            Some((x0.min(x), y0.min(y), x1.max(x + size), y1.max(y + size)))
+        }) else {
+            return;
+        };
+        let (dx, dy) = (2 * x0 as i32, 2 * y0 as i32);
+        self.transform(x1 - x0, y1 - y0, |x, y| (x - dx, y - dy), |_| {});
+    }
 }
 
 /// error created by creating a new schematic
@@ -773,4 +954,44 @@ mod test {
             pair!();
         ];
     }
+
+    #[test]
+    fn transform() {
+        use crate::block::*;
+        let mut s = Schematic::new(5, 2);
+        s.set(
+            0,
+            0,
+            &POWER_NODE,
+            DynData::Point2Array(vec![(2, 1)]),
+            Rotation::Up,
+        )
+        .unwrap();
+        s.put(2, 1, &ROUTER).put(3, 0, &DISTRIBUTOR);
+        let original = s.clone();
+        s.rotate(true);
+        assert_eq!((s.width, s.height), (2, 5));
+        let node = s.get(0, 4).unwrap().unwrap();
+        assert_eq!(format!("{:?}", node.get_state()), "Some(Power([(1, -2)]))");
+        assert_eq!(s.get(1, 2).unwrap().unwrap().block, &ROUTER);
+        // even sized blocks are stored at their bottom left
+        assert_eq!(s.get(0, 0).unwrap().unwrap().block, &DISTRIBUTOR);
+        for _ in 0..3 {
+            s.rotate(true);
+        }
+        assert_eq!(s, original);
+        s.rotate(false);
+        s.rotate(true);
+        assert_eq!(s, original);
+        s.mirror(true, true);
+        let node = s.get(4, 1).unwrap().unwrap();
+        assert_eq!(format!("{:?}", node.get_state()), "Some(Power([(-2, -1)]))");
+        assert_eq!(s.get(2, 0).unwrap().unwrap().block, &ROUTER);
+        s.mirror(true, true);
+        assert_eq!(s, original);
+        s.take(0, 0).unwrap();
+        s.crop_to_content();
+        assert_eq!((s.width, s.height), (3, 2));
+        assert_eq!(s.get(1, 0).unwrap().unwrap().block, &DISTRIBUTOR);
+    }
 }
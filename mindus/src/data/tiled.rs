@@ -0,0 +1,398 @@
+//! drawing big maps a band of rows at a time
+use std::io::Write;
+use std::ops::{Coroutine, CoroutineState::*};
+use std::pin::Pin;
+
+use super::map::{MapReader, Plan, ReadError, ThinBloc, ThinLinks, ThinMapData};
+use super::renderer::*;
+use crate::block::{content::Type, Rotation};
+use crate::team::DERELICT;
+use crate::unit::Unit;
+use fimg::uninit;
+
+/// a map, read for drawing in bands: the output is never held all at once.
+///
+/// only the blocks (not the buildings data) in and around the viewport are kept, which is a few bytes a tile;
+/// buildings centered elsewhere are skipped without being read, but for the links of bridges and power nodes.
+/// ```
+/// # use mindus::data::map::MapReader;
+/// # use mindus::data::renderer::RenderOptions;
+/// # use mindus::data::tiled::TiledMap;
+/// # use mindus::data::{DataRead, DataWrite, Serializable};
+/// # use mindus::Map;
+/// # let mut buff = DataWrite::default();
+/// # Map::filled(30, 20, mindus::block::content::Type::Stone).serialize(&mut buff).unwrap();
+/// # let bytes = buff.get_written();
+/// let mut r = MapReader::new(&mut DataRead::new(bytes)).unwrap();
+/// r.header().unwrap();
+/// r.version().unwrap();
+/// r.tags().unwrap();
+/// r.content().unwrap();
+/// let map = TiledMap::read(&mut r, &RenderOptions::default()).unwrap();
+/// assert_eq!(map.size(), (30 * 8, 20 * 8));
+/// let mut png = vec![];
+/// map.write_png(&mut png, 8).unwrap();
+/// ```
+pub struct TiledMap {
+    /// of the map
+    width: usize,
+    height: usize,
+    view: (usize, usize, usize, usize),
+    scale: Scale,
+    background: Background,
+    /// floors and ores in the viewport, from the bottom
+    floors: Vec<(Type, Type)>,
+    /// `(x, y, width, height)` that `blocks` holds; see [`RenderOptions::map_around`]
+    around: (usize, usize, usize, usize),
+    blocks: Vec<ThinTile>,
+    /// bridges and lasers, from anywhere on the map
+    links: ThinLinks,
+    plans: Vec<Plan>,
+    units: Vec<Unit>,
+    /// rows and columns of the whole render that make up the output, when it has to shrink
+    fit: Option<(Vec<usize>, Vec<usize>)>,
+}
+
+/// the rows that fimgs (nearest) scaling picks from `from` rows to make `to`
+fn nearest_rows(from: u32, to: u32) -> Vec<usize> {
+    let step = f64::from(from) / f64::from(to);
+    let mut at = step * 0.5;
+    (0..to)
+        .map(|_| {
+            let y = (at as usize).min(from as usize - 1);
+            at += step;
+            y
+        })
+        .collect()
+}
+
+/// the columns that fimgs (nearest) scaling picks from `from` columns to make `to`
+fn nearest_columns(from: u32, to: u32) -> Vec<usize> {
+    let step = f64::from(from) / f64::from(to);
+    (0..to)
+        .map(|x| ((step * 0.5 + step * f64::from(x)) as usize).min(from as usize - 1))
+        .collect()
+}
+
+impl TiledMap {
+    /// reads the map section and the entities.
+    ///
+    /// Reader must have read to the map section.
+    pub fn read(map: &mut MapReader, opts: &RenderOptions) -> Result<Self, ReadError> {
+        let mut co = map.thin_map_within(|w, h| opts.map_around(w, h))?;
+        let (w, h) = match Pin::new(&mut co).resume(()) {
+            Yielded(ThinMapData::Init { width, height }) => (width as usize, height as usize),
+            Complete(Err(x)) => return Err(x),
+            _ => unreachable!(),
+        };
+        let (view @ (vx, vy, vw, vh), scale) = opts.map_view(w, h);
+        let (ax, ay, aw, ah) = opts.map_around(w, h);
+        let (aw, ah) = (aw.min(w - ax), ah.min(h - ay));
+        let floored = matches!(opts.background, Background::Floor);
+        let mut floors = Vec::with_capacity(if floored { vw * vh } else { 0 });
+        for y in 0..h {
+            for x in 0..w {
+                let (floor, ore) = match Pin::new(&mut co).resume(()) {
+                    Yielded(ThinMapData::Tile { floor, ore }) => (floor, ore),
+                    Complete(Err(x)) => return Err(x),
+                    _ => unreachable!(),
+                };
+                if floored && (vx..vx + vw).contains(&x) && (vy..vy + vh).contains(&y) {
+                    floors.push((floor, ore));
+                }
+            }
+        }
+        let mut blocks = vec![None; aw * ah];
+        let mut put = |i: usize, tile: ThinTile| {
+            let (x, y) = (i % w, i / w);
+            if (ax..ax + aw).contains(&x) && (ay..ay + ah).contains(&y) {
+                blocks[(y - ay) * aw + x - ax] = tile;
+            }
+        };
+        let mut i = 0;
+        while i < w * h {
+            match Pin::new(&mut co).resume(()) {
+                Yielded(ThinMapData::Bloc(ThinBloc::None(n))) => i += n as usize + 1,
+                Yielded(ThinMapData::Bloc(ThinBloc::Build(r, bloc, team))) => {
+                    put(i, Some((bloc, r, team)));
+                    i += 1;
+                }
+                Yielded(ThinMapData::Bloc(ThinBloc::Many(bloc, n))) => {
+                    for i in i..(i + n as usize + 1).min(w * h) {
+                        put(i, Some((bloc, Rotation::Up, DERELICT)));
+                    }
+                    i += n as usize + 1;
+                }
+                Complete(Err(x)) => return Err(x),
+                x => unreachable!("{x:?}"),
+            }
+        }
+        match Pin::new(&mut co).resume(()) {
+            Complete(Ok(())) => (),
+            Complete(Err(x)) => return Err(x),
+            f => unreachable!("{f:?}"),
+        };
+        drop(co);
+        let links = map.take_thin_links();
+        let (plans, units) = map.collect_entities_and_plans()?;
+        let (pw, ph) = (scale * vw as u32, scale * vh as u32);
+        Ok(Self {
+            width: w,
+            height: h,
+            view,
+            scale,
+            background: opts.background,
+            floors,
+            around: (ax, ay, aw, ah),
+            blocks,
+            links,
+            plans: plans.into_iter().flat_map(|(_, p)| p).collect(),
+            units,
+            fit: opts
+                .fitted(pw, ph)
+                .map(|(w, h)| (nearest_rows(ph, h), nearest_columns(pw, w))),
+        })
+    }
+
+    /// the size of the output, in pixels
+    #[must_use]
+    pub fn size(&self) -> (u32, u32) {
+        match &self.fit {
+            Some((rows, columns)) => (columns.len() as u32, rows.len() as u32),
+            None => (
+                self.scale * self.view.2 as u32,
+                self.scale * self.view.3 as u32,
+            ),
+        }
+    }
+
+    /// the block at `(x, y)`, if it was kept
+    fn block(&self, x: usize, y: usize) -> ThinTile {
+        let (ax, ay, aw, ah) = self.around;
+        let (x, y) = (x.checked_sub(ax)?, y.checked_sub(ay)?);
+        if x >= aw || y >= ah {
+            return None;
+        }
+        self.blocks[y * aw + x]
+    }
+
+    /// draws `n` rows of tiles, from row `top` of the viewport (counting from the top)
+    fn draw(&self, top: usize, n: usize) -> Image<Vec<u8>, 3> {
+        let (vx, vy, vw, vh) = self.view;
+        let scale = self.scale;
+        let (pw, ph) = (scale * vw as u32, scale * n as u32);
+        let mut img = match self.background {
+            Background::Floor => {
+                let mut img =
+                    uninit::Image::<_, 3>::new(pw.try_into().unwrap(), ph.try_into().unwrap());
+                for row in 0..n {
+                    let y = vh - 1 - (top + row);
+                    for x in 0..vw {
+                        let (floor, ore) = self.floors[y * vw + x];
+                        let (px, py) = (scale * x as u32, scale * row as u32);
+                        unsafe { img.overlay_at(&crate::data::map::floor(floor, scale), px, py) };
+                        if ore != Type::Air {
+                            unsafe { img.overlay_at(&crate::data::map::ore(ore, scale), px, py) };
+                        }
+                    }
+                }
+                unsafe { img.assume_init() }
+            }
+            Background::Color(r, g, b) => solid(pw, ph, (r, g, b)),
+        };
+        // the map rows in this band, and the buildings that reach them
+        let (hi, lo) = (vy + vh - 1 - top, vy + vh - top - n);
+        let above = self.height - vy - vh;
+        let (left, top_px) = (
+            i64::from(scale * vx as u32),
+            i64::from(scale * (above + top) as u32),
+        );
+        let get = |x, y| self.block(x, y).map(|(b, r, _)| (b, r));
+        let builds = (lo.saturating_sub(4).max(vy.saturating_sub(4))..(hi + 5).min(self.height))
+            .flat_map(|y| {
+                (vx.saturating_sub(4)..(vx + vw + 4).min(self.width))
+                    .filter_map(move |x| Some(((x, y), self.block(x, y)?)))
+            })
+            .collect::<Vec<_>>();
+        for (sprite, (x, y)) in par::map(&builds, |&(at, tile)| {
+            thin_block_sprite(tile, at, (self.width, self.height), &get, scale)
+        }) {
+            overlay_clipped(&mut img.as_mut(), sprite.borrow(), x - left, y - top_px);
+        }
+        draw_links(
+            &mut img.as_mut(),
+            self.links
+                .links
+                .iter()
+                .map(|(pos, block, link, links, status)| {
+                    (*pos, *block, *link, &links[..], *status)
+                }),
+            (self.width, self.height),
+            (vx, lo, vw, n),
+            Frame {
+                height: vy + vh - top,
+                border: 0,
+                left: vx,
+                scale,
+            },
+            |to| self.links.size(to, self.width),
+        );
+        for plan in &self.plans {
+            draw_plan_in(img.as_mut(), plan, self.view, scale, scale * top as u32);
+        }
+        let px = i64::from(scale.px());
+        for unit in &self.units {
+            let (x, y) = (
+                i64::from(unit.state.position.0 as u32) - vx as i64,
+                (vy + vh) as i64 - i64::from(unit.state.position.1 as u32) - 1 - top as i64,
+            );
+            overlay_clipped(&mut img.as_mut(), unit.draw(scale).borrow(), px * x, px * y);
+        }
+        img
+    }
+
+    /// the output, `rows` rows of tiles at a time (top first).
+    /// after shrinking, a band may be fewer pixels tall (or empty, and skipped).
+    pub fn bands(&self, rows: usize) -> impl Iterator<Item = Image<Vec<u8>, 3>> + '_ {
+        let rows = rows.max(1);
+        let vh = self.view.3;
+        (0..vh).step_by(rows).filter_map(move |top| {
+            let n = rows.min(vh - top);
+            let img = self.draw(top, n);
+            let Some((ys, xs)) = &self.fit else {
+                return Some(img);
+            };
+            let from = (self.scale * top as u32) as usize;
+            let to = from + img.height() as usize;
+            let ys = &ys[ys.partition_point(|&y| y < from)..ys.partition_point(|&y| y < to)];
+            if ys.is_empty() {
+                return None;
+            }
+            let stride = img.width() as usize * 3;
+            let mut out = Vec::with_capacity(ys.len() * xs.len() * 3);
+            for &y in ys {
+                let row = &img.buffer()[(y - from) * stride..][..stride];
+                for &x in xs {
+                    out.extend_from_slice(&row[x * 3..x * 3 + 3]);
+                }
+            }
+            Some(Image::build(xs.len() as u32, ys.len() as u32).buf(out))
+        })
+    }
+
+    /// encodes the output as a png, drawing `rows` rows of tiles at a time.
+    pub fn write_png(&self, out: impl Write, rows: usize) -> Result<(), png::EncodingError> {
+        let (w, h) = self.size();
+        let mut encoder = png::Encoder::new(out, w, h);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        let mut stream = writer.stream_writer()?;
+        for band in self.bands(rows) {
+            stream.write_all(band.buffer())?;
+        }
+        stream.finish()?;
+        writer.finish()
+    }
+}
+
+#[test]
+fn same_as_single() {
+    use crate::block::{
+        BRIDGE_CONVEYOR, CONVEYOR, CORE_SHARD, POWER_NODE, STONE_WALL, THORIUM_WALL_LARGE,
+    };
+    use crate::data::dynamic::DynData;
+    use crate::data::map::{Extra, Power};
+    use crate::data::{DataRead, DataWrite, GridPos, Serializable};
+    use crate::team;
+    use crate::Map;
+    let mut m = Map::filled(12, 9, Type::Stone);
+    m.set_ore(3, 4, Type::OreCopper).unwrap();
+    m.set_block(7, 4, &CORE_SHARD, Rotation::Up, team::CRUX)
+        .unwrap();
+    m.set_block(10, 7, &THORIUM_WALL_LARGE, Rotation::Up, team::SHARDED)
+        .unwrap();
+    for (x, y, r) in [
+        (0, 3, Rotation::Right),
+        (1, 3, Rotation::Up),
+        (1, 4, Rotation::Up),
+        (1, 5, Rotation::Right),
+    ] {
+        m.set_block(x, y, &CONVEYOR, r, team::SHARDED).unwrap();
+    }
+    for x in 0..5 {
+        m.set_block(x, 0, &STONE_WALL, Rotation::Up, team::DERELICT)
+            .unwrap();
+    }
+    m.set_block(3, 7, &BRIDGE_CONVEYOR, Rotation::Up, team::SHARDED)
+        .unwrap()
+        .extra = Extra::linked(&BRIDGE_CONVEYOR, GridPos(6, 7));
+    m.set_block(6, 7, &BRIDGE_CONVEYOR, Rotation::Up, team::SHARDED)
+        .unwrap();
+    m.set_block(5, 1, &POWER_NODE, Rotation::Up, team::SHARDED)
+        .unwrap()
+        .power = Some(Power {
+        links: vec![GridPos(7, 4)],
+        status: 0.5,
+    });
+    m.plans.push((
+        team::SHARDED,
+        vec![Plan {
+            position: GridPos(4, 6),
+            rotation: Rotation::Up,
+            block: &CORE_SHARD,
+            config: DynData::Empty,
+        }],
+    ));
+    let mut buff = DataWrite::default();
+    m.serialize(&mut buff).unwrap();
+    let reader = || {
+        let mut r = MapReader::new(&mut DataRead::new(buff.get_written())).unwrap();
+        r.header().unwrap();
+        r.version().unwrap();
+        r.tags().unwrap();
+        r.content().unwrap();
+        r
+    };
+    for opts in [
+        RenderOptions::default(),
+        RenderOptions {
+            scale: Some(Scale::Half),
+            viewport: Some((2, 1, 7, 5)),
+            background: Background::Color(0, 0, 255),
+            ..Default::default()
+        },
+        RenderOptions {
+            viewport: Some((5, 2, 100, 100)),
+            max_size: Some(21),
+            ..Default::default()
+        },
+        RenderOptions {
+            max_size: Some(50),
+            ..Default::default()
+        },
+    ] {
+        let mut r = reader();
+        let (mut img, size) = draw_map_single_with(&mut r, &opts).unwrap();
+        draw_units_with(&mut r, img.as_mut(), size, &opts).unwrap();
+        let img = Image::build(img.width(), img.height()).buf(img.take_buffer().into_vec());
+        let expected = opts.fit(img);
+
+        let tiled = TiledMap::read(&mut reader(), &opts).unwrap();
+        assert_eq!(tiled.size(), (expected.width(), expected.height()));
+        for rows in [1, 2, 3, 100] {
+            let out = tiled
+                .bands(rows)
+                .flat_map(|band| band.take_buffer())
+                .collect::<Vec<_>>();
+            assert!(out == **expected.buffer(), "{opts:?} in bands of {rows}");
+        }
+
+        let mut png = vec![];
+        tiled.write_png(&mut png, 2).unwrap();
+        let mut decoder = png::Decoder::new(&png[..]).read_info().unwrap();
+        let mut out = vec![0; decoder.output_buffer_size()];
+        decoder.next_frame(&mut out).unwrap();
+        assert!(out == **expected.buffer());
+    }
+}
@@ -0,0 +1,151 @@
+//! check the code in a schematic's processors, with [`lemu`].
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+use thiserror::Error;
+
+use crate::block::logic::{ProcessorLink, ProcessorState};
+use crate::block::BLOCK_REGISTRY;
+use crate::data::schematic::Schematic;
+use crate::data::GridPos;
+
+/// a problem with the code of a processor.
+#[derive(Debug, Error)]
+pub enum Diagnostic<'s> {
+    /// the code does not parse.
+    #[error(transparent)]
+    Parse(lemu::Error<'s>),
+    /// the code uses a link that the processor does not have.
+    #[error("unknown link {name}")]
+    UnknownLink { name: &'s str, span: Range<usize> },
+    /// the code uses a link that does not point at a block.
+    #[error("link {} points at nothing", link.get_name())]
+    DanglingLink {
+        link: &'s ProcessorLink,
+        span: Range<usize>,
+    },
+}
+
+impl Diagnostic<'_> {
+    /// where in the code this is.
+    #[must_use]
+    pub fn span(&self) -> Option<Range<usize>> {
+        match self {
+            Self::Parse(e) => e.span().cloned(),
+            Self::UnknownLink { span, .. } | Self::DanglingLink { span, .. } => Some(span.clone()),
+        }
+    }
+}
+
+/// the name the game gives links to this block, without the number. (`LogicBlock.getLinkName`)
+fn link_prefix(block: &str) -> &str {
+    let mut split = block.rsplit('-');
+    let last = split.next().unwrap_or(block);
+    match split.next() {
+        Some(before) if last == "large" || last.parse::<f32>().is_ok() => before,
+        _ => last,
+    }
+}
+
+/// the words of `code` (outside of strings and comments), and where they are.
+fn words(code: &str) -> Vec<(&str, Range<usize>)> {
+    let mut words = vec![];
+    let mut start = None;
+    let (mut string, mut comment) = (false, false);
+    for (i, c) in code
+        .char_indices()
+        .chain(std::iter::once((code.len(), '\n')))
+    {
+        if c == '\n' {
+            (string, comment) = (false, false);
+        } else if comment || (string && c != '"') {
+            continue;
+        }
+        if matches!(c, '\n' | ' ' | '\t' | '\r' | ';' | '"' | '#') {
+            if let Some(s) = start.take() {
+                words.push((&code[s..i], s..i));
+            }
+            match c {
+                '"' => string = !string,
+                '#' => comment = true,
+                _ => {}
+            }
+        } else if start.is_none() {
+            start = Some(i);
+        }
+    }
+    words
+}
+
+impl Schematic {
+    /// check the code of every processor: that it parses, and that the links it uses exist and point at blocks.
+    ///
+    /// only processors with problems are returned.
+    /// ```
+    /// # use mindus::Schematic;
+    /// # use mindus::block::{MICRO_PROCESSOR, SWITCH};
+    /// # use mindus::block::logic::ProcessorState;
+    /// # use mindus::data::{GridPos, validate::Diagnostic};
+    /// let mut s = Schematic::new(3, 1);
+    /// s.put(0, 0, &MICRO_PROCESSOR).put(1, 0, &SWITCH);
+    /// let state = s.get_mut(0, 0).unwrap().unwrap().get_state_mut().unwrap();
+    /// let state = state.downcast_mut::<ProcessorState>().unwrap();
+    /// state.set_code("sensor s switch1 @enabled\ncontrol enabled switch2 s\nset\n".into()).unwrap();
+    /// state.create_link("switch1".to_string(), 1, 0).unwrap();
+    /// state.create_link("switch2".to_string(), 2, 0).unwrap();
+    /// let problems = s.validate_processors();
+    /// let [Diagnostic::Parse(..), Diagnostic::DanglingLink { link, .. }] = &problems[&GridPos(0, 0)][..] else {
+    ///     panic!()
+    /// };
+    /// assert_eq!(link.get_name(), "switch2");
+    /// ```
+    #[must_use]
+    pub fn validate_processors(&self) -> HashMap<GridPos, Vec<Diagnostic<'_>>> {
+        let prefixes = BLOCK_REGISTRY
+            .keys()
+            .map(|name| link_prefix(name))
+            .collect::<HashSet<_>>();
+        let mut found = HashMap::new();
+        for (pos, p) in self.block_iter() {
+            let Some(state) = p
+                .get_state()
+                .and_then(|s| s.downcast_ref::<ProcessorState>())
+            else {
+                continue;
+            };
+            let code = state.get_code();
+            let mut problems = match lemu::Executor::<std::io::Sink>::build().program(code) {
+                Ok(_) => vec![],
+                // displays are links, and get checked below
+                Err(errors) => errors
+                    .into_iter()
+                    .filter(|e| !matches!(e, lemu::Error::NoDisplay(..)))
+                    .map(Diagnostic::Parse)
+                    .collect(),
+            };
+            for (word, span) in words(code) {
+                let number = word.trim_start_matches(|c: char| !c.is_ascii_digit());
+                let prefix = &word[..word.len() - number.len()];
+                if number.is_empty()
+                    || !number.bytes().all(|b| b.is_ascii_digit())
+                    || !prefixes.contains(prefix)
+                {
+                    continue;
+                }
+                let Some(link) = state.get_links().iter().find(|l| l.get_name() == word) else {
+                    problems.push(Diagnostic::UnknownLink { name: word, span });
+                    continue;
+                };
+                let (dx, dy) = link.get_pos();
+                let x = pos.0.checked_add_signed(dx as isize);
+                let y = pos.1.checked_add_signed(dy as isize);
+                if x.zip(y).and_then(|(x, y)| self.covering(x, y)).is_none() {
+                    problems.push(Diagnostic::DanglingLink { link, span });
+                }
+            }
+            if !problems.is_empty() {
+                found.insert(pos, problems);
+            }
+        }
+        found
+    }
+}
@@ -1,4 +1,5 @@
 use mindus::data::map::MapReader;
+use mindus::data::renderer::RenderOptions;
 use mindus::data::DataRead;
 use std::env::Args;
 
@@ -15,9 +16,10 @@ pub fn main(args: Args) {
             m.version()?;
             let t = m.tags()?;
             println!("rendering {}", t["name"]);
-            m.skip()?;
-            let (mut img, sz) = mindus::data::renderer::draw_map_single(&mut m)?;
-            mindus::data::renderer::draw_units(&mut m, img.as_mut(), sz)?;
+            m.content()?;
+            let opts = RenderOptions::default();
+            let (mut img, sz) = mindus::data::renderer::draw_map_single_with(&mut m, &opts)?;
+            mindus::data::renderer::draw_units_with(&mut m, img.as_mut(), sz, &opts)?;
             Ok::<_, mindus::data::map::ReadError>(img)
         })() {
             Err(e) => print_err!(e, "fail"),
@@ -29,3 +29,21 @@ color_content_enum! {
         "dormant-cyst": "df824d",
     }
 }
+
+impl Type {
+    /// how hard this is to drill. drills can mine what is at most their tier.
+    /// ```
+    /// assert_eq!(mindus::item::Type::Titanium.hardness(), 3);
+    /// ```
+    #[must_use]
+    pub const fn hardness(self) -> u8 {
+        match self {
+            Self::Copper | Self::Lead | Self::Graphite => 1,
+            Self::Coal => 2,
+            Self::Titanium | Self::Beryllium => 3,
+            Self::Thorium => 4,
+            Self::Tungsten => 5,
+            _ => 0,
+        }
+    }
+}
@@ -27,7 +27,7 @@ pub mod data;
 pub mod fluid;
 pub mod item;
 pub mod modifier;
-mod team;
+pub mod team;
 pub mod unit;
 mod utils;
 #[doc(inline)]
@@ -6,6 +6,7 @@ use crate::content::content_enum;
 use crate::data::command::UnitCommand;
 use crate::data::dynamic::DynData;
 use crate::data::entity_mapping::UnitClass;
+use crate::data::map::ContentMap;
 use crate::data::{DataRead, ReadError};
 use crate::item::Type as Item;
 use crate::modifier::Type as Status;
@@ -99,7 +100,7 @@ units! {
     "scathe-missile",
 }
 
-#[derive(Default, Debug)]
+#[derive(Clone, Default, Debug)]
 pub struct UnitState {
     pub ammo: f32,
     pub elevation: f32,
@@ -117,7 +118,7 @@ pub struct UnitState {
     pub controller: Controller,
 }
 
-#[derive(Default, Debug)]
+#[derive(Clone, Default, Debug)]
 pub enum Controller {
     Player(i32),
     Logic(i32),
@@ -185,14 +186,37 @@ impl Controller {
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone)]
 pub struct Unit {
     pub state: UnitState,
     pub ty: Type,
+    /// the units chunk, as read (this is what gets written back)
+    pub(crate) raw: Box<[u8]>,
+}
+
+impl std::fmt::Debug for Unit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Unit")
+            .field("state", &self.state)
+            .field("ty", &self.ty)
+            .finish_non_exhaustive()
+    }
 }
 
 impl UnitClass {
+    /// read a unit, with our content ids
     pub fn read(self, buff: &mut DataRead) -> Result<Unit, ReadError> {
+        self.read_mapped(buff, &ContentMap::default())?
+            .ok_or(ReadError::Expected("unit type"))
+    }
+
+    /// [`UnitClass::read`], with content ids mapped through the maps [`ContentMap`].
+    /// units of types we dont know are [`None`].
+    pub fn read_mapped(
+        self,
+        buff: &mut DataRead,
+        content: &ContentMap,
+    ) -> Result<Option<Unit>, ReadError> {
         buff.skip(2)?;
         let mut state = UnitState::default();
         read_abilities(buff)?;
@@ -235,7 +259,7 @@ impl UnitClass {
                     // recursion more!
                     // this is unreliable, as read_payload may not read the full block.
                     // if read_plans reports a error, with a payload unit, this is why
-                    let _ = read_payload(buff);
+                    let _ = read_payload(buff, content);
                 }
             }
             Self::Bomb => {
@@ -259,7 +283,7 @@ impl UnitClass {
                 read_mounts(buff)?;
                 for _ in 0..buff.read_i32()? {
                     // recursion more!
-                    read_payload(buff)?;
+                    read_payload(buff, content)?;
                 }
             }
         }
@@ -267,20 +291,24 @@ impl UnitClass {
         state.rotation = buff.read_f32()?;
         state.shield = buff.read_f32()?;
         buff.skip(1)?; // spawned_by_core
-        state.stack = read_stack(buff)?;
+        state.stack = read_stack(buff, content)?;
         state.status = read_status(buff)?;
         state.team = Team::of(buff.read_u8()?);
         if self == Self::Bomb {
             buff.skip(4)?; // time
         }
-        let ty = Type::try_from(buff.read_u16()?).unwrap();
+        let ty = content.unit(buff.read_u16()?);
         buff.skip(1)?; // update_building
         state.velocity = (buff.read_f32()?, buff.read_f32()?);
         state.position = (
             (buff.read_f32()? / 8.0).floor(),
             (buff.read_f32()? / 8.0).floor(),
         );
-        Ok(Unit { state, ty })
+        Ok(ty.map(|ty| Unit {
+            state,
+            ty,
+            raw: Box::default(),
+        }))
     }
 }
 
@@ -345,10 +373,10 @@ fn read_plan(buff: &mut DataRead) -> Result<(), ReadError> {
 /// format:
 /// - item: [`i16`] attempt into [`Item`]
 /// - count: [`u32`]
-fn read_stack(buff: &mut DataRead) -> Result<(Option<Item>, u32), ReadError> {
+fn read_stack(buff: &mut DataRead, content: &ContentMap) -> Result<(Option<Item>, u32), ReadError> {
     let n = buff.read_i16()?;
     Ok((
-        (n != -1).then(|| Item::try_from(n as u16).unwrap()),
+        (n != -1).then(|| content.item(n as u16)).flatten(),
         buff.read_u32()?,
     ))
 }
@@ -376,7 +404,9 @@ fn read_status(buff: &mut DataRead) -> Result<[Status; 3], ReadError> {
 
 impl Unit {
     #[inline]
+    /// draw this unit, in its teams colors
     pub fn draw(&self, s: crate::data::renderer::Scale) -> ImageHolder<4> {
-        self.ty.draw(s)
+        use crate::content::Content;
+        crate::data::renderer::team_colored(self.ty.draw(s), self.ty.get_name(), self.state.team, s)
     }
 }
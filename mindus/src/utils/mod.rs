@@ -1,3 +1,4 @@
 pub mod array;
 pub mod image;
+pub mod par;
 pub use self::image::{Image, ImageHolder, ImageUtils, Overlay, OverlayAt};
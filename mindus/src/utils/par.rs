@@ -0,0 +1,42 @@
+//! spreading work over threads (with the `parallel` feature)
+
+/// how many items a thread gets, at least
+#[cfg(feature = "parallel")]
+const MIN_CHUNK: usize = 16;
+
+#[cfg(all(test, feature = "parallel"))]
+thread_local! {
+    /// how many threads [`map`] uses, when called from this thread
+    pub static THREADS: std::cell::Cell<Option<usize>> = const { std::cell::Cell::new(None) };
+}
+
+#[cfg(feature = "parallel")]
+fn threads() -> usize {
+    #[cfg(test)]
+    if let Some(n) = THREADS.with(std::cell::Cell::get) {
+        return n;
+    }
+    std::thread::available_parallelism().map_or(1, usize::from)
+}
+
+/// `f` of every item, in order. with the `parallel` feature, the items are split between threads.
+pub fn map<T: Sync, U: Send>(items: &[T], f: impl Fn(&T) -> U + Sync) -> Vec<U> {
+    #[cfg(feature = "parallel")]
+    {
+        let chunk = items.len().div_ceil(threads()).max(MIN_CHUNK);
+        if chunk < items.len() {
+            return std::thread::scope(|s| {
+                let f = &f;
+                let handles = items
+                    .chunks(chunk)
+                    .map(|items| s.spawn(move || items.iter().map(f).collect::<Vec<_>>()))
+                    .collect::<Vec<_>>();
+                handles
+                    .into_iter()
+                    .flat_map(|h| h.join().unwrap())
+                    .collect()
+            });
+        }
+    }
+    items.iter().map(f).collect()
+}